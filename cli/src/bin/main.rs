@@ -1,15 +1,36 @@
-use std::{process::exit, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    process::exit,
+    str::FromStr,
+    sync::Arc,
+};
 
 use anyhow::anyhow;
 use clap::{Parser, Subcommand};
 use jito_stake_pool_cli::{
+    client::AccountSource,
     command::{
         add_validator::{AddValidatorArgs, command_vsa_add},
+        audit::{AuditArgs, command_audit},
+        batch::{BatchArgs, command_batch, read_batch_file},
+        crank::{Backoff, CrankArgs, command_crank},
+        decrease_validator_stake::{DecreaseValidatorStakeArgs, command_decrease_validator_stake},
         deposit_sol::{DepositSolArgs, command_deposit_sol},
         increase_validator_stake::{IncreaseValidatorStakeArgs, command_increase_validator_stake},
+        inspect_tx::{InspectTxArgs, command_inspect_tx},
+        list::{ListArgs, command_list},
+        metrics::{MetricsArgs, command_metrics},
+        quote::{QuoteArgs, command_quote},
+        rebalance::{RebalanceArgs, command_rebalance},
+        stats::{StatsArgs, command_stats},
+        update::{UpdateArgs, command_update},
+        withdraw_sol::{WithdrawSolArgs, command_withdraw_sol},
     },
-    config::JitoStakePoolCliConfig,
+    config::{JitoStakePoolCliConfig, OutputFormat},
+    profile::Profiles,
+    snapshot::{RecordingAccounts, SnapshotAccounts},
 };
+use jito_stake_pool_sdk::layout::LayoutVersion;
 use solana_client::rpc_client::RpcClient;
 // use solana_program::{
 //     borsh::{get_instance_packed_len, get_packed_len},
@@ -45,6 +66,10 @@ struct Cli {
     #[arg(short = 'C', long, global = true, value_name = "PATH")]
     config_file: Option<String>,
 
+    /// Pool profiles file to use. [default: stake-pool-profiles.yml next to the configuration file]
+    #[arg(long, global = true, value_name = "PATH")]
+    profiles: Option<String>,
+
     /// Show additional information
     #[arg(long, short = 'v', global = true)]
     verbose: bool,
@@ -61,6 +86,27 @@ struct Cli {
     #[arg(long = "no-update", global = true)]
     no_update: bool,
 
+    /// Commitment level used for queries and transaction confirmation
+    #[arg(long, global = true, value_enum, default_value = "confirmed")]
+    commitment: CommitmentArg,
+
+    /// Number of times a failed or expired transaction is re-signed and sent again
+    #[arg(long = "max-retries", global = true, default_value_t = 5)]
+    max_retries: usize,
+
+    /// Read accounts from a snapshot directory instead of the cluster
+    #[arg(long = "snapshot-in", global = true, value_name = "DIR")]
+    snapshot_in: Option<PathBuf>,
+
+    /// Layout of the stake pool program version, 0.6.4 or 2.x. Default from the
+    /// profile, otherwise detected from the account contents
+    #[arg(long, global = true, value_name = "VERSION")]
+    layout: Option<LayoutVersion>,
+
+    /// Write every account the command reads to a snapshot directory
+    #[arg(long = "snapshot-out", global = true, value_name = "DIR")]
+    snapshot_out: Option<PathBuf>,
+
     /// JSON RPC URL for the cluster. Default from the configuration file.
     #[arg(long = "url", value_name = "URL")]
     json_rpc_url: Option<String>,
@@ -95,6 +141,13 @@ enum OutputFormatArg {
     JsonCompact,
 }
 
+#[derive(clap::ValueEnum, Clone)]
+enum CommitmentArg {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     // Create a new stake pool
@@ -107,8 +160,8 @@ enum Commands {
     //
     /// Increase stake to a validator, drawing from the stake pool reserve. Must be signed by the pool staker.
     IncreaseValidatorStake(IncreaseValidatorStakeArgs),
-    // Decrease stake to a validator, splitting from the active stake. Must be signed by the pool staker.
-    // DecreaseValidatorStake(DecreaseValidatorStakeArgs),
+    /// Decrease stake to a validator, splitting from the active stake. Must be signed by the pool staker.
+    DecreaseValidatorStake(DecreaseValidatorStakeArgs),
     // /// Set the preferred validator for deposits or withdrawals. Must be signed by the pool staker.
     // SetPreferredValidator(SetPreferredValidatorArgs),
     // /// Deposit active stake account into the stake pool in exchange for pool tokens
@@ -117,14 +170,30 @@ enum Commands {
     // DepositAllStake(DepositAllStakeArgs),
     /// Deposit SOL into the stake pool in exchange for pool tokens
     DepositSol(DepositSolArgs),
-    // /// List stake accounts managed by this pool
-    // List(ListArgs),
-    // /// Updates all balances in the pool after validator stake accounts receive rewards.
-    // Update(UpdateArgs),
+    /// Withdraw SOL from the stake pool's reserve in exchange for pool tokens
+    WithdrawSol(WithdrawSolArgs),
+    /// Validate and run a file of add-validator, increase and decrease operations
+    Batch(BatchArgs),
+    /// Move stake between validators towards target shares. Must be signed by the pool staker.
+    Rebalance(RebalanceArgs),
+    /// Keep running and update the stake pool as soon as every new epoch starts
+    Crank(CrankArgs),
+    /// Serve Prometheus metrics about the stake pool on `/metrics`
+    Metrics(MetricsArgs),
+    /// Recompute the pool balances from the chain and report any inconsistency as JSON
+    Audit(AuditArgs),
+    /// Show the pool tokens or SOL received for a deposit or withdrawal, with every fee, without sending anything
+    Quote(QuoteArgs),
+    /// Show the exchange rate and APY of the pool, and their history with `--history`
+    Stats(StatsArgs),
+    /// Decode the stake pool instructions of a transaction and the balances they moved
+    InspectTx(InspectTxArgs),
+    /// Updates all balances in the pool after validator stake accounts receive rewards
+    Update(UpdateArgs),
+    /// List stake accounts managed by this pool
+    List(ListArgs),
     // /// Withdraw active stake from the stake pool in exchange for pool tokens
     // WithdrawStake(WithdrawStakeArgs),
-    // /// Withdraw SOL from the stake pool's reserve in exchange for pool tokens
-    // WithdrawSol(WithdrawSolArgs),
     // /// Change manager or fee receiver account for the stake pool. Must be signed by the current manager.
     // SetManager(SetManagerArgs),
     // /// Change staker account for the stake pool. Must be signed by the manager or current staker.
@@ -148,6 +217,30 @@ enum Commands {
 //     };
 // }
 
+impl Commands {
+    /// Pool address or profile name the command operates on
+    fn pool(&self) -> Option<&str> {
+        match self {
+            Commands::AddValidator(args) => Some(&args.pool),
+            Commands::IncreaseValidatorStake(args) => Some(&args.pool),
+            Commands::DecreaseValidatorStake(args) => Some(&args.pool),
+            Commands::DepositSol(args) => Some(&args.pool),
+            Commands::WithdrawSol(args) => Some(&args.pool),
+            // The batch file names the pool, see `main`
+            Commands::Batch(_) => None,
+            Commands::Rebalance(args) => Some(&args.pool),
+            Commands::Crank(args) => Some(&args.pool),
+            Commands::Metrics(args) => Some(&args.pool),
+            Commands::Audit(args) => Some(&args.pool),
+            Commands::Quote(args) => Some(&args.pool),
+            Commands::Stats(args) => Some(&args.pool),
+            Commands::InspectTx(args) => args.pool.as_deref(),
+            Commands::Update(args) => Some(&args.pool),
+            Commands::List(args) => Some(&args.pool),
+        }
+    }
+}
+
 // Helper function to parse pubkey from string
 fn parse_pubkey(s: &str) -> anyhow::Result<Pubkey> {
     Pubkey::from_str(s).map_err(|e| e.into())
@@ -191,23 +284,44 @@ fn main() -> anyhow::Result<()> {
         solana_cli_config::Config::default()
     };
 
+    let profiles = match &cli.profiles {
+        Some(path) => Profiles::load(Path::new(path))?,
+        None => Profiles::load_default(cli.config_file.as_deref())?,
+    };
+    let pool = match &cli.command {
+        Commands::Batch(args) => read_batch_file(Path::new(&args.file))?
+            .first()
+            .map(|operation| operation.pool().to_string()),
+        command => command.pool().map(str::to_string),
+    };
+    let profile = pool
+        .and_then(|pool| profiles.get(&pool).cloned())
+        .unwrap_or_default();
+
     let config = {
         let json_rpc_url = cli
             .json_rpc_url
+            .or(profile.url.clone())
             .unwrap_or_else(|| cli_config.json_rpc_url.clone());
 
+        let program_id = profile.program_id()?.unwrap_or(spl_stake_pool::id());
+        let layout = cli
+            .layout
+            .map_or_else(|| profile.layout(), |layout| Ok(Some(layout)))?;
+
         let staker = get_signer_simple(
-            cli.staker.as_deref(),
+            cli.staker.as_deref().or(profile.staker.as_deref()),
             &cli_config.keypair_path,
             // &mut wallet_manager,
         )?;
 
         let funding_authority = cli
             .funding_authority
-            .map(|path| get_signer_simple(Some(&path), &cli_config.keypair_path).unwrap());
+            .map(|path| get_signer_simple(Some(&path), &cli_config.keypair_path))
+            .transpose()?;
 
         let manager = get_signer_simple(
-            cli.manager.as_deref(),
+            cli.manager.as_deref().or(profile.manager.as_deref()),
             &cli_config.keypair_path,
             // &mut wallet_manager,
         )?;
@@ -224,22 +338,37 @@ fn main() -> anyhow::Result<()> {
             // &mut wallet_manager,
         )?;
 
-        // let output_format = match cli.output_format {
-        //     Some(OutputFormatArg::Json) => OutputFormat::Json,
-        //     Some(OutputFormatArg::JsonCompact) => OutputFormat::JsonCompact,
-        //     None => {
-        //         if cli.verbose {
-        //             OutputFormat::DisplayVerbose
-        //         } else {
-        //             OutputFormat::Display
-        //         }
-        //     }
-        // };
+        let output_format = match cli.output_format {
+            Some(OutputFormatArg::Json) => OutputFormat::Json,
+            Some(OutputFormatArg::JsonCompact) => OutputFormat::JsonCompact,
+            None => OutputFormat::Display,
+        };
+
+        let commitment = match cli.commitment {
+            CommitmentArg::Processed => CommitmentConfig::processed(),
+            CommitmentArg::Confirmed => CommitmentConfig::confirmed(),
+            CommitmentArg::Finalized => CommitmentConfig::finalized(),
+        };
+
+        let rpc_client = Arc::new(RpcClient::new_with_commitment(json_rpc_url, commitment));
+        let mut accounts: Box<dyn AccountSource> = match &cli.snapshot_in {
+            Some(dir) => Box::new(SnapshotAccounts::load(dir)?),
+            None => Box::new(rpc_client.clone()),
+        };
+        if let Some(dir) = cli.snapshot_out.clone() {
+            accounts = Box::new(RecordingAccounts::new(accounts, dir)?);
+        }
 
         JitoStakePoolCliConfig {
-            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            transactions: Box::new(rpc_client.clone()),
+            rpc_client,
+            accounts,
+            replay: cli.snapshot_in.is_some(),
+            program_id,
+            layout,
+            profiles,
             verbose: cli.verbose,
-            // output_format,
+            output_format,
             manager,
             staker,
             funding_authority,
@@ -247,6 +376,7 @@ fn main() -> anyhow::Result<()> {
             fee_payer,
             dry_run: cli.dry_run,
             no_update: cli.no_update,
+            max_retries: cli.max_retries,
         }
     };
 
@@ -284,7 +414,7 @@ fn main() -> anyhow::Result<()> {
         //     )
         // }
         Commands::AddValidator(args) => {
-            let stake_pool_address = parse_pubkey(&args.pool)?;
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
             let vote_account_address = parse_pubkey(&args.vote_account)?;
             command_vsa_add(&config, &stake_pool_address, &vote_account_address)
         }
@@ -310,17 +440,26 @@ fn main() -> anyhow::Result<()> {
         //     )
         // }
         Commands::IncreaseValidatorStake(args) => {
-            let stake_pool_address = parse_pubkey(&args.pool)?;
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
             let vote_account = parse_pubkey(&args.vote_account)?;
-            let amount = args.amount.unwrap_or(0.0);
-            command_increase_validator_stake(&config, &stake_pool_address, &vote_account, amount)
+            command_increase_validator_stake(
+                &config,
+                &stake_pool_address,
+                &vote_account,
+                args.amount,
+            )
             // command_increase_validator_stake(&config, &stake_pool_address, &vote_account, amount)
-        } // Commands::DecreaseValidatorStake(args) => {
-        //     let stake_pool_address = parse_pubkey(&args.pool)?;
-        //     let vote_account = parse_pubkey(&args.vote_account)?;
-        //     let amount = args.amount.unwrap_or(0.0);
-        //     command_decrease_validator_stake(&config, &stake_pool_address, &vote_account, amount)
-        // }
+        }
+        Commands::DecreaseValidatorStake(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            let vote_account = parse_pubkey(&args.vote_account)?;
+            command_decrease_validator_stake(
+                &config,
+                &stake_pool_address,
+                &vote_account,
+                args.amount,
+            )
+        }
         // Commands::SetPreferredValidator(args) => {
         //     let stake_pool_address = parse_pubkey(&args.pool)?;
         //     let preferred_type = match args.preferred_type {
@@ -367,7 +506,7 @@ fn main() -> anyhow::Result<()> {
         //     )
         // }
         Commands::DepositSol(args) => {
-            let stake_pool_address = parse_pubkey(&args.pool)?;
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
             let token_receiver = args
                 .token_receiver
                 .as_ref()
@@ -387,148 +526,194 @@ fn main() -> anyhow::Result<()> {
                 &token_receiver,
                 &referrer,
                 amount,
+                args.min_pool_tokens_out,
             )
-        } // Commands::List(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     command_list(&config, &stake_pool_address)
-          // }
-          // Commands::Update(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     command_update(&config, &stake_pool_address, args.force, args.no_merge)
-          // }
-          // Commands::WithdrawStake(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let vote_account = args
-          //         .vote_account
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let pool_account = args
-          //         .pool_account
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let stake_receiver = args
-          //         .stake_receiver
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     command_withdraw_stake(
-          //         &config,
-          //         &stake_pool_address,
-          //         args.use_reserve,
-          //         &vote_account,
-          //         &stake_receiver,
-          //         &pool_account,
-          //         args.amount,
-          //     )
-          // }
-          // Commands::WithdrawSol(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let pool_account = args
-          //         .pool_account
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let sol_receiver = parse_pubkey(&args.sol_receiver)?;
-          //     command_withdraw_sol(
-          //         &config,
-          //         &stake_pool_address,
-          //         &pool_account,
-          //         &sol_receiver,
-          //         args.amount,
-          //     )
-          // }
-          // Commands::SetManager(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let new_manager = args.new_manager.map(|_| Keypair::new()); // Simplified
-          //     let new_fee_receiver = args
-          //         .new_fee_receiver
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     command_set_manager(
-          //         &config,
-          //         &stake_pool_address,
-          //         &new_manager,
-          //         &new_fee_receiver,
-          //     )
-          // }
-          // Commands::SetStaker(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let new_staker = parse_pubkey(&args.new_staker)?;
-          //     command_set_staker(&config, &stake_pool_address, &new_staker)
-          // }
-          // Commands::SetFundingAuthority(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let new_authority = args
-          //         .new_authority
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let funding_type = match args.funding_type {
-          //         FundingTypeArg::SolDeposit => FundingType::SolDeposit,
-          //         FundingTypeArg::StakeDeposit => FundingType::StakeDeposit,
-          //         FundingTypeArg::SolWithdraw => FundingType::SolWithdraw,
-          //     };
-          //     command_set_funding_authority(&config, &stake_pool_address, new_authority, funding_type)
-          // }
-          // Commands::SetFee(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let new_fee = Fee {
-          //         denominator: args.fee_denominator,
-          //         numerator: args.fee_numerator,
-          //     };
-          //     let fee_type = match args.fee_type {
-          //         FeeTypeArg::Epoch => FeeType::Epoch(new_fee),
-          //         FeeTypeArg::StakeDeposit => FeeType::StakeDeposit(new_fee),
-          //         FeeTypeArg::SolDeposit => FeeType::SolDeposit(new_fee),
-          //         FeeTypeArg::StakeWithdrawal => FeeType::StakeWithdrawal(new_fee),
-          //         FeeTypeArg::SolWithdrawal => FeeType::SolWithdrawal(new_fee),
-          //     };
-          //     command_set_fee(&config, &stake_pool_address, fee_type)
-          // }
-          // Commands::SetReferralFee(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     assert!(
-          //         args.fee <= 100u8,
-          //         "Invalid fee {}%. Fee needs to be in range [0-100]",
-          //         args.fee
-          //     );
-          //     let fee_type = match args.fee_type {
-          //         ReferralFeeTypeArg::Sol => FeeType::SolReferral(args.fee),
-          //         ReferralFeeTypeArg::Stake => FeeType::StakeReferral(args.fee),
-          //     };
-          //     command_set_fee(&config, &stake_pool_address, fee_type)
-          // }
-          // Commands::ListAll => command_list_all_pools(&config),
-          // Commands::DepositAllStake(args) => {
-          //     let stake_pool_address = parse_pubkey(&args.pool)?;
-          //     let stake_authority = parse_pubkey(&args.stake_authority)?;
-          //     let token_receiver = args
-          //         .token_receiver
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let referrer = args
-          //         .referrer
-          //         .as_ref()
-          //         .map(|s| parse_pubkey(s))
-          //         .transpose()?;
-          //     let withdraw_authority = get_signer_simple(
-          //         args.withdraw_authority.as_deref(),
-          //         &cli_config.keypair_path,
-          //         &mut wallet_manager,
-          //     );
-          //     command_deposit_all_stake(
-          //         &config,
-          //         &stake_pool_address,
-          //         &stake_authority,
-          //         withdraw_authority,
-          //         &token_receiver,
-          //         &referrer,
-          //     )
-          // }
+        }
+        Commands::WithdrawSol(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            let pool_account = args
+                .pool_account
+                .as_ref()
+                .map(|s| parse_pubkey(s))
+                .transpose()?;
+            let sol_receiver = parse_pubkey(&args.sol_receiver)?;
+            command_withdraw_sol(
+                &config,
+                &stake_pool_address,
+                &pool_account,
+                &sol_receiver,
+                args.amount,
+                args.min_lamports_out,
+            )
+        }
+        Commands::Rebalance(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_rebalance(
+                &config,
+                &stake_pool_address,
+                &args.targets,
+                args.reserve_buffer,
+            )
+        }
+        Commands::Crank(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_crank(
+                &config,
+                &stake_pool_address,
+                args.state_file.as_deref(),
+                args.poll_interval,
+                args.no_merge,
+                Backoff::new(args.max_attempts),
+            )
+        }
+        Commands::Metrics(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_metrics(&config, &stake_pool_address, &args.bind)
+        }
+        Commands::Audit(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_audit(&config, &stake_pool_address)
+        }
+        Commands::Quote(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_quote(&config, &stake_pool_address, args.operation, args.amount)
+        }
+        Commands::Stats(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_stats(
+                &config,
+                &stake_pool_address,
+                args.history,
+                args.history_dir.as_deref(),
+            )
+        }
+        Commands::InspectTx(args) => command_inspect_tx(&config, &args.signature, &args.symbol),
+        Commands::Update(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_update(&config, &stake_pool_address, args.force, args.no_merge)
+        }
+        Commands::List(args) => {
+            let stake_pool_address = config.profiles.resolve_pool(&args.pool)?;
+            command_list(&config, &stake_pool_address)
+        }
+        Commands::Batch(args) => command_batch(&config, &args.file, args.results.as_deref()),
+        // Commands::WithdrawStake(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let vote_account = args
+        //         .vote_account
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     let pool_account = args
+        //         .pool_account
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     let stake_receiver = args
+        //         .stake_receiver
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     command_withdraw_stake(
+        //         &config,
+        //         &stake_pool_address,
+        //         args.use_reserve,
+        //         &vote_account,
+        //         &stake_receiver,
+        //         &pool_account,
+        //         args.amount,
+        //     )
+        // }
+        // Commands::SetManager(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let new_manager = args.new_manager.map(|_| Keypair::new()); // Simplified
+        //     let new_fee_receiver = args
+        //         .new_fee_receiver
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     command_set_manager(
+        //         &config,
+        //         &stake_pool_address,
+        //         &new_manager,
+        //         &new_fee_receiver,
+        //     )
+        // }
+        // Commands::SetStaker(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let new_staker = parse_pubkey(&args.new_staker)?;
+        //     command_set_staker(&config, &stake_pool_address, &new_staker)
+        // }
+        // Commands::SetFundingAuthority(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let new_authority = args
+        //         .new_authority
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     let funding_type = match args.funding_type {
+        //         FundingTypeArg::SolDeposit => FundingType::SolDeposit,
+        //         FundingTypeArg::StakeDeposit => FundingType::StakeDeposit,
+        //         FundingTypeArg::SolWithdraw => FundingType::SolWithdraw,
+        //     };
+        //     command_set_funding_authority(&config, &stake_pool_address, new_authority, funding_type)
+        // }
+        // Commands::SetFee(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let new_fee = Fee {
+        //         denominator: args.fee_denominator,
+        //         numerator: args.fee_numerator,
+        //     };
+        //     let fee_type = match args.fee_type {
+        //         FeeTypeArg::Epoch => FeeType::Epoch(new_fee),
+        //         FeeTypeArg::StakeDeposit => FeeType::StakeDeposit(new_fee),
+        //         FeeTypeArg::SolDeposit => FeeType::SolDeposit(new_fee),
+        //         FeeTypeArg::StakeWithdrawal => FeeType::StakeWithdrawal(new_fee),
+        //         FeeTypeArg::SolWithdrawal => FeeType::SolWithdrawal(new_fee),
+        //     };
+        //     command_set_fee(&config, &stake_pool_address, fee_type)
+        // }
+        // Commands::SetReferralFee(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     assert!(
+        //         args.fee <= 100u8,
+        //         "Invalid fee {}%. Fee needs to be in range [0-100]",
+        //         args.fee
+        //     );
+        //     let fee_type = match args.fee_type {
+        //         ReferralFeeTypeArg::Sol => FeeType::SolReferral(args.fee),
+        //         ReferralFeeTypeArg::Stake => FeeType::StakeReferral(args.fee),
+        //     };
+        //     command_set_fee(&config, &stake_pool_address, fee_type)
+        // }
+        // Commands::ListAll => command_list_all_pools(&config),
+        // Commands::DepositAllStake(args) => {
+        //     let stake_pool_address = parse_pubkey(&args.pool)?;
+        //     let stake_authority = parse_pubkey(&args.stake_authority)?;
+        //     let token_receiver = args
+        //         .token_receiver
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     let referrer = args
+        //         .referrer
+        //         .as_ref()
+        //         .map(|s| parse_pubkey(s))
+        //         .transpose()?;
+        //     let withdraw_authority = get_signer_simple(
+        //         args.withdraw_authority.as_deref(),
+        //         &cli_config.keypair_path,
+        //         &mut wallet_manager,
+        //     );
+        //     command_deposit_all_stake(
+        //         &config,
+        //         &stake_pool_address,
+        //         &stake_authority,
+        //         withdraw_authority,
+        //         &token_receiver,
+        //         &referrer,
+        //     )
+        // }
     };
 
     result
@@ -1,70 +1,464 @@
+use std::sync::Arc;
+
 use anyhow::anyhow;
-use borsh::BorshDeserialize;
 // use solana_client::{
 //     client_error::ClientError,
 //     rpc_client::RpcClient,
 //     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
 // };
 // use solana_program::{borsh::try_from_slice_unchecked, program_pack::Pack, stake};
+use jito_stake_pool_sdk::{
+    client::{self, ClientError, PoolSnapshot},
+    layout::{
+        DecodedStakePool, DecodedValidatorList, LayoutVersion, decode_stake_pool,
+        decode_validator_list,
+    },
+    stake_account::{
+        STAKE_CONTEXT_ADDRESSES, StakeAccountInfo, StakeContext, classify_stake_accounts,
+        stake_authority_filter,
+    },
+};
+use solana_account_decoder::UiAccountEncoding;
 use solana_rpc_client::rpc_client::RpcClient;
-use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
-use spl_stake_pool::state::{StakePool, ValidatorList};
+use solana_rpc_client_api::{
+    config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig,
+        RpcSimulateTransactionConfig,
+    },
+    filter::RpcFilterType,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    epoch_schedule::EpochSchedule,
+    hash::Hash,
+    message::Message,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signers::Signers,
+    stake::state::StakeAuthorize,
+    sysvar::Sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_stake_pool::state::StakePool;
 // use spl_stake_pool::{
 //     find_withdraw_authority_program_address,
 //     state::{StakePool, ValidatorList},
 // };
 // use spl_stake_pool_legacy::state::ValidatorList;
 
-pub fn get_stake_pool(
+use crate::send::{TransactionStatus, send_with_rpc};
+
+/// Where commands read accounts from: the cluster, or a snapshot directory
+pub trait AccountSource {
+    /// Fetches any number of accounts, `None` for the ones that do not exist
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>>;
+
+    /// Fetches the accounts owned by `program_id` matching every filter
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>>;
+
+    /// Fetches an account that must exist
+    fn get_account(&self, address: &Pubkey) -> anyhow::Result<Account> {
+        self.get_multiple_accounts(std::slice::from_ref(address))?
+            .pop()
+            .flatten()
+            .ok_or_else(|| anyhow!("AccountNotFound: pubkey={address}"))
+    }
+}
+
+impl AccountSource for RpcClient {
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        block_on(
+            self,
+            client::get_multiple_accounts_chunked(self.get_inner_client(), addresses),
+        )
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        Ok(self.get_program_accounts_with_config(
+            program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(filters.to_vec()),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(self.commitment()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?)
+    }
+}
+
+impl<T: AccountSource + ?Sized> AccountSource for Arc<T> {
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        self.as_ref().get_multiple_accounts(addresses)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        self.as_ref().get_program_accounts(program_id, filters)
+    }
+}
+
+impl<T: AccountSource + ?Sized> AccountSource for Box<T> {
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        self.as_ref().get_multiple_accounts(addresses)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        self.as_ref().get_program_accounts(program_id, filters)
+    }
+}
+
+/// Outcome of simulating a transaction
+#[derive(Clone, Debug, Default)]
+pub struct Simulation {
+    pub err: Option<TransactionError>,
+    pub logs: Option<Vec<String>>,
+    pub units_consumed: Option<u64>,
+    /// State of the requested accounts after the transaction, in order
+    pub accounts: Vec<Option<Account>>,
+}
+
+/// Where commands send the transactions they build: the cluster, or an
+/// in-memory sink in tests
+pub trait TransactionSink {
+    /// Blockhash to build new transactions with
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash>;
+
+    /// Fee the cluster charges for `message`
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64>;
+
+    /// Simulates `transaction` and returns the resulting state of `addresses`
+    fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation>;
+
+    /// Signs and sends `transaction` until it is confirmed, fails, or runs out
+    /// of retries
+    fn send_transaction(
+        &self,
+        transaction: Transaction,
+        signers: &dyn Signers,
+        max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus>;
+}
+
+impl TransactionSink for RpcClient {
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        Ok(self
+            .get_latest_blockhash_with_commitment(self.commitment())?
+            .0)
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64> {
+        Ok(RpcClient::get_fee_for_message(self, message)?)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation> {
+        let result = self
+            .simulate_transaction_with_config(
+                transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.commitment()),
+                    accounts: Some(RpcSimulateTransactionAccountsConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        addresses: addresses.iter().map(|a| a.to_string()).collect(),
+                    }),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )?
+            .value;
+        let accounts = result
+            .accounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account| account.and_then(|account| account.decode::<Account>()))
+            .collect();
+        Ok(Simulation {
+            err: result.err,
+            logs: result.logs,
+            units_consumed: result.units_consumed,
+            accounts,
+        })
+    }
+
+    fn send_transaction(
+        &self,
+        transaction: Transaction,
+        signers: &dyn Signers,
+        max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus> {
+        send_with_rpc(self, transaction, signers, max_retries)
+    }
+}
+
+impl<T: TransactionSink + ?Sized> TransactionSink for Arc<T> {
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        self.as_ref().get_latest_blockhash()
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64> {
+        self.as_ref().get_fee_for_message(message)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation> {
+        self.as_ref().simulate_transaction(transaction, addresses)
+    }
+
+    fn send_transaction(
+        &self,
+        transaction: Transaction,
+        signers: &dyn Signers,
+        max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus> {
+        self.as_ref()
+            .send_transaction(transaction, signers, max_retries)
+    }
+}
+
+impl<T: TransactionSink + ?Sized> TransactionSink for Box<T> {
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        self.as_ref().get_latest_blockhash()
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64> {
+        self.as_ref().get_fee_for_message(message)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation> {
+        self.as_ref().simulate_transaction(transaction, addresses)
+    }
+
+    fn send_transaction(
+        &self,
+        transaction: Transaction,
+        signers: &dyn Signers,
+        max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus> {
+        self.as_ref()
+            .send_transaction(transaction, signers, max_retries)
+    }
+}
+
+/// Runs an SDK client future on the runtime of the blocking `RpcClient`
+fn block_on<T>(
     rpc_client: &RpcClient,
+    future: impl Future<Output = Result<T, ClientError>>,
+) -> anyhow::Result<T> {
+    Ok(rpc_client.runtime().block_on(future)?)
+}
+
+/// Fetches and decodes a stake pool, as `layout` when the pool's program
+/// version is configured
+pub fn get_stake_pool(
+    accounts: &dyn AccountSource,
     stake_pool_address: &Pubkey,
-) -> anyhow::Result<StakePool> {
-    let account_data = rpc_client.get_account_data(stake_pool_address)?;
-    let stake_pool = StakePool::deserialize(&mut account_data.as_slice())
-        .map_err(|err| anyhow!("Invalid stake pool {}: {}", stake_pool_address, err))?;
-    Ok(stake_pool)
+    layout: Option<LayoutVersion>,
+) -> anyhow::Result<DecodedStakePool> {
+    let account = accounts.get_account(stake_pool_address)?;
+    Ok(
+        decode_stake_pool(&account.data, layout).map_err(|error| ClientError::Layout {
+            address: *stake_pool_address,
+            error,
+        })?,
+    )
 }
 
+/// Fetches and decodes the validator list of a pool, as `layout` when the
+/// pool's program version is configured. 0.6.4 active stake is normalized
+/// with the validator stake accounts, which are only fetched for 0.6.4 lists.
 pub fn get_validator_list(
-    rpc_client: &RpcClient,
+    accounts: &dyn AccountSource,
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    layout: Option<LayoutVersion>,
+) -> anyhow::Result<DecodedValidatorList> {
+    let mut list = fetch_validator_list(accounts, &stake_pool.validator_list, layout)?;
+    if list.layout == LayoutVersion::Legacy {
+        let addresses: Vec<Pubkey> = list
+            .validator_list
+            .validators
+            .iter()
+            .map(|info| client::validator_stake_addresses(program_id, stake_pool_address, info).0)
+            .collect();
+        let stake_accounts = accounts.get_multiple_accounts(&addresses)?;
+        list.normalize_active_stake(&stake_accounts);
+    }
+    Ok(list)
+}
+
+/// Decodes a validator list, with 0.6.4 active stake as recorded
+fn fetch_validator_list(
+    accounts: &dyn AccountSource,
     validator_list_address: &Pubkey,
-) -> anyhow::Result<ValidatorList> {
-    let account_data = rpc_client.get_account_data(validator_list_address)?;
-    let validator_list = ValidatorList::deserialize(&mut account_data.as_slice())
-        .map_err(|err| anyhow!("Invalid validator list {}: {}", validator_list_address, err))?;
-    Ok(validator_list)
+    layout: Option<LayoutVersion>,
+) -> anyhow::Result<DecodedValidatorList> {
+    let account = accounts.get_account(validator_list_address)?;
+    Ok(
+        decode_validator_list(&account.data, layout).map_err(|error| ClientError::Layout {
+            address: *validator_list_address,
+            error,
+        })?,
+    )
 }
 
 pub fn get_token_account(
-    rpc_client: &RpcClient,
+    accounts: &dyn AccountSource,
     token_account_address: &Pubkey,
     expected_token_mint: &Pubkey,
 ) -> anyhow::Result<spl_token::state::Account> {
-    let account_data = rpc_client.get_account_data(token_account_address)?;
-    let token_account = spl_token::state::Account::unpack_unchecked(account_data.as_slice())
-        .map_err(|err| anyhow!("Invalid token account {}: {}", token_account_address, err))?;
+    let account = accounts.get_account(token_account_address)?;
+    Ok(client::decode_token_account(
+        token_account_address,
+        &account.data,
+        expected_token_mint,
+    )?)
+}
 
-    if token_account.mint != *expected_token_mint {
-        Err(anyhow!(
-            "Invalid token mint for {}, expected mint is {}",
-            token_account_address,
-            expected_token_mint
-        ))
-    } else {
-        Ok(token_account)
-    }
+/// Fetches the stake pool with its validator list, reserve and every
+/// validator and transient stake account
+pub fn get_pool_snapshot(
+    accounts: &dyn AccountSource,
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    layout: Option<LayoutVersion>,
+) -> anyhow::Result<PoolSnapshot> {
+    let pool = get_stake_pool(accounts, stake_pool_address, layout)?;
+    // Normalized along with the rest of the snapshot
+    let list = fetch_validator_list(accounts, &pool.stake_pool.validator_list, layout)?;
+    let addresses = client::pool_snapshot_addresses(
+        program_id,
+        stake_pool_address,
+        &pool.stake_pool,
+        &list.validator_list,
+    );
+    let stake_accounts = accounts.get_multiple_accounts(&addresses)?;
+    Ok(client::build_pool_snapshot(
+        program_id,
+        stake_pool_address,
+        pool,
+        list,
+        stake_accounts,
+    )?)
+}
+
+/// Fetches every stake account whose withdraw authority is `withdrawer`
+pub fn get_stake_accounts_by_withdrawer(
+    accounts: &dyn AccountSource,
+    withdrawer: &Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+    accounts.get_program_accounts(
+        &solana_sdk::stake::program::id(),
+        &[stake_authority_filter(
+            withdrawer,
+            StakeAuthorize::Withdrawer,
+        )],
+    )
+}
+
+/// Finds and classifies every stake account where `authority` is the staker
+/// or the withdrawer
+pub fn find_stake_accounts(
+    accounts: &dyn AccountSource,
+    authority: &Pubkey,
+) -> anyhow::Result<Vec<StakeAccountInfo>> {
+    let stake_program_id = solana_sdk::stake::program::id();
+    let staker_accounts = accounts.get_program_accounts(
+        &stake_program_id,
+        &[stake_authority_filter(authority, StakeAuthorize::Staker)],
+    )?;
+    let withdrawer_accounts = accounts.get_program_accounts(
+        &stake_program_id,
+        &[stake_authority_filter(
+            authority,
+            StakeAuthorize::Withdrawer,
+        )],
+    )?;
+    let context =
+        StakeContext::from_accounts(&accounts.get_multiple_accounts(&STAKE_CONTEXT_ADDRESSES)?)?;
+    Ok(classify_stake_accounts(
+        &context,
+        staker_accounts.into_iter().chain(withdrawer_accounts),
+    ))
+}
+
+pub fn get_token_mint(
+    accounts: &dyn AccountSource,
+    token_mint_address: &Pubkey,
+) -> anyhow::Result<spl_token::state::Mint> {
+    let account = accounts.get_account(token_mint_address)?;
+    let token_mint = spl_token::state::Mint::unpack_from_slice(account.data.as_slice())
+        .map_err(|err| anyhow!("Invalid token mint {}: {}", token_mint_address, err))?;
+
+    Ok(token_mint)
+}
+
+/// Lamports of an account, 0 if it does not exist
+pub fn get_balance(accounts: &dyn AccountSource, address: &Pubkey) -> anyhow::Result<u64> {
+    Ok(accounts
+        .get_multiple_accounts(std::slice::from_ref(address))?
+        .pop()
+        .flatten()
+        .map_or(0, |account| account.lamports))
+}
+
+fn get_sysvar<T: Sysvar>(accounts: &dyn AccountSource) -> anyhow::Result<T> {
+    let account = accounts.get_account(&T::id())?;
+    bincode::deserialize(&account.data).map_err(|err| anyhow!("Invalid sysvar {}: {err}", T::id()))
+}
+
+/// Current epoch, from the clock sysvar
+pub fn get_epoch(accounts: &dyn AccountSource) -> anyhow::Result<u64> {
+    Ok(get_sysvar::<Clock>(accounts)?.epoch)
+}
+
+/// Epoch schedule of the cluster, from the epoch schedule sysvar
+pub fn get_epoch_schedule(accounts: &dyn AccountSource) -> anyhow::Result<EpochSchedule> {
+    get_sysvar(accounts)
+}
+
+/// Rent-exempt balance of an account of `data_len` bytes, from the rent sysvar
+pub fn get_minimum_balance_for_rent_exemption(
+    accounts: &dyn AccountSource,
+    data_len: usize,
+) -> anyhow::Result<u64> {
+    Ok(get_sysvar::<Rent>(accounts)?.minimum_balance(data_len))
 }
-//
-// pub fn get_token_mint(
-//     rpc_client: &RpcClient,
-//     token_mint_address: &Pubkey,
-// ) -> Result<spl_token::state::Mint, Error> {
-//     let account_data = rpc_client.get_account_data(token_mint_address)?;
-//     let token_mint = spl_token::state::Mint::unpack_from_slice(account_data.as_slice())
-//         .map_err(|err| format!("Invalid token mint {}: {}", token_mint_address, err))?;
-//
-//     Ok(token_mint)
-// }
 //
 // pub(crate) fn get_stake_state(
 //     rpc_client: &RpcClient,
@@ -118,33 +512,3 @@ pub fn get_token_account(
 //                 .collect()
 //         })
 // }
-
-// pub(crate) fn get_all_stake(
-//     rpc_client: &RpcClient,
-//     authorized_staker: &Pubkey,
-// ) -> Result<HashSet<Pubkey>, ClientError> {
-//     let all_stake_accounts = rpc_client.get_program_accounts_with_config(
-//         &stake::program::id(),
-//         RpcProgramAccountsConfig {
-//             filters: Some(vec![
-//                 // Filter by `Meta::authorized::staker`, which begins at byte offset 12
-//                 RpcFilterType::Memcmp(Memcmp {
-//                     offset: 12,
-//                     bytes: MemcmpEncodedBytes::Base58(authorized_staker.to_string()),
-//                     encoding: Some(MemcmpEncoding::Binary),
-//                 }),
-//             ]),
-//             account_config: RpcAccountInfoConfig {
-//                 encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
-//                 commitment: Some(rpc_client.commitment()),
-//                 ..RpcAccountInfoConfig::default()
-//             },
-//             ..RpcProgramAccountsConfig::default()
-//         },
-//     )?;
-//
-//     Ok(all_stake_accounts
-//         .into_iter()
-//         .map(|(address, _)| address)
-//         .collect())
-// }
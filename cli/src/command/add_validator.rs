@@ -1,7 +1,8 @@
-use std::str::FromStr;
-
 use clap::Args;
-use jito_stake_pool_sdk::sdk::add_validator::add_validator_to_pool_with_vote;
+use jito_stake_pool_sdk::{
+    address::find_stake_program_address, sdk::add_validator::add_validator_to_pool_with_vote,
+};
+use serde::Deserialize;
 use solana_sdk::pubkey::Pubkey;
 
 use crate::{
@@ -11,9 +12,9 @@ use crate::{
     send_transaction,
 };
 
-#[derive(Args)]
+#[derive(Args, Deserialize)]
 pub struct AddValidatorArgs {
-    /// Stake pool address
+    /// Stake pool address or profile name
     pub pool: String,
 
     /// The validator vote account that the stake is delegated to
@@ -25,28 +26,29 @@ pub fn command_vsa_add(
     stake_pool_address: &Pubkey,
     vote_account_address: &Pubkey,
 ) -> anyhow::Result<()> {
-    let vote_account =
-        solana_program::pubkey::Pubkey::new_from_array(vote_account_address.to_bytes());
-    // let (stake_account_address, _) =
-    //     find_stake_program_address(&spl_stake_pool::id(), vote_account, stake_pool_address);
-
-    let program_id = Pubkey::from_str("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy").unwrap();
-
-    let stake_account_address = Pubkey::find_program_address(
-        &[&vote_account.to_bytes(), &stake_pool_address.to_bytes()],
-        &program_id,
-    )
-    .0;
-
-    println!("Adding stake account {stake_account_address}, delegated to {vote_account}",);
-
-    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
-    let validator_list = Pubkey::new_from_array(stake_pool.validator_list.to_bytes());
-
-    let validator_list = get_validator_list(&config.rpc_client, &validator_list)?;
+    let (stake_account_address, _) = find_stake_program_address(
+        &config.program_id,
+        vote_account_address,
+        stake_pool_address,
+        None,
+    );
+
+    println!("Adding stake account {stake_account_address}, delegated to {vote_account_address}",);
+
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
 
     if validator_list.contains(vote_account_address) {
-        eprintln!("Stake pool already contains validator {vote_account}, ignoring",);
+        eprintln!("Stake pool already contains validator {vote_account_address}, ignoring",);
         return Ok(());
     }
 
@@ -62,7 +64,7 @@ pub fn command_vsa_add(
     let transaction = checked_transaction_with_signers(
         config,
         &[add_validator_to_pool_with_vote(
-            &spl_stake_pool::id(),
+            &config.program_id,
             &stake_pool,
             stake_pool_address,
             &fee_payer,
@@ -71,7 +73,71 @@ pub fn command_vsa_add(
         &signers,
     )?;
 
-    send_transaction(config, transaction)?;
+    send_transaction(config, transaction, &signers)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, test_config},
+    };
+
+    #[test]
+    fn adds_validator_signed_by_fee_payer_and_staker() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let pool = TestPool::new(&config);
+        config.accounts = Box::new(pool.accounts());
+        let vote_account = Pubkey::new_unique();
+
+        command_vsa_add(&config, &pool.address, &vote_account).unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let fee_payer = config.fee_payer.pubkey();
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[add_validator_to_pool_with_vote(
+                &config.program_id,
+                &pool.stake_pool,
+                &pool.address,
+                &fee_payer,
+                &vote_account,
+            )],
+        );
+        assert_eq!(sent[0].signers, [fee_payer, config.staker.pubkey()]);
+    }
+
+    #[test]
+    fn skips_validator_already_in_pool() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let vote_account = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+
+        command_vsa_add(&config, &pool.address, &vote_account).unwrap();
+
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn fails_without_stake_pool() {
+        let (config, transactions) = test_config(MemoryTransactions::default());
+        let stake_pool_address = Pubkey::new_unique();
+
+        let error =
+            command_vsa_add(&config, &stake_pool_address, &Pubkey::new_unique()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("AccountNotFound: pubkey={stake_pool_address}")
+        );
+        assert!(transactions.sent().is_empty());
+    }
+}
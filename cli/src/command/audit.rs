@@ -0,0 +1,455 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::{
+    address::{
+        MINIMUM_ACTIVE_STAKE, find_withdraw_authority_program_address, minimum_reserve_lamports,
+    },
+    client::ValidatorSnapshot,
+    layout::LayoutVersion,
+};
+use serde::Serialize;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    stake::state::{Meta, StakeStateV2},
+};
+use spl_stake_pool::state::StakeStatus;
+
+use crate::{
+    client::{get_epoch, get_pool_snapshot, get_stake_accounts_by_withdrawer},
+    config::JitoStakePoolCliConfig,
+};
+
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+}
+
+/// Pool totals recomputed from the chain, along with everything that does
+/// not match the pool's own accounting
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub pool: String,
+    pub epoch: u64,
+    pub last_update_epoch: u64,
+    /// Balances are only expected to match once the pool is updated this epoch
+    pub up_to_date: bool,
+    /// Layout of the pool accounts, which decides how active stake is counted
+    pub layout: String,
+    pub reserve_lamports: u64,
+    /// Part of the reserve the pool never counts: its rent-exempt reserve,
+    /// plus one lamport for 0.6.4
+    pub reserve_minimum_lamports: u64,
+    pub validator_stake_lamports: u64,
+    pub transient_stake_lamports: u64,
+    /// Reserve above its minimum, plus the validator and transient stake
+    pub computed_total_lamports: u64,
+    pub recorded_total_lamports: u64,
+    pub validators: Vec<ValidatorAudit>,
+    pub issues: Vec<AuditIssue>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorAudit {
+    pub vote_account: String,
+    pub status: String,
+    pub stake_account: String,
+    pub transient_stake_account: String,
+    pub recorded_active_lamports: u64,
+    pub actual_active_lamports: u64,
+    pub recorded_transient_lamports: u64,
+    pub actual_transient_lamports: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum AuditIssue {
+    /// `StakePool::total_lamports` differs from the recomputed total
+    #[serde(rename_all = "camelCase")]
+    TotalLamportsMismatch { recorded: u64, computed: u64 },
+
+    /// The validator stake account differs from the validator list
+    #[serde(rename_all = "camelCase")]
+    ActiveStakeMismatch {
+        vote_account: String,
+        recorded: u64,
+        actual: u64,
+    },
+
+    /// The transient stake account balance differs from the validator list
+    #[serde(rename_all = "camelCase")]
+    TransientStakeMismatch {
+        vote_account: String,
+        recorded: u64,
+        actual: u64,
+    },
+
+    /// An active validator has no validator stake account
+    #[serde(rename_all = "camelCase")]
+    MissingValidatorStake {
+        vote_account: String,
+        stake_account: String,
+    },
+
+    /// A transient stake account exists that the validator list does not count
+    #[serde(rename_all = "camelCase")]
+    OrphanedTransientStake {
+        vote_account: String,
+        stake_account: String,
+        lamports: u64,
+    },
+
+    /// A stake account owned by the withdraw authority that is neither the
+    /// reserve nor a validator or transient stake account of the list
+    #[serde(rename_all = "camelCase")]
+    UnknownStakeAccount {
+        stake_account: String,
+        lamports: u64,
+        delegated_vote_account: Option<String>,
+    },
+
+    /// A removed validator whose entry was never cleaned up
+    #[serde(rename_all = "camelCase")]
+    UncleanedRemoval { vote_account: String },
+}
+
+pub fn command_audit(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+) -> anyhow::Result<()> {
+    let report = audit_pool(config, stake_pool_address)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if report.issues.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Audit found {} issues", report.issues.len()))
+    }
+}
+
+/// Recomputes the pool accounting from the stake accounts the way the
+/// program's update instructions do
+pub fn audit_pool(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+) -> anyhow::Result<AuditReport> {
+    let snapshot = get_pool_snapshot(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        config.layout,
+    )?;
+    let stake_pool = &snapshot.stake_pool;
+    let epoch = get_epoch(&config.accounts)?;
+    let reserve_lamports = snapshot.reserve_lamports();
+    let reserve_meta = snapshot
+        .reserve_stake
+        .as_ref()
+        .and_then(stake_state)
+        .and_then(|stake_state| stake_state.meta())
+        .ok_or_else(|| {
+            anyhow!(
+                "Reserve {} is not an initialized stake account",
+                stake_pool.reserve_stake
+            )
+        })?;
+    let reserve_minimum_lamports = reserve_minimum_lamports(snapshot.layout, &reserve_meta);
+    let withdraw_authority =
+        find_withdraw_authority_program_address(&config.program_id, stake_pool_address).0;
+
+    let owned_accounts = get_stake_accounts_by_withdrawer(&config.accounts, &withdraw_authority)?;
+
+    let mut issues = vec![];
+    let mut validators = vec![];
+    let mut known: HashSet<Pubkey> = HashSet::from([stake_pool.reserve_stake]);
+    let (mut validator_stake_lamports, mut transient_stake_lamports) = (0u64, 0u64);
+
+    for ValidatorSnapshot {
+        info: validator,
+        stake_address,
+        stake_account,
+        transient_stake_address: transient_address,
+        transient_stake_account,
+    } in &snapshot.validators
+    {
+        known.insert(*stake_address);
+        known.insert(*transient_address);
+        let vote_account = validator.vote_account_address.to_string();
+        let status = StakeStatus::try_from(validator.status)
+            .map_err(|err| anyhow!("Invalid status for {vote_account}: {err}"))?;
+        let recorded_active = u64::from(validator.active_stake_lamports);
+        let recorded_transient = u64::from(validator.transient_stake_lamports);
+        let (actual_active, counted_active) = stake_account.as_ref().map_or((0, 0), |account| {
+            active_stake_lamports(snapshot.layout, account)
+        });
+        let actual_transient = transient_stake_account
+            .as_ref()
+            .map_or(0, |account| account.lamports);
+        validator_stake_lamports += counted_active;
+        transient_stake_lamports += actual_transient;
+
+        if status == StakeStatus::ReadyForRemoval {
+            issues.push(AuditIssue::UncleanedRemoval {
+                vote_account: vote_account.clone(),
+            });
+        }
+        if stake_account.is_none() && status == StakeStatus::Active {
+            issues.push(AuditIssue::MissingValidatorStake {
+                vote_account: vote_account.clone(),
+                stake_account: stake_address.to_string(),
+            });
+        } else if actual_active != recorded_active {
+            issues.push(AuditIssue::ActiveStakeMismatch {
+                vote_account: vote_account.clone(),
+                recorded: recorded_active,
+                actual: actual_active,
+            });
+        }
+        if transient_stake_account.is_some() && recorded_transient == 0 {
+            issues.push(AuditIssue::OrphanedTransientStake {
+                vote_account: vote_account.clone(),
+                stake_account: transient_address.to_string(),
+                lamports: actual_transient,
+            });
+        } else if actual_transient != recorded_transient {
+            issues.push(AuditIssue::TransientStakeMismatch {
+                vote_account: vote_account.clone(),
+                recorded: recorded_transient,
+                actual: actual_transient,
+            });
+        }
+
+        validators.push(ValidatorAudit {
+            vote_account,
+            status: format!("{status:?}"),
+            stake_account: stake_address.to_string(),
+            transient_stake_account: transient_address.to_string(),
+            recorded_active_lamports: recorded_active,
+            actual_active_lamports: actual_active,
+            recorded_transient_lamports: recorded_transient,
+            actual_transient_lamports: actual_transient,
+        });
+    }
+
+    for (address, account) in &owned_accounts {
+        if !known.contains(address) {
+            issues.push(AuditIssue::UnknownStakeAccount {
+                stake_account: address.to_string(),
+                lamports: account.lamports,
+                delegated_vote_account: delegated_vote_account(account)
+                    .map(|vote_account| vote_account.to_string()),
+            });
+        }
+    }
+
+    let computed_total_lamports = reserve_lamports.saturating_sub(reserve_minimum_lamports)
+        + validator_stake_lamports
+        + transient_stake_lamports;
+    if computed_total_lamports != stake_pool.total_lamports {
+        issues.push(AuditIssue::TotalLamportsMismatch {
+            recorded: stake_pool.total_lamports,
+            computed: computed_total_lamports,
+        });
+    }
+
+    Ok(AuditReport {
+        pool: stake_pool_address.to_string(),
+        epoch,
+        last_update_epoch: stake_pool.last_update_epoch,
+        up_to_date: stake_pool.last_update_epoch >= epoch,
+        layout: snapshot.layout.to_string(),
+        reserve_lamports,
+        reserve_minimum_lamports,
+        validator_stake_lamports,
+        transient_stake_lamports,
+        computed_total_lamports,
+        recorded_total_lamports: stake_pool.total_lamports,
+        validators,
+        issues,
+    })
+}
+
+/// Part of the reserve the pool leaves out of its total: 0.6.4 keeps one
+/// lamport above the rent-exempt reserve, 2.x only the reserve
+fn reserve_minimum_lamports(layout: LayoutVersion, meta: &Meta) -> u64 {
+    match layout {
+        LayoutVersion::Legacy => minimum_reserve_lamports(meta),
+        LayoutVersion::Current | LayoutVersion::Compatible => meta.rent_exempt_reserve,
+    }
+}
+
+/// Active stake of a validator stake account, as the decoded validator list
+/// records it and as the pool counts it in its total.
+///
+/// Decoding gives 0.6.4 entries the 2.x meaning, the delegation plus its rent,
+/// but the 0.6.4 total only counts the delegation above the minimum active
+/// stake.
+fn active_stake_lamports(layout: LayoutVersion, account: &Account) -> (u64, u64) {
+    match layout {
+        LayoutVersion::Legacy => match stake_state(account) {
+            Some(StakeStateV2::Stake(meta, stake, _)) => (
+                stake.delegation.stake + meta.rent_exempt_reserve,
+                stake.delegation.stake.saturating_sub(MINIMUM_ACTIVE_STAKE),
+            ),
+            _ => (0, 0),
+        },
+        LayoutVersion::Current | LayoutVersion::Compatible => (account.lamports, account.lamports),
+    }
+}
+
+fn stake_state(account: &Account) -> Option<StakeStateV2> {
+    bincode::deserialize(&account.data).ok()
+}
+
+fn delegated_vote_account(account: &Account) -> Option<Pubkey> {
+    stake_state(account)?
+        .delegation()
+        .map(|delegation| delegation.voter_pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use jito_stake_pool_sdk::client::validator_stake_addresses;
+    use solana_sdk::{rent::Rent, stake::state::StakeStateV2};
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{EPOCH, TestPool, stake_account, test_config},
+    };
+
+    const SOL: u64 = 1_000_000_000;
+
+    /// Pool with one validator, updated this epoch, whose accounting matches
+    /// its reserve and validator stake account
+    fn consistent_pool(config: &JitoStakePoolCliConfig) -> (TestPool, Pubkey) {
+        let mut pool = TestPool::new(config);
+        let vote_account = pool.add_validator(0);
+        let withdraw_authority =
+            find_withdraw_authority_program_address(&config.program_id, &pool.address).0;
+        let (stake_address, _) = validator_stake_addresses(
+            &config.program_id,
+            &pool.address,
+            &pool.validator_list.validators[0],
+        );
+        pool.add_account(
+            stake_address,
+            stake_account(&withdraw_authority, 10 * SOL, Some(&vote_account)),
+        );
+        let reserve_stake = pool.stake_pool.reserve_stake;
+        pool.add_account(
+            reserve_stake,
+            stake_account(&withdraw_authority, 5 * SOL, None),
+        );
+        let stake_rent = Rent::default().minimum_balance(StakeStateV2::size_of());
+        pool.stake_pool.total_lamports = 5 * SOL - stake_rent + 10 * SOL;
+        pool.stake_pool.last_update_epoch = EPOCH;
+        (pool, withdraw_authority)
+    }
+
+    fn issue_kinds(report: &AuditReport) -> Vec<String> {
+        report
+            .issues
+            .iter()
+            .map(|issue| {
+                serde_json::to_value(issue).unwrap()["kind"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn current_pool_has_no_issues() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        config.layout = Some(LayoutVersion::Current);
+        let (pool, _) = consistent_pool(&config);
+        config.accounts = Box::new(pool.accounts());
+
+        let report = audit_pool(&config, &pool.address).unwrap();
+
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert!(report.up_to_date);
+        assert_eq!(report.layout, "2.x");
+        assert_eq!(
+            report.reserve_minimum_lamports,
+            Rent::default().minimum_balance(StakeStateV2::size_of())
+        );
+        assert_eq!(
+            report.computed_total_lamports,
+            report.recorded_total_lamports
+        );
+        assert_eq!(report.validator_stake_lamports, 10 * SOL);
+    }
+
+    #[test]
+    fn legacy_pool_counts_stake_above_minimum() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        config.layout = Some(LayoutVersion::Legacy);
+        let (mut pool, _) = consistent_pool(&config);
+        let stake_rent = Rent::default().minimum_balance(StakeStateV2::size_of());
+        let counted = 10 * SOL - stake_rent - MINIMUM_ACTIVE_STAKE;
+        pool.validator_list.validators[0].active_stake_lamports = counted.into();
+        pool.stake_pool.total_lamports = 5 * SOL - (stake_rent + 1) + counted;
+        config.accounts = Box::new(pool.accounts());
+
+        let report = audit_pool(&config, &pool.address).unwrap();
+
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert_eq!(report.layout, "0.6.4");
+        assert_eq!(report.reserve_minimum_lamports, stake_rent + 1);
+        assert_eq!(report.validator_stake_lamports, counted);
+        assert_eq!(report.validators[0].recorded_active_lamports, 10 * SOL);
+    }
+
+    #[test]
+    fn corrupted_pool_reports_every_issue() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        config.layout = Some(LayoutVersion::Current);
+        let (mut pool, withdraw_authority) = consistent_pool(&config);
+        let vote_account = pool.validator_list.validators[0].vote_account_address;
+        let (stake_address, transient_address) = validator_stake_addresses(
+            &config.program_id,
+            &pool.address,
+            &pool.validator_list.validators[0],
+        );
+        // Rewards the list never saw, a transient stake it does not count,
+        // and a stake account it knows nothing about
+        pool.add_account(
+            stake_address,
+            stake_account(&withdraw_authority, 11 * SOL, Some(&vote_account)),
+        );
+        pool.add_account(
+            transient_address,
+            stake_account(&withdraw_authority, SOL, Some(&vote_account)),
+        );
+        let unknown = Pubkey::new_unique();
+        pool.add_account(
+            unknown,
+            stake_account(&withdraw_authority, 2 * SOL, Some(&vote_account)),
+        );
+        config.accounts = Box::new(pool.accounts());
+
+        let report = audit_pool(&config, &pool.address).unwrap();
+
+        assert_eq!(
+            issue_kinds(&report),
+            [
+                "active-stake-mismatch",
+                "orphaned-transient-stake",
+                "unknown-stake-account",
+                "total-lamports-mismatch",
+            ]
+        );
+        assert_eq!(
+            report.computed_total_lamports - report.recorded_total_lamports,
+            2 * SOL
+        );
+    }
+}
@@ -0,0 +1,854 @@
+use std::{collections::HashSet, fs::File, path::Path, str::FromStr};
+
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::{
+    address::{MINIMUM_ACTIVE_STAKE, minimum_reserve_lamports, minimum_stake_lamports},
+    sdk::{
+        add_validator::add_validator_to_pool_with_vote,
+        decrease_validator_stake::decrease_validator_stake_with_vote,
+        increase_validator_stake::increase_validator_stake_with_vote,
+    },
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    native_token::{Sol, sol_str_to_lamports},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    stake::state::{Meta, StakeStateV2},
+    transaction::Transaction,
+};
+use spl_stake_pool::state::{StakePool, StakeStatus, ValidatorList};
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{
+        get_balance, get_minimum_balance_for_rent_exemption, get_stake_pool, get_validator_list,
+    },
+    command::{
+        add_validator::AddValidatorArgs, decrease_validator_stake::DecreaseValidatorStakeArgs,
+        increase_validator_stake::IncreaseValidatorStakeArgs,
+    },
+    config::JitoStakePoolCliConfig,
+    error::explain_transaction_error,
+    send::{TransactionStatus, send_with_retries},
+    simulate::simulate_transaction,
+};
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// File listing the operations to run, in JSON, YAML or CSV format
+    pub file: String,
+
+    /// File to write the per-operation results to. [default: <FILE>.results.json]
+    #[arg(long, value_name = "PATH")]
+    pub results: Option<String>,
+}
+
+/// One operation of a batch file, using the same fields as the matching subcommand
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum BatchOperation {
+    AddValidator(AddValidatorArgs),
+    IncreaseValidatorStake(IncreaseValidatorStakeArgs),
+    DecreaseValidatorStake(DecreaseValidatorStakeArgs),
+}
+
+impl BatchOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::AddValidator(_) => "add-validator",
+            Self::IncreaseValidatorStake(_) => "increase-validator-stake",
+            Self::DecreaseValidatorStake(_) => "decrease-validator-stake",
+        }
+    }
+
+    pub fn pool(&self) -> &str {
+        match self {
+            Self::AddValidator(args) => &args.pool,
+            Self::IncreaseValidatorStake(args) => &args.pool,
+            Self::DecreaseValidatorStake(args) => &args.pool,
+        }
+    }
+
+    fn vote_account(&self) -> &str {
+        match self {
+            Self::AddValidator(args) => &args.vote_account,
+            Self::IncreaseValidatorStake(args) => &args.vote_account,
+            Self::DecreaseValidatorStake(args) => &args.vote_account,
+        }
+    }
+}
+
+/// CSV rows are flat, so the operation is rebuilt from the `op` column
+#[derive(Deserialize)]
+struct CsvOperation {
+    op: String,
+    pool: String,
+    vote_account: String,
+    amount: Option<f64>,
+}
+
+impl TryFrom<CsvOperation> for BatchOperation {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CsvOperation) -> anyhow::Result<Self> {
+        let CsvOperation {
+            op,
+            pool,
+            vote_account,
+            amount,
+        } = row;
+        let amount = || amount.ok_or_else(|| anyhow!("Operation {op} requires an amount"));
+        match op.as_str() {
+            "add-validator" => Ok(Self::AddValidator(AddValidatorArgs { pool, vote_account })),
+            "increase-validator-stake" => {
+                Ok(Self::IncreaseValidatorStake(IncreaseValidatorStakeArgs {
+                    pool,
+                    vote_account,
+                    amount: amount()?,
+                }))
+            }
+            "decrease-validator-stake" => {
+                Ok(Self::DecreaseValidatorStake(DecreaseValidatorStakeArgs {
+                    pool,
+                    vote_account,
+                    amount: amount()?,
+                }))
+            }
+            _ => Err(anyhow!("Unknown operation {op}")),
+        }
+    }
+}
+
+/// Outcome of one operation, written to the results file
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub index: usize,
+    pub op: String,
+    pub vote_account: String,
+    pub signature: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+pub fn read_batch_file(path: &Path) -> anyhow::Result<Vec<BatchOperation>> {
+    let file =
+        File::open(path).map_err(|err| anyhow!("Unable to open {}: {err}", path.display()))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => Ok(serde_json::from_reader(file)?),
+        Some("yaml") | Some("yml") => Ok(serde_yaml::from_reader(file)?),
+        Some("csv") => csv::Reader::from_reader(file)
+            .deserialize::<CsvOperation>()
+            .map(|row| BatchOperation::try_from(row?))
+            .collect(),
+        _ => Err(anyhow!(
+            "Unsupported batch file {}, expected a .json, .yaml or .csv file",
+            path.display()
+        )),
+    }
+}
+
+pub fn command_batch(
+    config: &JitoStakePoolCliConfig,
+    file: &str,
+    results: Option<&str>,
+) -> anyhow::Result<()> {
+    let path = Path::new(file);
+    let operations = read_batch_file(path)?;
+    if operations.is_empty() {
+        return Err(anyhow!("Batch file {file} has no operations"));
+    }
+
+    // The cluster and program id come from the profile of the first operation,
+    // so every operation naming a profile must name that one
+    let profile_names: Vec<_> = operations
+        .iter()
+        .map(BatchOperation::pool)
+        .filter(|pool| config.profiles.get(pool).is_some())
+        .collect();
+    if let Some(name) = profile_names
+        .iter()
+        .find(|name| **name != operations[0].pool())
+    {
+        return Err(anyhow!(
+            "All operations must use the profile of the first operation, found {} and {name}",
+            operations[0].pool()
+        ));
+    }
+
+    let stake_pool_address = config.profiles.resolve_pool(operations[0].pool())?;
+    if let Some(operation) = operations.iter().find(|operation| {
+        config.profiles.resolve_pool(operation.pool()).ok() != Some(stake_pool_address)
+    }) {
+        return Err(anyhow!(
+            "All operations must target the same pool, found {} and {}",
+            operations[0].pool(),
+            operation.pool()
+        ));
+    }
+
+    let stake_pool =
+        get_stake_pool(&config.accounts, &stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        &stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
+
+    let instructions = validate_operations(
+        config,
+        &stake_pool_address,
+        &stake_pool,
+        &validator_list,
+        &operations,
+    )?;
+
+    let mut batch_results = vec![];
+    for (indexes, transaction_instructions) in pack_instructions(config, instructions)? {
+        println!(
+            "Sending operations {indexes:?} in one transaction, {} instructions",
+            transaction_instructions.len()
+        );
+        let (signature, status, error) =
+            match execute_transaction(config, &transaction_instructions) {
+                Ok(outcome) => outcome,
+                Err(err) => {
+                    println!("{err}");
+                    (None, "error", Some(err.to_string()))
+                }
+            };
+
+        for index in indexes {
+            batch_results.push(BatchResult {
+                index,
+                op: operations[index].name().to_string(),
+                vote_account: operations[index].vote_account().to_string(),
+                signature: signature.clone(),
+                status: status.to_string(),
+                error: error.clone(),
+            });
+        }
+    }
+
+    let results_path = results.map_or_else(|| format!("{file}.results.json"), str::to_string);
+    serde_json::to_writer_pretty(File::create(&results_path)?, &batch_results)?;
+    println!("Results written to {results_path}");
+
+    let failed = batch_results
+        .iter()
+        .filter(|result| result.status != "confirmed" && result.status != "simulated")
+        .count();
+    if failed > 0 {
+        return Err(anyhow!("{failed} operations did not succeed"));
+    }
+
+    Ok(())
+}
+
+/// Simulates or sends one packed transaction. Errors are returned rather than
+/// recorded, so the caller can keep going with the next transaction.
+fn execute_transaction(
+    config: &JitoStakePoolCliConfig,
+    instructions: &[Instruction],
+) -> anyhow::Result<(Option<String>, &'static str, Option<String>)> {
+    let signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    let transaction = checked_transaction_with_signers(config, instructions, &signers)?;
+    if config.dry_run {
+        return Ok(match simulate_transaction(config, &transaction) {
+            Ok(()) => (None, "simulated", None),
+            Err(err) => (None, "failed", Some(err.to_string())),
+        });
+    }
+
+    let message = transaction.message.clone();
+    let status = send_with_retries(config, transaction, &signers)?;
+    println!("{status}");
+    let signature = Some(status.signature().to_string());
+    Ok(match status {
+        TransactionStatus::Confirmed(_) => (signature, "confirmed", None),
+        TransactionStatus::Failed { error, .. } => {
+            let error = explain_transaction_error(&message, &error, &config.program_id)
+                .unwrap_or_else(|| error.to_string());
+            println!("{error}");
+            (signature, "failed", Some(error))
+        }
+        TransactionStatus::Expired(_) => (signature, "expired", None),
+    })
+}
+
+/// Checks every operation against one snapshot of the pool and builds its
+/// instruction. All problems are reported together before anything is sent.
+fn validate_operations(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+    operations: &[BatchOperation],
+) -> anyhow::Result<Vec<(usize, Instruction)>> {
+    let stake_rent =
+        get_minimum_balance_for_rent_exemption(&config.accounts, StakeStateV2::size_of())?;
+    let stake_meta = Meta {
+        rent_exempt_reserve: stake_rent,
+        ..Meta::default()
+    };
+    let reserve_lamports = get_balance(&config.accounts, &stake_pool.reserve_stake)?;
+    let mut available_reserve_lamports =
+        reserve_lamports.saturating_sub(minimum_reserve_lamports(&stake_meta));
+    let mut validator_count = validator_list.validators.len() as u32;
+    // Validators added earlier in the batch can take a stake change right
+    // after, but each validator has a single transient stake account
+    let mut added_validators = HashSet::new();
+    let mut changed_validators = HashSet::new();
+
+    let mut errors = vec![];
+    let mut instructions = vec![];
+    for (index, operation) in operations.iter().enumerate() {
+        let vote_account = match Pubkey::from_str(operation.vote_account()) {
+            Ok(vote_account) => vote_account,
+            Err(err) => {
+                errors.push(format!("#{index}: invalid vote account: {err}"));
+                continue;
+            }
+        };
+
+        match operation {
+            BatchOperation::AddValidator(_) => {
+                if validator_list.contains(&vote_account)
+                    || added_validators.contains(&vote_account)
+                {
+                    errors.push(format!("#{index}: pool already contains {vote_account}"));
+                    continue;
+                }
+                if validator_count >= validator_list.header.max_validators {
+                    errors.push(format!(
+                        "#{index}: pool is full with {} validators",
+                        validator_list.header.max_validators
+                    ));
+                    continue;
+                }
+                validator_count += 1;
+                added_validators.insert(vote_account);
+                instructions.push((
+                    index,
+                    add_validator_to_pool_with_vote(
+                        &config.program_id,
+                        stake_pool,
+                        stake_pool_address,
+                        &config.fee_payer.pubkey(),
+                        &vote_account,
+                    ),
+                ));
+            }
+            BatchOperation::IncreaseValidatorStake(IncreaseValidatorStakeArgs {
+                amount, ..
+            })
+            | BatchOperation::DecreaseValidatorStake(DecreaseValidatorStakeArgs {
+                amount, ..
+            }) => {
+                // A validator added in this batch starts with the minimum
+                // delegation, which cannot be decreased, and transient seed 0
+                let (seed, active_lamports) =
+                    if let Some(validator) = validator_list.find(&vote_account) {
+                        if validator.status != StakeStatus::Active.into() {
+                            errors.push(format!("#{index}: {vote_account} is not active"));
+                            continue;
+                        }
+                        let transient_lamports: u64 = validator.transient_stake_lamports.into();
+                        if transient_lamports > 0 {
+                            errors.push(format!(
+                            "#{index}: {vote_account} already has {} in a transient stake account",
+                            Sol(transient_lamports)
+                        ));
+                            continue;
+                        }
+                        (
+                            validator.transient_seed_suffix.into(),
+                            validator.active_stake_lamports.into(),
+                        )
+                    } else if added_validators.contains(&vote_account) {
+                        (0, 0)
+                    } else {
+                        errors.push(format!(
+                            "#{index}: {vote_account} not found in validator list"
+                        ));
+                        continue;
+                    };
+                if !changed_validators.insert(vote_account) {
+                    errors.push(format!(
+                        "#{index}: {vote_account} already has a stake change earlier in the batch"
+                    ));
+                    continue;
+                }
+                let Some(lamports) = sol_str_to_lamports(&amount.to_string()) else {
+                    errors.push(format!("#{index}: invalid amount {amount}"));
+                    continue;
+                };
+
+                if let BatchOperation::IncreaseValidatorStake(_) = operation {
+                    if lamports < MINIMUM_ACTIVE_STAKE {
+                        errors.push(format!(
+                            "#{index}: increase of {} is below the minimum of {}",
+                            Sol(lamports),
+                            Sol(MINIMUM_ACTIVE_STAKE)
+                        ));
+                        continue;
+                    }
+                    let required = lamports + stake_rent;
+                    if required > available_reserve_lamports {
+                        errors.push(format!(
+                            "#{index}: reserve only has {} available, {} required",
+                            Sol(available_reserve_lamports),
+                            Sol(required)
+                        ));
+                        continue;
+                    }
+                    available_reserve_lamports -= required;
+                    instructions.push((
+                        index,
+                        increase_validator_stake_with_vote(
+                            &config.program_id,
+                            stake_pool,
+                            stake_pool_address,
+                            &vote_account,
+                            lamports,
+                            seed,
+                        ),
+                    ));
+                } else {
+                    let remaining_minimum = minimum_stake_lamports(&stake_meta);
+                    if lamports <= stake_rent
+                        || active_lamports.saturating_sub(lamports) < remaining_minimum
+                    {
+                        errors.push(format!(
+                            "#{index}: cannot decrease {} from {vote_account} with {} active",
+                            Sol(lamports),
+                            Sol(active_lamports)
+                        ));
+                        continue;
+                    }
+                    instructions.push((
+                        index,
+                        decrease_validator_stake_with_vote(
+                            &config.program_id,
+                            stake_pool,
+                            stake_pool_address,
+                            &vote_account,
+                            lamports,
+                            seed,
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow!(
+            "{} of {} operations failed validation, nothing was sent\n{}",
+            errors.len(),
+            operations.len(),
+            errors.join("\n")
+        ));
+    }
+
+    Ok(instructions)
+}
+
+/// Greedily packs instructions, in order, into as few transactions as fit in a packet
+pub fn pack_instructions(
+    config: &JitoStakePoolCliConfig,
+    instructions: Vec<(usize, Instruction)>,
+) -> anyhow::Result<Vec<(Vec<usize>, Vec<Instruction>)>> {
+    let fee_payer = config.fee_payer.pubkey();
+    let fits = |instructions: &[Instruction]| -> anyhow::Result<bool> {
+        let transaction = Transaction::new_unsigned(Message::new(instructions, Some(&fee_payer)));
+        Ok(bincode::serialized_size(&transaction)? as usize <= PACKET_DATA_SIZE)
+    };
+
+    let mut transactions: Vec<(Vec<usize>, Vec<Instruction>)> = vec![];
+    for (index, instruction) in instructions {
+        if let Some((indexes, current)) = transactions.last_mut() {
+            current.push(instruction.clone());
+            if fits(current)? {
+                indexes.push(index);
+                continue;
+            }
+            current.pop();
+        }
+        if !fits(std::slice::from_ref(&instruction))? {
+            return Err(anyhow!("Operation #{index} does not fit in a transaction"));
+        }
+        transactions.push((vec![index], vec![instruction]));
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{hash::Hash, signers::Signers};
+
+    use super::*;
+    use crate::{
+        client::{Simulation, TransactionSink},
+        profile::PoolProfile,
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, system_account, test_config},
+    };
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn increase(pool: &Pubkey, vote_account: &Pubkey, amount: f64) -> BatchOperation {
+        BatchOperation::IncreaseValidatorStake(IncreaseValidatorStakeArgs {
+            pool: pool.to_string(),
+            vote_account: vote_account.to_string(),
+            amount,
+        })
+    }
+
+    fn decrease(pool: &Pubkey, vote_account: &Pubkey, amount: f64) -> BatchOperation {
+        BatchOperation::DecreaseValidatorStake(DecreaseValidatorStakeArgs {
+            pool: pool.to_string(),
+            vote_account: vote_account.to_string(),
+            amount,
+        })
+    }
+
+    fn add(pool: &Pubkey, vote_account: &Pubkey) -> BatchOperation {
+        BatchOperation::AddValidator(AddValidatorArgs {
+            pool: pool.to_string(),
+            vote_account: vote_account.to_string(),
+        })
+    }
+
+    fn pool_with_reserve(config: &JitoStakePoolCliConfig, reserve_lamports: u64) -> TestPool {
+        let mut pool = TestPool::new(config);
+        let reserve_stake = pool.stake_pool.reserve_stake;
+        pool.add_account(reserve_stake, system_account(reserve_lamports));
+        pool
+    }
+
+    /// Fails every send, as when the RPC node cannot be reached
+    struct UnreachableSink;
+
+    impl TransactionSink for UnreachableSink {
+        fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+            Ok(Hash::default())
+        }
+
+        fn get_fee_for_message(&self, _message: &Message) -> anyhow::Result<u64> {
+            Ok(0)
+        }
+
+        fn simulate_transaction(
+            &self,
+            _transaction: &Transaction,
+            _addresses: &[Pubkey],
+        ) -> anyhow::Result<Simulation> {
+            Err(anyhow!("connection refused"))
+        }
+
+        fn send_transaction(
+            &self,
+            _transaction: Transaction,
+            _signers: &dyn Signers,
+            _max_retries: usize,
+        ) -> anyhow::Result<TransactionStatus> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    #[test]
+    fn stakes_validator_added_earlier_in_batch() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        let pool = pool_with_reserve(&config, 100 * SOL);
+        config.accounts = Box::new(pool.accounts());
+        let vote_account = Pubkey::new_unique();
+        let operations = [
+            add(&pool.address, &vote_account),
+            increase(&pool.address, &vote_account, 5.0),
+        ];
+
+        let instructions = validate_operations(
+            &config,
+            &pool.address,
+            &pool.stake_pool,
+            &pool.validator_list,
+            &operations,
+        )
+        .unwrap();
+
+        assert_eq!(
+            instructions,
+            [
+                (
+                    0,
+                    add_validator_to_pool_with_vote(
+                        &config.program_id,
+                        &pool.stake_pool,
+                        &pool.address,
+                        &config.fee_payer.pubkey(),
+                        &vote_account,
+                    )
+                ),
+                (
+                    1,
+                    increase_validator_stake_with_vote(
+                        &config.program_id,
+                        &pool.stake_pool,
+                        &pool.address,
+                        &vote_account,
+                        5 * SOL,
+                        0,
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_every_invalid_operation() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        let mut pool = pool_with_reserve(&config, 100 * SOL);
+        let changed_twice = pool.add_validator(3);
+        let staked = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+        let (unknown_increase, unknown_decrease) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let operations = [
+            increase(&pool.address, &changed_twice, 5.0),
+            decrease(&pool.address, &changed_twice, 1.0),
+            decrease(&pool.address, &staked, 10.0),
+            increase(&pool.address, &unknown_increase, 5.0),
+            decrease(&pool.address, &unknown_decrease, 1.0),
+            add(&pool.address, &staked),
+        ];
+
+        let error = validate_operations(
+            &config,
+            &pool.address,
+            &pool.stake_pool,
+            &pool.validator_list,
+            &operations,
+        )
+        .unwrap_err();
+
+        // Only the first operation passes
+        assert_eq!(
+            error.to_string().lines().collect::<Vec<_>>(),
+            [
+                "5 of 6 operations failed validation, nothing was sent".to_string(),
+                format!("#1: {changed_twice} already has a stake change earlier in the batch"),
+                format!(
+                    "#2: cannot decrease ◎10.000000000 from {staked} with ◎10.000000000 active"
+                ),
+                format!("#3: {unknown_increase} not found in validator list"),
+                format!("#4: {unknown_decrease} not found in validator list"),
+                format!("#5: pool already contains {staked}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_increases_to_available_reserve() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        let mut pool = pool_with_reserve(&config, 8 * SOL);
+        let first = pool.add_validator(0);
+        let second = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+        let validate = |operations: &[BatchOperation]| {
+            validate_operations(
+                &config,
+                &pool.address,
+                &pool.stake_pool,
+                &pool.validator_list,
+                operations,
+            )
+        };
+
+        assert!(validate(&[increase(&pool.address, &first, 5.0)]).is_ok());
+        assert!(
+            validate(&[
+                increase(&pool.address, &first, 5.0),
+                increase(&pool.address, &second, 3.0),
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn packs_instructions_in_order_into_packets() {
+        let (config, _) = test_config(MemoryTransactions::default());
+        let pool = TestPool::new(&config);
+        let instructions: Vec<_> = (0..20)
+            .map(|index| {
+                (
+                    index,
+                    increase_validator_stake_with_vote(
+                        &config.program_id,
+                        &pool.stake_pool,
+                        &pool.address,
+                        &Pubkey::new_unique(),
+                        SOL,
+                        0,
+                    ),
+                )
+            })
+            .collect();
+
+        let transactions = pack_instructions(&config, instructions.clone()).unwrap();
+
+        assert!(transactions.len() > 1);
+        let fee_payer = config.fee_payer.pubkey();
+        for (indexes, transaction_instructions) in &transactions {
+            assert_eq!(indexes.len(), transaction_instructions.len());
+            let transaction =
+                Transaction::new_unsigned(Message::new(transaction_instructions, Some(&fee_payer)));
+            assert!(bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE);
+        }
+        let packed: Vec<_> = transactions
+            .into_iter()
+            .flat_map(|(indexes, instructions)| indexes.into_iter().zip(instructions))
+            .collect();
+        assert_eq!(packed, instructions);
+    }
+
+    #[test]
+    fn sends_operations_and_writes_results() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = pool_with_reserve(&config, 100 * SOL);
+        let increased = pool.add_validator(2);
+        let decreased = pool.add_validator(4);
+        config.accounts = Box::new(pool.accounts());
+        let file = std::env::temp_dir().join(format!("batch-{}.csv", Pubkey::new_unique()));
+        let results = file.with_extension("results.json");
+        std::fs::write(
+            &file,
+            format!(
+                "op,pool,vote_account,amount\n\
+                 increase-validator-stake,{0},{increased},5\n\
+                 decrease-validator-stake,{0},{decreased},1\n",
+                pool.address
+            ),
+        )
+        .unwrap();
+
+        command_batch(&config, file.to_str().unwrap(), results.to_str()).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_reader(File::open(&results).unwrap()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        std::fs::remove_file(&results).unwrap();
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let fee_payer = config.fee_payer.pubkey();
+        assert_eq!(sent[0].signers, [fee_payer, config.staker.pubkey()]);
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[
+                increase_validator_stake_with_vote(
+                    &config.program_id,
+                    &pool.stake_pool,
+                    &pool.address,
+                    &increased,
+                    5 * SOL,
+                    2,
+                ),
+                decrease_validator_stake_with_vote(
+                    &config.program_id,
+                    &pool.stake_pool,
+                    &pool.address,
+                    &decreased,
+                    SOL,
+                    4,
+                ),
+            ],
+        );
+        let signature = sent[0].transaction.signatures[0].to_string();
+        for (index, op) in ["increase-validator-stake", "decrease-validator-stake"]
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(written[index]["op"], op);
+            assert_eq!(written[index]["status"], "confirmed");
+            assert_eq!(written[index]["signature"], signature);
+        }
+    }
+
+    #[test]
+    fn writes_results_when_sending_fails() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        let mut pool = pool_with_reserve(&config, 100 * SOL);
+        let vote_account = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+        config.transactions = Box::new(UnreachableSink);
+        let file = std::env::temp_dir().join(format!("batch-{}.json", Pubkey::new_unique()));
+        let results = file.with_extension("results.json");
+        serde_json::to_writer(
+            File::create(&file).unwrap(),
+            &serde_json::json!([{
+                "op": "increase-validator-stake",
+                "pool": pool.address.to_string(),
+                "vote_account": vote_account.to_string(),
+                "amount": 5,
+            }]),
+        )
+        .unwrap();
+
+        let error = command_batch(&config, file.to_str().unwrap(), results.to_str()).unwrap_err();
+
+        let written: serde_json::Value =
+            serde_json::from_reader(File::open(&results).unwrap()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        std::fs::remove_file(&results).unwrap();
+        assert_eq!(error.to_string(), "1 operations did not succeed");
+        assert_eq!(written[0]["status"], "error");
+        assert_eq!(written[0]["error"], "connection refused");
+    }
+
+    #[test]
+    fn rejects_operations_on_another_profile() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let pool = Pubkey::new_unique().to_string();
+        for (name, url) in [
+            ("mainnet", "https://api.mainnet-beta.solana.com"),
+            ("devnet", "https://api.devnet.solana.com"),
+        ] {
+            config.profiles.profiles.insert(
+                name.to_string(),
+                PoolProfile {
+                    pool: pool.clone(),
+                    url: Some(url.to_string()),
+                    ..PoolProfile::default()
+                },
+            );
+        }
+        let file = std::env::temp_dir().join(format!("batch-{}.csv", Pubkey::new_unique()));
+        std::fs::write(
+            &file,
+            format!(
+                "op,pool,vote_account,amount\n\
+                 add-validator,mainnet,{0},\n\
+                 add-validator,{pool},{0},\n\
+                 add-validator,devnet,{0},\n",
+                Pubkey::new_unique()
+            ),
+        )
+        .unwrap();
+
+        let error = command_batch(&config, file.to_str().unwrap(), None).unwrap_err();
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            error.to_string(),
+            "All operations must use the profile of the first operation, found mainnet and devnet"
+        );
+        assert!(transactions.sent().is_empty());
+    }
+}
@@ -0,0 +1,476 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::{
+    sdk::update_stake_pool::{
+        cleanup_removed_validator_entries, update_stake_pool_balance_with_pool,
+    },
+    update_plan::{UpdateOptions, UpdateStep, plan_update},
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{get_epoch, get_stake_pool, get_validator_list},
+    config::JitoStakePoolCliConfig,
+    send_transaction,
+};
+
+/// Delay before the first retry of a failed step, doubled on every attempt
+const RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Longest delay between two retries of a failed step
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+#[derive(Args)]
+pub struct CrankArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// File storing the update progress, so that a restart resumes where it stopped [default: crank-<POOL>.json]
+    #[arg(long = "state-file", value_name = "PATH")]
+    pub state_file: Option<String>,
+
+    /// Seconds between two epoch checks
+    #[arg(long = "poll-interval", value_name = "SECONDS", default_value_t = 60)]
+    pub poll_interval: u64,
+
+    /// Do not automatically merge transient stakes
+    #[arg(long = "no-merge")]
+    pub no_merge: bool,
+
+    /// Attempts at a failing step before the crank stops with an error
+    #[arg(long = "max-attempts", value_name = "COUNT", default_value_t = 20)]
+    pub max_attempts: u32,
+}
+
+/// How a failing step is retried
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    /// Delay before the first retry, doubled on every attempt up to `MAX_RETRY_BACKOFF`
+    pub delay: Duration,
+    /// Attempts before giving up, the first one included
+    pub max_attempts: u32,
+}
+
+impl Backoff {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            delay: RETRY_BACKOFF,
+            max_attempts,
+        }
+    }
+}
+
+/// Next step of the epoch update
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrankStage {
+    #[default]
+    UpdateValidatorList,
+    UpdateStakePool,
+    Cleanup,
+    Done,
+}
+
+/// Progress of the update for one epoch, persisted after every step
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrankState {
+    pub epoch: u64,
+    pub stage: CrankStage,
+    pub next_validator_index: usize,
+}
+
+impl CrankState {
+    /// Loads the state file, a missing file meaning nothing was done yet
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)
+            .map_err(|err| anyhow!("Unable to open state file {}: {err}", path.display()))?;
+        serde_json::from_reader(file)
+            .map_err(|err| anyhow!("Invalid state file {}: {err}", path.display()))
+    }
+
+    /// Writes the state next to `path` first and renames it, so that a crash
+    /// never leaves a truncated file behind
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let temporary = path.with_extension("tmp");
+        serde_json::to_writer_pretty(File::create(&temporary)?, self)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    fn start_epoch(epoch: u64) -> Self {
+        Self {
+            epoch,
+            ..Self::default()
+        }
+    }
+}
+
+pub fn command_crank(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    state_file: Option<&str>,
+    poll_interval: u64,
+    no_merge: bool,
+    backoff: Backoff,
+) -> anyhow::Result<()> {
+    let state_file = state_file
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("crank-{stake_pool_address}.json")));
+    let mut state = CrankState::load(&state_file)?;
+    println!(
+        "Cranking stake pool {stake_pool_address}, progress in {}",
+        state_file.display()
+    );
+
+    loop {
+        let epoch = with_backoff(config, backoff, || get_epoch(&config.accounts))?;
+        if epoch > state.epoch {
+            println!("Epoch {epoch} started");
+            state = CrankState::start_epoch(epoch);
+        }
+
+        if state.stage != CrankStage::Done {
+            crank_epoch(
+                config,
+                stake_pool_address,
+                &mut state,
+                &state_file,
+                no_merge,
+                backoff,
+            )?;
+        }
+
+        if config.dry_run {
+            return Ok(());
+        }
+        sleep(Duration::from_secs(poll_interval));
+    }
+}
+
+/// Runs the remaining steps of the update for `state.epoch`, saving the state
+/// after each of them. Stops early if the epoch changes in the meantime, and
+/// after simulating the next step in a dry run.
+fn crank_epoch(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    state: &mut CrankState,
+    state_file: &Path,
+    no_merge: bool,
+    backoff: Backoff,
+) -> anyhow::Result<()> {
+    while state.stage != CrankStage::Done {
+        let (stake_pool, validator_list) = with_backoff(config, backoff, || {
+            let stake_pool =
+                get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+            let validator_list = get_validator_list(
+                &config.accounts,
+                &config.program_id,
+                stake_pool_address,
+                &stake_pool,
+                config.layout,
+            )?
+            .validator_list;
+            Ok((stake_pool, validator_list))
+        })?;
+
+        if state.stage == CrankStage::UpdateValidatorList
+            && stake_pool.last_update_epoch >= state.epoch
+            && state.next_validator_index == 0
+        {
+            println!("Stake pool already updated for epoch {}", state.epoch);
+            state.stage = CrankStage::Done;
+            save_state(config, state, state_file)?;
+            break;
+        }
+
+        let (instructions, next) = match state.stage {
+            CrankStage::UpdateValidatorList => {
+                let batch = plan_update(
+                    &config.program_id,
+                    stake_pool_address,
+                    &stake_pool,
+                    &validator_list,
+                    state.epoch,
+                    UpdateOptions {
+                        force: false,
+                        no_merge,
+                    },
+                )
+                .into_iter()
+                .find_map(|batch| match batch.step {
+                    UpdateStep::ValidatorList {
+                        start_index,
+                        end_index,
+                    } if start_index >= state.next_validator_index => {
+                        Some((start_index, end_index, batch.instructions))
+                    }
+                    _ => None,
+                });
+                let Some((start_index, end_index, instructions)) = batch else {
+                    state.stage = CrankStage::UpdateStakePool;
+                    continue;
+                };
+                println!(
+                    "Updating validators {start_index}..{end_index} of {}",
+                    validator_list.validators.len()
+                );
+                let next = CrankState {
+                    next_validator_index: end_index,
+                    ..state.clone()
+                };
+                (instructions, next)
+            }
+            CrankStage::UpdateStakePool => {
+                println!("Updating stake pool balance");
+                let instruction = update_stake_pool_balance_with_pool(
+                    &config.program_id,
+                    &stake_pool,
+                    stake_pool_address,
+                );
+                let next = CrankState {
+                    stage: CrankStage::Cleanup,
+                    ..state.clone()
+                };
+                (vec![instruction], next)
+            }
+            CrankStage::Cleanup => {
+                println!("Cleaning up removed validator entries");
+                let instruction = cleanup_removed_validator_entries(
+                    &config.program_id,
+                    stake_pool_address,
+                    &stake_pool.validator_list,
+                );
+                let next = CrankState {
+                    stage: CrankStage::Done,
+                    ..state.clone()
+                };
+                (vec![instruction], next)
+            }
+            CrankStage::Done => break,
+        };
+
+        with_backoff(config, backoff, || {
+            let epoch = get_epoch(&config.accounts)?;
+            if epoch != state.epoch {
+                return Ok(());
+            }
+            send_instructions(config, &instructions)
+        })?;
+        if config.dry_run {
+            return Ok(());
+        }
+        if get_epoch(&config.accounts)? != state.epoch {
+            println!("Epoch changed during the update, starting over");
+            return Ok(());
+        }
+
+        *state = next;
+        save_state(config, state, state_file)?;
+    }
+
+    println!("Epoch {} fully updated", state.epoch);
+    Ok(())
+}
+
+fn send_instructions(
+    config: &JitoStakePoolCliConfig,
+    instructions: &[Instruction],
+) -> anyhow::Result<()> {
+    let signers = vec![config.fee_payer.as_ref()];
+    let transaction = checked_transaction_with_signers(config, instructions, &signers)?;
+    send_transaction(config, transaction, &signers)
+}
+
+fn save_state(
+    config: &JitoStakePoolCliConfig,
+    state: &CrankState,
+    state_file: &Path,
+) -> anyhow::Result<()> {
+    if config.dry_run {
+        return Ok(());
+    }
+    state.save(state_file)
+}
+
+/// Retries `f` until it succeeds or `backoff.max_attempts` attempts failed,
+/// waiting longer after every failure. Dry runs fail on the first error instead.
+fn with_backoff<T>(
+    config: &JitoStakePoolCliConfig,
+    backoff: Backoff,
+    mut f: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut delay = backoff.delay;
+    let mut failures = 0;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if config.dry_run => return Err(err),
+            Err(err) => {
+                failures += 1;
+                if failures >= backoff.max_attempts {
+                    return Err(anyhow!("Giving up after {failures} failed attempts: {err}"));
+                }
+                eprintln!(
+                    "{err}, attempt {failures} of {} failed, retrying in {}s",
+                    backoff.max_attempts,
+                    delay.as_secs()
+                );
+                sleep(delay);
+                delay = (delay * 2).min(MAX_RETRY_BACKOFF);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jito_stake_pool_sdk::address::MAX_VALIDATORS_TO_UPDATE;
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{EPOCH, MAX_VALIDATORS, TestPool, assert_instructions, test_config},
+    };
+
+    const NO_DELAY: Backoff = Backoff {
+        delay: Duration::ZERO,
+        max_attempts: 3,
+    };
+
+    fn state_file() -> PathBuf {
+        std::env::temp_dir().join(format!("crank-{}.json", Pubkey::new_unique()))
+    }
+
+    #[test]
+    fn resumes_from_saved_validator_index() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        for _ in 0..MAX_VALIDATORS {
+            pool.add_validator(0);
+        }
+        config.accounts = Box::new(pool.accounts());
+        let state_file = state_file();
+        assert_eq!(
+            CrankState::load(&state_file).unwrap(),
+            CrankState::default()
+        );
+        CrankState {
+            epoch: EPOCH,
+            stage: CrankStage::UpdateValidatorList,
+            next_validator_index: MAX_VALIDATORS_TO_UPDATE,
+        }
+        .save(&state_file)
+        .unwrap();
+
+        let mut state = CrankState::load(&state_file).unwrap();
+        crank_epoch(
+            &config,
+            &pool.address,
+            &mut state,
+            &state_file,
+            false,
+            NO_DELAY,
+        )
+        .unwrap();
+
+        let expected: Vec<_> = plan_update(
+            &config.program_id,
+            &pool.address,
+            &pool.stake_pool,
+            &pool.validator_list,
+            EPOCH,
+            UpdateOptions::default(),
+        )
+        .into_iter()
+        .filter(|batch| {
+            !matches!(batch.step, UpdateStep::ValidatorList { start_index, .. }
+                if start_index < MAX_VALIDATORS_TO_UPDATE)
+        })
+        .collect();
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), expected.len());
+        for (sent, batch) in sent.iter().zip(&expected) {
+            assert_instructions(sent, &config.fee_payer.pubkey(), &batch.instructions);
+        }
+        let saved = CrankState::load(&state_file).unwrap();
+        std::fs::remove_file(&state_file).unwrap();
+        assert_eq!(
+            saved,
+            CrankState {
+                epoch: EPOCH,
+                stage: CrankStage::Done,
+                next_validator_index: MAX_VALIDATORS as usize,
+            }
+        );
+    }
+
+    #[test]
+    fn resumes_at_saved_stage() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+        let state_file = state_file();
+        let mut state = CrankState {
+            epoch: EPOCH,
+            stage: CrankStage::Cleanup,
+            next_validator_index: 1,
+        };
+
+        crank_epoch(
+            &config,
+            &pool.address,
+            &mut state,
+            &state_file,
+            false,
+            NO_DELAY,
+        )
+        .unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        assert_instructions(
+            &sent[0],
+            &config.fee_payer.pubkey(),
+            &[cleanup_removed_validator_entries(
+                &config.program_id,
+                &pool.address,
+                &pool.stake_pool.validator_list,
+            )],
+        );
+        let saved = CrankState::load(&state_file).unwrap();
+        std::fs::remove_file(&state_file).unwrap();
+        assert_eq!(saved.stage, CrankStage::Done);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let (config, _) = test_config(MemoryTransactions::default());
+        let mut attempts = 0;
+
+        let error = with_backoff(&config, NO_DELAY, || -> anyhow::Result<()> {
+            attempts += 1;
+            Err(anyhow!("connection refused"))
+        })
+        .unwrap_err();
+
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            error.to_string(),
+            "Giving up after 3 failed attempts: connection refused"
+        );
+    }
+}
@@ -1,11 +1,124 @@
+use anyhow::anyhow;
 use clap::Args;
+use jito_stake_pool_sdk::sdk::decrease_validator_stake::decrease_validator_stake_with_vote;
+use serde::Deserialize;
+use solana_sdk::{native_token::sol_str_to_lamports, pubkey::Pubkey};
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{get_stake_pool, get_validator_list},
+    config::JitoStakePoolCliConfig,
+    send_transaction,
+};
+
+#[derive(Args, Deserialize)]
+pub struct DecreaseValidatorStakeArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
 
-#[derive(Args)]
-struct DecreaseValidatorStakeArgs {
-    /// Stake pool address
-    pool: String,
     /// Vote account for the validator to decrease stake from
-    vote_account: String,
+    pub vote_account: String,
+
     /// Amount in SOL to remove from the validator stake account. Must be at least the rent-exempt amount for a stake.
-    amount: Option<f64>,
+    pub amount: f64,
+}
+
+pub fn command_decrease_validator_stake(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    vote_account: &Pubkey,
+    amount: f64,
+) -> anyhow::Result<()> {
+    let lamports = sol_str_to_lamports(&amount.to_string())
+        .ok_or_else(|| anyhow!("Invalid amount {amount}"))?;
+
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
+    let validator_stake_info = validator_list
+        .find(vote_account)
+        .ok_or(anyhow!("Vote account not found in validator list"))?;
+
+    let signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    let seed: u64 = validator_stake_info.transient_seed_suffix.into();
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[decrease_validator_stake_with_vote(
+            &config.program_id,
+            &stake_pool,
+            stake_pool_address,
+            vote_account,
+            lamports,
+            seed,
+        )],
+        &signers,
+    )?;
+
+    send_transaction(config, transaction, &signers)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, test_config},
+    };
+
+    #[test]
+    fn decreases_stake_with_transient_seed_from_validator_list() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let vote_account = pool.add_validator(5);
+        pool.add_validator(2);
+        config.accounts = Box::new(pool.accounts());
+
+        command_decrease_validator_stake(&config, &pool.address, &vote_account, 0.25).unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let fee_payer = config.fee_payer.pubkey();
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[decrease_validator_stake_with_vote(
+                &config.program_id,
+                &pool.stake_pool,
+                &pool.address,
+                &vote_account,
+                250_000_000,
+                5,
+            )],
+        );
+        assert_eq!(sent[0].signers, [fee_payer, config.staker.pubkey()]);
+    }
+
+    #[test]
+    fn fails_for_unknown_vote_account() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+
+        let error =
+            command_decrease_validator_stake(&config, &pool.address, &Pubkey::new_unique(), 1.0)
+                .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Vote account not found in validator list"
+        );
+        assert!(transactions.sent().is_empty());
+    }
 }
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
 use clap::Args;
+use jito_stake_pool_sdk::address::find_withdraw_authority_program_address;
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
     instruction::Instruction,
     message::Message,
     native_token::{self, Sol},
@@ -10,16 +10,18 @@ use solana_sdk::{
     signer::Signer,
     transaction::Transaction,
 };
-use spl_stake_pool::find_withdraw_authority_program_address;
 
 use crate::{
-    add_associated_token_account, client::get_stake_pool, config::JitoStakePoolCliConfig,
+    add_associated_token_account,
+    client::{get_balance, get_stake_pool, get_token_mint},
+    config::JitoStakePoolCliConfig,
     send_transaction,
+    simulate::simulate_balance_delta,
 };
 
 #[derive(Args)]
 pub struct DepositSolArgs {
-    /// Stake pool address
+    /// Stake pool address or profile name
     pub pool: String,
 
     /// Amount in SOL to deposit into the stake pool reserve account.
@@ -35,6 +37,10 @@ pub struct DepositSolArgs {
     /// Account to receive the referral fees for deposits. Defaults to the token receiver.
     #[arg(long, value_name = "REFERRER_TOKEN_ADDRESS")]
     pub referrer: Option<String>,
+
+    /// Abort if the deposit, simulated first, mints fewer pool tokens than this
+    #[arg(long = "min-pool-tokens-out", value_name = "AMOUNT")]
+    pub min_pool_tokens_out: Option<f64>,
 }
 
 pub fn command_deposit_sol(
@@ -44,6 +50,7 @@ pub fn command_deposit_sol(
     pool_token_receiver_account: &Option<Pubkey>,
     referrer_token_account: &Option<Pubkey>,
     amount: f64,
+    min_pool_tokens_out: Option<f64>,
 ) -> anyhow::Result<()> {
     // if !config.no_update {
     //     command_update(config, stake_pool_address, false, false)?;
@@ -55,7 +62,7 @@ pub fn command_deposit_sol(
     let from_pubkey = from
         .as_ref()
         .map_or_else(|| config.fee_payer.pubkey(), |keypair| keypair.pubkey());
-    let from_balance = config.rpc_client.get_balance(&from_pubkey)?;
+    let from_balance = get_balance(&config.accounts, &from_pubkey)?;
     if from_balance < amount {
         return Err(anyhow!(
             "Not enough SOL to deposit into pool: {}.\nMaximum deposit amount is {} SOL.",
@@ -64,7 +71,8 @@ pub fn command_deposit_sol(
         ));
     }
 
-    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
 
     let mut instructions: Vec<Instruction> = vec![];
 
@@ -97,7 +105,7 @@ pub fn command_deposit_sol(
     let referrer_token_account = referrer_token_account.unwrap_or(pool_token_receiver_account);
 
     let pool_withdraw_authority =
-        find_withdraw_authority_program_address(&spl_stake_pool::id(), stake_pool_address).0;
+        find_withdraw_authority_program_address(&config.program_id, stake_pool_address).0;
 
     let deposit_instruction = if let Some(deposit_authority) = config.funding_authority.as_ref() {
         let expected_sol_deposit_authority = stake_pool.sol_deposit_authority.ok_or_else(|| {
@@ -114,7 +122,7 @@ pub fn command_deposit_sol(
         }
 
         spl_stake_pool::instruction::deposit_sol_with_authority(
-            &spl_stake_pool::id(),
+            &config.program_id,
             stake_pool_address,
             &deposit_authority.pubkey(),
             &pool_withdraw_authority,
@@ -129,7 +137,7 @@ pub fn command_deposit_sol(
         )
     } else {
         spl_stake_pool::instruction::deposit_sol(
-            &spl_stake_pool::id(),
+            &config.program_id,
             stake_pool_address,
             &pool_withdraw_authority,
             &stake_pool.reserve_stake,
@@ -145,10 +153,7 @@ pub fn command_deposit_sol(
 
     instructions.push(deposit_instruction);
 
-    let recent_blockhash = config
-        .rpc_client
-        .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())?
-        .0;
+    let recent_blockhash = config.transactions.get_latest_blockhash()?;
     let message = Message::new_with_blockhash(
         &instructions,
         Some(&config.fee_payer.pubkey()),
@@ -161,7 +166,207 @@ pub fn command_deposit_sol(
     // unique_signers!(signers);
     let transaction = Transaction::new(&signers, message, recent_blockhash);
 
-    send_transaction(config, transaction)?;
+    if let Some(min_pool_tokens_out) = min_pool_tokens_out {
+        let decimals = get_token_mint(&config.accounts, &stake_pool.pool_mint)?.decimals;
+        let minimum = spl_token::ui_amount_to_amount(min_pool_tokens_out, decimals);
+        let pool_tokens_out =
+            simulate_balance_delta(config, &transaction, &pool_token_receiver_account)?;
+        if pool_tokens_out < minimum as i128 {
+            return Err(anyhow!(
+                "Deposit would mint {} pool tokens, below the minimum of {}",
+                spl_token::amount_to_ui_amount_string_trimmed(
+                    pool_tokens_out.max(0) as u64,
+                    decimals
+                ),
+                spl_token::amount_to_ui_amount_string_trimmed(minimum, decimals),
+            ));
+        }
+    }
+
+    send_transaction(config, transaction, &signers)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+    use spl_associated_token_account::get_associated_token_address;
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, system_account, test_config, token_account},
+    };
+
+    #[test]
+    fn deposits_into_existing_token_account() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let fee_payer = config.fee_payer.pubkey();
+        let token_owner = config.token_owner.pubkey();
+        let receiver = get_associated_token_address(&token_owner, &pool.stake_pool.pool_mint);
+        pool.add_account(fee_payer, system_account(10 * LAMPORTS_PER_SOL));
+        pool.add_token_account(receiver, &token_owner, 0);
+        let from = Keypair::new();
+        let from_pubkey = from.pubkey();
+        pool.add_account(from_pubkey, system_account(10 * LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+
+        command_deposit_sol(&config, &pool.address, &Some(from), &None, &None, 2.0, None).unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let ephemeral = sent[0].signers[1];
+        assert_eq!(sent[0].signers, [fee_payer, ephemeral, from_pubkey]);
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[
+                solana_system_interface::instruction::transfer(
+                    &from_pubkey,
+                    &ephemeral,
+                    2 * LAMPORTS_PER_SOL,
+                ),
+                spl_stake_pool::instruction::deposit_sol(
+                    &config.program_id,
+                    &pool.address,
+                    &find_withdraw_authority_program_address(&config.program_id, &pool.address).0,
+                    &pool.stake_pool.reserve_stake,
+                    &ephemeral,
+                    &receiver,
+                    &pool.stake_pool.manager_fee_account,
+                    &receiver,
+                    &pool.stake_pool.pool_mint,
+                    &spl_token::id(),
+                    2 * LAMPORTS_PER_SOL,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn creates_missing_associated_token_account() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let fee_payer = config.fee_payer.pubkey();
+        let token_owner = config.token_owner.pubkey();
+        pool.add_account(fee_payer, system_account(10 * LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+        let referrer = Pubkey::new_unique();
+
+        command_deposit_sol(
+            &config,
+            &pool.address,
+            &None,
+            &None,
+            &Some(referrer),
+            1.0,
+            None,
+        )
+        .unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let ephemeral = sent[0].signers[1];
+        assert_eq!(sent[0].signers, [fee_payer, ephemeral]);
+        let receiver = get_associated_token_address(&token_owner, &pool.stake_pool.pool_mint);
+        #[allow(deprecated)]
+        let create_token_account =
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &fee_payer,
+                &token_owner,
+                &pool.stake_pool.pool_mint,
+                &spl_token::id(),
+            );
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[
+                solana_system_interface::instruction::transfer(
+                    &fee_payer,
+                    &ephemeral,
+                    LAMPORTS_PER_SOL,
+                ),
+                create_token_account,
+                spl_stake_pool::instruction::deposit_sol(
+                    &config.program_id,
+                    &pool.address,
+                    &find_withdraw_authority_program_address(&config.program_id, &pool.address).0,
+                    &pool.stake_pool.reserve_stake,
+                    &ephemeral,
+                    &receiver,
+                    &pool.stake_pool.manager_fee_account,
+                    &referrer,
+                    &pool.stake_pool.pool_mint,
+                    &spl_token::id(),
+                    LAMPORTS_PER_SOL,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn fails_without_enough_sol() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_account(config.fee_payer.pubkey(), system_account(LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+
+        let error = command_deposit_sol(&config, &pool.address, &None, &None, &None, 1.5, None)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Not enough SOL to deposit into pool: ◎1.500000000.\nMaximum deposit amount is ◎1.000000000 SOL."
+        );
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn rejects_funding_authority_when_pool_has_none() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_account(config.fee_payer.pubkey(), system_account(LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+        config.funding_authority = Some(Box::new(Keypair::new()));
+
+        let error = command_deposit_sol(&config, &pool.address, &None, &None, &None, 1.0, None)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "SOL deposit authority specified in arguments but stake pool has none"
+        );
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn aborts_below_min_pool_tokens_out() {
+        let receiver = Pubkey::new_unique();
+        let minted = token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 900_000_000);
+        let (mut config, transactions) =
+            test_config(MemoryTransactions::default().with_post_account(receiver, minted));
+        let mut pool = TestPool::new(&config);
+        pool.add_account(config.fee_payer.pubkey(), system_account(LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+
+        let error = command_deposit_sol(
+            &config,
+            &pool.address,
+            &None,
+            &Some(receiver),
+            &None,
+            1.0,
+            Some(0.95),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Deposit would mint 0.9 pool tokens, below the minimum of 0.95"
+        );
+        assert_eq!(transactions.simulated().len(), 1);
+        assert!(transactions.sent().is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::anyhow;
 use clap::Args;
 use jito_stake_pool_sdk::sdk::increase_validator_stake::increase_validator_stake_with_vote;
+use serde::Deserialize;
 use solana_sdk::{native_token::sol_str_to_lamports, pubkey::Pubkey};
 
 use crate::{
@@ -10,16 +11,16 @@ use crate::{
     send_transaction,
 };
 
-#[derive(Args)]
+#[derive(Args, Deserialize)]
 pub struct IncreaseValidatorStakeArgs {
-    /// Stake pool address
+    /// Stake pool address or profile name
     pub pool: String,
 
     /// Vote account for the validator to increase stake to
     pub vote_account: String,
 
     /// Amount in SOL to add to the validator stake account. Must be at least the rent-exempt amount for a stake plus 1 SOL for merging.
-    pub amount: Option<f64>,
+    pub amount: f64,
 }
 
 pub fn command_increase_validator_stake(
@@ -28,13 +29,22 @@ pub fn command_increase_validator_stake(
     vote_account: &Pubkey,
     amount: f64,
 ) -> anyhow::Result<()> {
-    let lamports = sol_str_to_lamports(&amount.to_string()).unwrap();
+    let lamports = sol_str_to_lamports(&amount.to_string())
+        .ok_or_else(|| anyhow!("Invalid amount {amount}"))?;
     // if !config.no_update {
     //     command_update(config, stake_pool_address, false, false)?;
     // }
 
-    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
-    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
     let validator_stake_info = validator_list
         .find(vote_account)
         .ok_or(anyhow!("Vote account not found in validator list"))?;
@@ -45,7 +55,7 @@ pub fn command_increase_validator_stake(
     let transaction = checked_transaction_with_signers(
         config,
         &[increase_validator_stake_with_vote(
-            &spl_stake_pool::id(),
+            &config.program_id,
             &stake_pool,
             stake_pool_address,
             vote_account,
@@ -55,7 +65,97 @@ pub fn command_increase_validator_stake(
         &signers,
     )?;
 
-    send_transaction(config, transaction)?;
+    send_transaction(config, transaction, &signers)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{signer::Signer, transaction::TransactionError};
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, test_config},
+    };
+
+    #[test]
+    fn increases_stake_with_transient_seed_from_validator_list() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_validator(3);
+        let vote_account = pool.add_validator(7);
+        config.accounts = Box::new(pool.accounts());
+
+        command_increase_validator_stake(&config, &pool.address, &vote_account, 1.5).unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let fee_payer = config.fee_payer.pubkey();
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[increase_validator_stake_with_vote(
+                &config.program_id,
+                &pool.stake_pool,
+                &pool.address,
+                &vote_account,
+                1_500_000_000,
+                7,
+            )],
+        );
+        assert_eq!(sent[0].signers, [fee_payer, config.staker.pubkey()]);
+    }
+
+    #[test]
+    fn fails_for_unknown_vote_account() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+
+        let error =
+            command_increase_validator_stake(&config, &pool.address, &Pubkey::new_unique(), 1.0)
+                .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Vote account not found in validator list"
+        );
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn dry_run_simulates_instead_of_sending() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let vote_account = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+        config.dry_run = true;
+
+        command_increase_validator_stake(&config, &pool.address, &vote_account, 1.0).unwrap();
+
+        assert_eq!(transactions.simulated().len(), 1);
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn reports_failed_transaction() {
+        let (mut config, transactions) = test_config(
+            MemoryTransactions::default().with_error(TransactionError::InsufficientFundsForFee),
+        );
+        let mut pool = TestPool::new(&config);
+        let vote_account = pool.add_validator(0);
+        config.accounts = Box::new(pool.accounts());
+
+        let error = command_increase_validator_stake(&config, &pool.address, &vote_account, 1.0)
+            .unwrap_err();
+
+        let signature = transactions.sent()[0].transaction.signatures[0];
+        assert_eq!(
+            error.to_string(),
+            format!("Transaction {signature} was not confirmed")
+        );
+    }
+}
@@ -0,0 +1,811 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::decode::{DecodeError, DecodedInstruction, decode_compiled_instruction};
+use serde::Serialize;
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_sdk::{bs58, instruction::CompiledInstruction, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiTransactionEncoding,
+    UiTransactionTokenBalance,
+};
+
+use crate::config::{JitoStakePoolCliConfig, OutputFormat};
+
+#[derive(Args)]
+pub struct InspectTxArgs {
+    /// Signature of the transaction to inspect
+    pub signature: String,
+
+    /// Pool profile to take the cluster and program id from
+    #[arg(long, value_name = "PROFILE")]
+    pub pool: Option<String>,
+
+    /// Name of the pool token in the summary
+    #[arg(long, value_name = "SYMBOL", default_value = "pool tokens")]
+    pub symbol: String,
+}
+
+/// Stake pool instruction of a transaction, with its accounts labeled
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectedInstruction {
+    /// Position in the transaction, `<outer>.<inner>` for inner instructions
+    pub index: String,
+    pub name: String,
+    pub accounts: Vec<LabeledAccount>,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabeledAccount {
+    pub label: String,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LamportDelta {
+    pub account: String,
+    pub pre_balance: u64,
+    pub post_balance: u64,
+    pub delta: i128,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDelta {
+    pub account: String,
+    pub mint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub decimals: u8,
+    pub pre_amount: u64,
+    pub post_amount: u64,
+    pub delta: i128,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInspection {
+    pub signature: String,
+    pub slot: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub fee: u64,
+    pub instructions: Vec<InspectedInstruction>,
+    pub lamport_deltas: Vec<LamportDelta>,
+    pub token_deltas: Vec<TokenDelta>,
+}
+
+pub fn command_inspect_tx(
+    config: &JitoStakePoolCliConfig,
+    signature: &str,
+    symbol: &str,
+) -> anyhow::Result<()> {
+    // Snapshots only hold accounts, the transaction would come from the cluster
+    if config.replay {
+        return Err(anyhow!(
+            "inspect-tx reads the transaction from the cluster, it cannot replay a snapshot"
+        ));
+    }
+    let signature = Signature::from_str(signature)
+        .map_err(|err| anyhow!("Invalid signature {signature}: {err}"))?;
+    let transaction = config.rpc_client.get_transaction_with_config(
+        &signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(config.rpc_client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+
+    let inspection = inspect_transaction(&config.program_id, &signature, &transaction, symbol)?;
+
+    match config.output_format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&inspection)?),
+        OutputFormat::JsonCompact => println!("{}", serde_json::to_string(&inspection)?),
+        OutputFormat::Display => print_inspection(&inspection),
+    }
+    Ok(())
+}
+
+/// Decodes every instruction of `transaction` addressed to `program_id`,
+/// including inner instructions, and the balance changes of the transaction
+pub fn inspect_transaction(
+    program_id: &Pubkey,
+    signature: &Signature,
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    symbol: &str,
+) -> anyhow::Result<TransactionInspection> {
+    let versioned = transaction
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(anyhow!("Unable to decode transaction {signature}"))?;
+    let meta = transaction
+        .transaction
+        .meta
+        .as_ref()
+        .ok_or(anyhow!("Transaction {signature} has no status metadata"))?;
+
+    // Static keys first, then the keys loaded from lookup tables
+    let mut account_keys = versioned.message.static_account_keys().to_vec();
+    if let Some(loaded) = meta.loaded_addresses.as_ref().map(|loaded| {
+        loaded
+            .writable
+            .iter()
+            .chain(loaded.readonly.iter())
+            .map(|key| Pubkey::from_str(key))
+            .collect::<Result<Vec<_>, _>>()
+    }) {
+        account_keys.extend(loaded?);
+    }
+
+    let lamport_deltas: Vec<LamportDelta> = meta
+        .pre_balances
+        .iter()
+        .zip(meta.post_balances.iter())
+        .zip(account_keys.iter())
+        .filter(|((pre, post), _)| pre != post)
+        .map(|((pre, post), key)| LamportDelta {
+            account: key.to_string(),
+            pre_balance: *pre,
+            post_balance: *post,
+            delta: *post as i128 - *pre as i128,
+        })
+        .collect();
+    let token_deltas = token_deltas(
+        &account_keys,
+        meta.pre_token_balances.as_ref().unwrap_or(&vec![]),
+        meta.post_token_balances.as_ref().unwrap_or(&vec![]),
+    );
+
+    // Outer instructions, then the inner instructions they invoked
+    let mut compiled: Vec<(String, CompiledInstruction)> = versioned
+        .message
+        .instructions()
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| (index.to_string(), instruction.clone()))
+        .collect();
+    if let Some(inner_instructions) = meta.inner_instructions.as_ref().map(|inner| inner.to_vec()) {
+        for inner in inner_instructions {
+            for (position, instruction) in inner.instructions.iter().enumerate() {
+                if let UiInstruction::Compiled(instruction) = instruction {
+                    compiled.push((
+                        format!("{}.{}", inner.index, position),
+                        CompiledInstruction {
+                            program_id_index: instruction.program_id_index,
+                            accounts: instruction.accounts.clone(),
+                            data: bs58::decode(&instruction.data).into_vec()?,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut instructions = vec![];
+    for (index, instruction) in compiled {
+        let decoded = match decode_compiled_instruction(program_id, &instruction, &account_keys) {
+            Ok(decoded) => decoded,
+            Err(DecodeError::WrongProgram(_)) => continue,
+            Err(err) => {
+                instructions.push(unknown_instruction(
+                    index,
+                    &instruction,
+                    &account_keys,
+                    &err,
+                ));
+                continue;
+            }
+        };
+        let mut accounts: Vec<LabeledAccount> = decoded
+            .labeled_accounts()
+            .into_iter()
+            .map(|(label, address)| LabeledAccount {
+                label,
+                address: address.to_string(),
+            })
+            .collect();
+        // Accounts passed beyond the ones read by the program
+        for (extra, account) in instruction.accounts[accounts.len()..].iter().enumerate() {
+            accounts.push(LabeledAccount {
+                label: format!("extra_account_{extra}"),
+                address: account_keys[*account as usize].to_string(),
+            });
+        }
+        instructions.push(InspectedInstruction {
+            index,
+            name: decoded.name().to_string(),
+            accounts,
+            summary: summarize(
+                &decoded,
+                &lamport_deltas,
+                &token_deltas,
+                (&account_keys[0], meta.fee),
+                symbol,
+            ),
+        });
+    }
+
+    Ok(TransactionInspection {
+        signature: signature.to_string(),
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+        error: meta.err.as_ref().map(ToString::to_string),
+        fee: meta.fee,
+        instructions,
+        lamport_deltas,
+        token_deltas,
+    })
+}
+
+/// Stake pool instruction the decoder does not know, such as a variant only
+/// 2.x has, listed with its raw accounts so the rest can still be inspected
+fn unknown_instruction(
+    index: String,
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+    err: &DecodeError,
+) -> InspectedInstruction {
+    let name = match instruction.data.first() {
+        Some(tag) => format!("Unknown (tag {tag})"),
+        None => "Unknown (no data)".to_string(),
+    };
+    let accounts = instruction
+        .accounts
+        .iter()
+        .enumerate()
+        .map(|(position, account)| LabeledAccount {
+            label: format!("account_{position}"),
+            address: account_keys
+                .get(*account as usize)
+                .map_or_else(|| format!("index {account}"), ToString::to_string),
+        })
+        .collect();
+    InspectedInstruction {
+        index,
+        summary: format!("{name}: {err}"),
+        name,
+        accounts,
+    }
+}
+
+fn token_deltas(
+    account_keys: &[Pubkey],
+    pre_balances: &[UiTransactionTokenBalance],
+    post_balances: &[UiTransactionTokenBalance],
+) -> Vec<TokenDelta> {
+    let amount = |balance: &UiTransactionTokenBalance| {
+        balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0)
+    };
+    let pre: HashMap<u8, &UiTransactionTokenBalance> = pre_balances
+        .iter()
+        .map(|balance| (balance.account_index, balance))
+        .collect();
+    let post: HashMap<u8, &UiTransactionTokenBalance> = post_balances
+        .iter()
+        .map(|balance| (balance.account_index, balance))
+        .collect();
+
+    let mut indexes: Vec<u8> = pre.keys().chain(post.keys()).copied().collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+        .into_iter()
+        .filter_map(|index| {
+            let pre = pre.get(&index);
+            let post = post.get(&index);
+            let balance = post.or(pre)?;
+            let pre_amount = pre.map(|balance| amount(balance)).unwrap_or(0);
+            let post_amount = post.map(|balance| amount(balance)).unwrap_or(0);
+            (pre_amount != post_amount).then(|| TokenDelta {
+                account: account_keys
+                    .get(index as usize)
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                mint: balance.mint.clone(),
+                owner: balance.owner.as_ref().map(ToString::to_string),
+                decimals: balance.ui_token_amount.decimals,
+                pre_amount,
+                post_amount,
+                delta: post_amount as i128 - pre_amount as i128,
+            })
+        })
+        .collect()
+}
+
+/// One line description of `instruction`, with the amounts moved.
+/// `fee_payer` is the account that paid the transaction fee, and the fee.
+fn summarize(
+    instruction: &DecodedInstruction,
+    lamport_deltas: &[LamportDelta],
+    token_deltas: &[TokenDelta],
+    fee_payer: (&Pubkey, u64),
+    symbol: &str,
+) -> String {
+    let lamports = |account: &Pubkey| {
+        lamport_deltas
+            .iter()
+            .find(|delta| delta.account == account.to_string())
+    };
+    let token_delta = |account: &Pubkey| {
+        token_deltas
+            .iter()
+            .find(|delta| delta.account == account.to_string())
+    };
+    // Pool tokens moved in or out of `account`
+    let tokens = |account: &Pubkey| {
+        let delta = token_delta(account);
+        format!(
+            "{} {symbol}",
+            signed_amount(
+                delta.map_or(0, |delta| delta.delta),
+                delta.map_or(9, |delta| delta.decimals)
+            )
+        )
+    };
+    // `amount` of pool tokens, in the decimals of `account`
+    let token_amount = |amount: u64, account: &Pubkey| {
+        let decimals = token_delta(account).map_or(9, |delta| delta.decimals);
+        format!("{} {symbol}", signed_amount(amount as i128, decimals))
+    };
+    let sol = |lamports: u64| format!("{} SOL", signed_amount(lamports as i128, 9));
+
+    let name = instruction.name();
+    match instruction {
+        DecodedInstruction::DepositSol {
+            lamports,
+            pool_tokens_to,
+            referrer_pool_tokens_account,
+            ..
+        } => format!(
+            "{name} {} → {}, referral {}",
+            sol(*lamports),
+            tokens(pool_tokens_to),
+            tokens(referrer_pool_tokens_account)
+        ),
+        DecodedInstruction::DepositStake {
+            deposit_stake,
+            pool_tokens_to,
+            referrer_pool_tokens_account,
+            ..
+        } => format!(
+            "{name} {} stake → {}, referral {}",
+            sol(lamports(deposit_stake).map_or(0, |delta| delta.pre_balance)),
+            tokens(pool_tokens_to),
+            tokens(referrer_pool_tokens_account)
+        ),
+        DecodedInstruction::WithdrawSol {
+            pool_tokens,
+            pool_tokens_from,
+            lamports_to,
+            manager_fee_account,
+            ..
+        } => {
+            // A receiver paying the transaction fee got the fee on top of its delta
+            let fee = if lamports_to == fee_payer.0 {
+                fee_payer.1 as i128
+            } else {
+                0
+            };
+            format!(
+                "{name} {} → {} SOL, fee {}",
+                token_amount(*pool_tokens, pool_tokens_from),
+                signed_amount(
+                    lamports(lamports_to).map_or(0, |delta| delta.delta) + fee,
+                    9
+                ),
+                tokens(manager_fee_account)
+            )
+        }
+        DecodedInstruction::WithdrawStake {
+            pool_tokens,
+            pool_tokens_from,
+            stake_to_receive,
+            manager_fee_account,
+            ..
+        } => format!(
+            "{name} {} → {} stake in {stake_to_receive}, fee {}",
+            token_amount(*pool_tokens, pool_tokens_from),
+            sol(lamports(stake_to_receive).map_or(0, |delta| delta.post_balance)),
+            tokens(manager_fee_account)
+        ),
+        DecodedInstruction::IncreaseValidatorStake {
+            lamports,
+            vote_account,
+            ..
+        } => format!("{name} {} to vote account {vote_account}", sol(*lamports)),
+        DecodedInstruction::DecreaseValidatorStake {
+            lamports,
+            validator_stake,
+            ..
+        } => format!(
+            "{name} {} from validator stake {validator_stake}",
+            sol(*lamports)
+        ),
+        DecodedInstruction::AddValidatorToPool { vote_account, .. } => {
+            format!("{name} vote account {vote_account}")
+        }
+        DecodedInstruction::RemoveValidatorFromPool {
+            validator_stake, ..
+        } => format!("{name} validator stake {validator_stake}"),
+        DecodedInstruction::UpdateValidatorListBalance {
+            stake_accounts,
+            start_index,
+            no_merge,
+            ..
+        } => format!(
+            "{name} of {} validators from index {start_index}{}",
+            stake_accounts.len(),
+            if *no_merge { ", without merging" } else { "" }
+        ),
+        DecodedInstruction::SetPreferredValidator {
+            validator_type,
+            validator_vote_address,
+            ..
+        } => match validator_vote_address {
+            Some(vote_address) => format!("{name} {validator_type:?} to {vote_address}"),
+            None => format!("{name} {validator_type:?} unset"),
+        },
+        DecodedInstruction::SetFee { fee, .. } => format!("{name} {fee:?}"),
+        DecodedInstruction::SetFundingAuthority {
+            funding_type,
+            new_funding_authority,
+            ..
+        } => match new_funding_authority {
+            Some(authority) => format!("{name} {funding_type:?} to {authority}"),
+            None => format!("{name} {funding_type:?} unset"),
+        },
+        DecodedInstruction::SetManager { new_manager, .. } => {
+            format!("{name} to {new_manager}")
+        }
+        DecodedInstruction::SetStaker { new_staker, .. } => format!("{name} to {new_staker}"),
+        _ => name.to_string(),
+    }
+}
+
+/// `amount` in ui units, with a sign when negative
+fn signed_amount(amount: i128, decimals: u8) -> String {
+    let ui_amount = spl_token::amount_to_ui_amount_string_trimmed(
+        amount.unsigned_abs().min(u64::MAX as u128) as u64,
+        decimals,
+    );
+    if amount < 0 {
+        format!("-{ui_amount}")
+    } else {
+        ui_amount
+    }
+}
+
+fn print_inspection(inspection: &TransactionInspection) {
+    println!("Signature: {}", inspection.signature);
+    println!("Slot: {}", inspection.slot);
+    match &inspection.error {
+        Some(error) => println!("Status: failed, {error}"),
+        None => println!("Status: success"),
+    }
+    println!("Fee: {} SOL", signed_amount(inspection.fee as i128, 9));
+
+    if inspection.instructions.is_empty() {
+        println!("No stake pool instruction");
+    }
+    for instruction in &inspection.instructions {
+        println!();
+        println!("#{} {}", instruction.index, instruction.summary);
+        for account in &instruction.accounts {
+            println!("  {:<30} {}", account.label, account.address);
+        }
+    }
+
+    if !inspection.lamport_deltas.is_empty() {
+        println!();
+        println!("Lamport changes:");
+        for delta in &inspection.lamport_deltas {
+            println!(
+                "  {:<44} {:>20} SOL",
+                delta.account,
+                signed_amount(delta.delta, 9)
+            );
+        }
+    }
+    if !inspection.token_deltas.is_empty() {
+        println!();
+        println!("Token changes:");
+        for delta in &inspection.token_deltas {
+            println!(
+                "  {:<44} {:>20} (mint {})",
+                delta.account,
+                signed_amount(delta.delta, delta.decimals),
+                delta.mint
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine, prelude::BASE64_STANDARD};
+    use jito_stake_pool_sdk::sdk::{deposit_sol::deposit_sol, withdraw_sol::withdraw_sol};
+    use serde_json::json;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        transaction::Transaction,
+    };
+
+    use super::*;
+    use crate::{sink::MemoryTransactions, test_utils::test_config};
+
+    const SOL: u64 = 1_000_000_000;
+    const FEE: u64 = 10_000;
+
+    /// Deposit and withdraw of one user, after an instruction of another
+    /// program, with the withdraw invoked by that program, as returned by
+    /// `getTransaction`
+    struct Fixture {
+        program_id: Pubkey,
+        fee_payer: Pubkey,
+        depositor: Pubkey,
+        pool_tokens: Pubkey,
+        transaction: EncodedConfirmedTransactionWithStatusMeta,
+    }
+
+    fn fixture() -> Fixture {
+        let program_id = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let [
+            stake_pool,
+            withdraw_authority,
+            reserve,
+            pool_mint,
+            manager_fee,
+            referrer,
+            fee_payer,
+            depositor,
+            pool_tokens,
+            withdrawn_tokens,
+        ] = std::array::from_fn(|_| Pubkey::new_unique());
+        let deposit = deposit_sol(
+            &program_id,
+            &stake_pool,
+            &withdraw_authority,
+            &reserve,
+            &depositor,
+            &pool_tokens,
+            &manager_fee,
+            &referrer,
+            &pool_mint,
+            &spl_token::id(),
+            10 * SOL,
+        );
+        let withdraw = withdraw_sol(
+            &program_id,
+            &stake_pool,
+            &withdraw_authority,
+            &fee_payer,
+            &withdrawn_tokens,
+            &reserve,
+            &fee_payer,
+            &manager_fee,
+            &pool_mint,
+            &spl_token::id(),
+            SOL,
+        );
+        let mut router_accounts = withdraw.accounts.clone();
+        router_accounts.push(AccountMeta::new_readonly(program_id, false));
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(router, &[0], vec![]),
+                deposit,
+                Instruction::new_with_bytes(router, &[], router_accounts),
+            ],
+            Some(&fee_payer),
+        );
+        let keys = message.account_keys.clone();
+        let key_index = |key: &Pubkey| keys.iter().position(|k| k == key).unwrap() as u8;
+
+        // The fee payer receives 1.1 SOL and pays the fee
+        let pre_balances = vec![100 * SOL; keys.len()];
+        let post_balances: Vec<u64> = keys
+            .iter()
+            .map(|key| match key {
+                key if *key == fee_payer => 101_100_000_000 - FEE,
+                key if *key == depositor => 90 * SOL,
+                key if *key == reserve => 108_900_000_000,
+                _ => 100 * SOL,
+            })
+            .collect();
+        let token_balance = |key: &Pubkey, amount: u64| {
+            json!({
+                "accountIndex": key_index(key),
+                "mint": pool_mint.to_string(),
+                "owner": depositor.to_string(),
+                "uiTokenAmount": {
+                    "amount": amount.to_string(),
+                    "decimals": 9,
+                    "uiAmount": null,
+                    "uiAmountString": "",
+                },
+            })
+        };
+        let withdraw_data = bs58::encode(&withdraw.data).into_string();
+        let transaction = Transaction::new_unsigned(message);
+        let response = json!({
+            "slot": 7,
+            "blockTime": null,
+            "transaction": [
+                BASE64_STANDARD.encode(bincode::serialize(&transaction).unwrap()),
+                "base64",
+            ],
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": FEE,
+                "preBalances": pre_balances,
+                "postBalances": post_balances,
+                "innerInstructions": [{
+                    "index": 2,
+                    "instructions": [{
+                        "programIdIndex": key_index(&program_id),
+                        "accounts": withdraw
+                            .accounts
+                            .iter()
+                            .map(|meta| key_index(&meta.pubkey))
+                            .collect::<Vec<_>>(),
+                        "data": withdraw_data,
+                        "stackHeight": 2,
+                    }],
+                }],
+                "logMessages": [],
+                "preTokenBalances": [
+                    token_balance(&pool_tokens, 0),
+                    token_balance(&withdrawn_tokens, 2 * SOL),
+                    token_balance(&referrer, 5 * SOL),
+                    token_balance(&manager_fee, 0),
+                ],
+                "postTokenBalances": [
+                    token_balance(&pool_tokens, 9_120_000_000),
+                    token_balance(&withdrawn_tokens, SOL),
+                    token_balance(&referrer, 5 * SOL),
+                    token_balance(&manager_fee, 1_000_000),
+                ],
+                "rewards": [],
+            },
+        });
+        Fixture {
+            program_id,
+            fee_payer,
+            depositor,
+            pool_tokens,
+            transaction: serde_json::from_value(response).unwrap(),
+        }
+    }
+
+    #[test]
+    fn summarizes_outer_and_inner_instructions() {
+        let fixture = fixture();
+
+        let inspection = inspect_transaction(
+            &fixture.program_id,
+            &Signature::default(),
+            &fixture.transaction,
+            "JitoSOL",
+        )
+        .unwrap();
+
+        assert_eq!(inspection.fee, FEE);
+        let summaries: Vec<_> = inspection
+            .instructions
+            .iter()
+            .map(|instruction| (instruction.index.as_str(), instruction.summary.as_str()))
+            .collect();
+        assert_eq!(
+            summaries,
+            [
+                ("1", "DepositSol 10 SOL → 9.12 JitoSOL, referral 0 JitoSOL"),
+                ("2.0", "WithdrawSol 1 JitoSOL → 1.1 SOL, fee 0.001 JitoSOL"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_undecodable_instruction_and_keeps_going() {
+        let fixture = fixture();
+        // A 2.x `DepositSolWithSlippage`, which 0.6.4 does not have, invoked
+        // by the router after the withdraw
+        let mut response = serde_json::to_value(&fixture.transaction).unwrap();
+        let inner = &mut response["meta"]["innerInstructions"][0]["instructions"];
+        let mut slippage = inner[0].clone();
+        let mut data = vec![25];
+        data.extend_from_slice(&SOL.to_le_bytes());
+        data.extend_from_slice(&SOL.to_le_bytes());
+        slippage["data"] = json!(bs58::encode(data).into_string());
+        slippage["accounts"] = json!([0, 1]);
+        inner.as_array_mut().unwrap().push(slippage);
+        let transaction: EncodedConfirmedTransactionWithStatusMeta =
+            serde_json::from_value(response).unwrap();
+
+        let inspection = inspect_transaction(
+            &fixture.program_id,
+            &Signature::default(),
+            &transaction,
+            "JitoSOL",
+        )
+        .unwrap();
+
+        let names: Vec<_> = inspection
+            .instructions
+            .iter()
+            .map(|instruction| (instruction.index.as_str(), instruction.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("1", "DepositSol"),
+                ("2.0", "WithdrawSol"),
+                ("2.1", "Unknown (tag 25)")
+            ]
+        );
+        let unknown = &inspection.instructions[2];
+        assert!(unknown.summary.starts_with("Unknown (tag 25): "));
+        assert_eq!(unknown.accounts[0].label, "account_0");
+        assert_eq!(unknown.accounts[0].address, fixture.fee_payer.to_string());
+    }
+
+    #[test]
+    fn replay_is_rejected() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        config.replay = true;
+
+        let err =
+            command_inspect_tx(&config, &Signature::default().to_string(), "JitoSOL").unwrap_err();
+
+        assert!(
+            err.to_string().contains("cannot replay a snapshot"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn labels_accounts_in_program_order() {
+        let fixture = fixture();
+
+        let inspection = inspect_transaction(
+            &fixture.program_id,
+            &Signature::default(),
+            &fixture.transaction,
+            "JitoSOL",
+        )
+        .unwrap();
+
+        let deposit = &inspection.instructions[0];
+        assert_eq!(deposit.name, "DepositSol");
+        let label = |instruction: &InspectedInstruction, address: &Pubkey| {
+            instruction
+                .accounts
+                .iter()
+                .find(|account| account.address == address.to_string())
+                .map(|account| account.label.clone())
+        };
+        assert_eq!(
+            label(deposit, &fixture.depositor).as_deref(),
+            Some("lamports_from")
+        );
+        assert_eq!(
+            label(deposit, &fixture.pool_tokens).as_deref(),
+            Some("pool_tokens_to")
+        );
+        let withdraw = &inspection.instructions[1];
+        assert_eq!(
+            label(withdraw, &fixture.fee_payer).as_deref(),
+            Some("user_transfer_authority")
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use clap::Args;
+use jito_stake_pool_sdk::output::CliStakePool;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    client::{get_epoch, get_pool_snapshot},
+    config::{JitoStakePoolCliConfig, OutputFormat},
+};
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+}
+
+pub fn command_list(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+) -> anyhow::Result<()> {
+    let snapshot = get_pool_snapshot(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        config.layout,
+    )?;
+    let epoch = get_epoch(&config.accounts)?;
+    let cli_stake_pool = CliStakePool::from_snapshot(&config.program_id, &snapshot, epoch);
+
+    match config.output_format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&cli_stake_pool)?),
+        OutputFormat::JsonCompact => println!("{}", serde_json::to_string(&cli_stake_pool)?),
+        OutputFormat::Display if config.verbose => {
+            let mut output = String::new();
+            cli_stake_pool.write_verbose(&mut output)?;
+            print!("{output}");
+        }
+        OutputFormat::Display => print!("{cli_stake_pool}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use std::{
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use clap::Args;
+use solana_sdk::pubkey::Pubkey;
+use spl_stake_pool::state::{StakePool, ValidatorList};
+
+use crate::{
+    client::{get_balance, get_epoch, get_stake_pool, get_validator_list},
+    config::JitoStakePoolCliConfig,
+};
+
+/// Longest a scraper may take to send its request or read the response, so
+/// that a stalled client cannot block the server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Args)]
+pub struct MetricsArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// Address the HTTP server listens on
+    #[arg(long, value_name = "HOST:PORT", default_value = "127.0.0.1:9090")]
+    pub bind: String,
+}
+
+/// Serves the pool metrics on `/metrics` in the Prometheus text format. The
+/// accounts are fetched again on every scrape.
+pub fn command_metrics(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    bind: &str,
+) -> anyhow::Result<()> {
+    let listener =
+        TcpListener::bind(bind).map_err(|err| anyhow!("Unable to listen on {bind}: {err}"))?;
+    println!("Serving metrics for {stake_pool_address} on http://{bind}/metrics");
+
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(Into::into)
+            .and_then(|stream| handle_request(config, stake_pool_address, stream));
+        if let Err(err) = result {
+            eprintln!("Metrics request failed: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    mut stream: TcpStream,
+) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers, the request has no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = if path == "/metrics" {
+        let body = match fetch_metrics(config, stake_pool_address) {
+            Ok(metrics) => metrics,
+            Err(err) => {
+                eprintln!("Unable to fetch stake pool {stake_pool_address}: {err}");
+                up_metric(stake_pool_address, false)
+            }
+        };
+        ("200 OK", body)
+    } else {
+        ("404 Not Found", "Not found\n".to_string())
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+fn fetch_metrics(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+) -> anyhow::Result<String> {
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
+    let reserve_lamports = get_balance(&config.accounts, &stake_pool.reserve_stake)?;
+    let epoch = get_epoch(&config.accounts)?;
+    Ok(render_metrics(
+        stake_pool_address,
+        &stake_pool,
+        &validator_list,
+        reserve_lamports,
+        epoch,
+    ))
+}
+
+fn up_metric(stake_pool_address: &Pubkey, up: bool) -> String {
+    let mut output = String::new();
+    gauge(
+        &mut output,
+        "stake_pool_up",
+        "Whether the last fetch of the stake pool accounts succeeded",
+        &[(
+            format!("pool=\"{stake_pool_address}\""),
+            u8::from(up).into(),
+        )],
+    );
+    output
+}
+
+/// Renders the pool state in the Prometheus text format
+pub fn render_metrics(
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+    reserve_lamports: u64,
+    epoch: u64,
+) -> String {
+    let pool = format!("pool=\"{stake_pool_address}\"");
+    let exchange_rate = if stake_pool.pool_token_supply == 0 {
+        1.0
+    } else {
+        stake_pool.total_lamports as f64 / stake_pool.pool_token_supply as f64
+    };
+
+    let mut output = up_metric(stake_pool_address, true);
+    gauge(
+        &mut output,
+        "stake_pool_total_lamports",
+        "Total lamports managed by the pool",
+        &[(pool.clone(), stake_pool.total_lamports as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_pool_token_supply",
+        "Pool token supply",
+        &[(pool.clone(), stake_pool.pool_token_supply as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_exchange_rate",
+        "Lamports per pool token",
+        &[(pool.clone(), exchange_rate)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_reserve_lamports",
+        "Lamports in the reserve stake account",
+        &[(pool.clone(), reserve_lamports as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_validators",
+        "Validators in the validator list",
+        &[(pool.clone(), validator_list.validators.len() as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_max_validators",
+        "Maximum number of validators in the validator list",
+        &[(pool.clone(), validator_list.header.max_validators as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_last_update_epoch",
+        "Epoch of the last pool balance update",
+        &[(pool.clone(), stake_pool.last_update_epoch as f64)],
+    );
+    gauge(
+        &mut output,
+        "stake_pool_epochs_since_update",
+        "Epochs since the last pool balance update",
+        &[(
+            pool.clone(),
+            epoch.saturating_sub(stake_pool.last_update_epoch) as f64,
+        )],
+    );
+
+    let validator_labels =
+        |vote_account: &Pubkey| format!("{pool},vote_account=\"{vote_account}\"");
+    gauge(
+        &mut output,
+        "stake_pool_validator_active_stake_lamports",
+        "Active stake lamports of each validator",
+        &validator_list
+            .validators
+            .iter()
+            .map(|validator| {
+                (
+                    validator_labels(&validator.vote_account_address),
+                    u64::from(validator.active_stake_lamports) as f64,
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    gauge(
+        &mut output,
+        "stake_pool_validator_transient_stake_lamports",
+        "Transient stake lamports of each validator",
+        &validator_list
+            .validators
+            .iter()
+            .map(|validator| {
+                (
+                    validator_labels(&validator.vote_account_address),
+                    u64::from(validator.transient_stake_lamports) as f64,
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    output
+}
+
+fn gauge(output: &mut String, name: &str, help: &str, samples: &[(String, f64)]) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} gauge");
+    for (labels, value) in samples {
+        let _ = writeln!(output, "{name}{{{labels}}} {value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_stake_pool::state::ValidatorStakeInfo;
+
+    use super::*;
+
+    #[test]
+    fn renders_pool_and_validator_gauges() {
+        let stake_pool_address = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let stake_pool = StakePool {
+            total_lamports: 3_000,
+            pool_token_supply: 2_000,
+            last_update_epoch: 98,
+            ..StakePool::default()
+        };
+        let validator_list = ValidatorList {
+            validators: vec![ValidatorStakeInfo {
+                vote_account_address: vote_account,
+                active_stake_lamports: 2_500.into(),
+                transient_stake_lamports: 400.into(),
+                ..ValidatorStakeInfo::default()
+            }],
+            ..ValidatorList::new(5)
+        };
+
+        let output = render_metrics(&stake_pool_address, &stake_pool, &validator_list, 100, 100);
+
+        let pool = format!("pool=\"{stake_pool_address}\"");
+        let validator = format!("{pool},vote_account=\"{vote_account}\"");
+        let samples: Vec<_> = output
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        assert_eq!(
+            samples,
+            [
+                format!("stake_pool_up{{{pool}}} 1"),
+                format!("stake_pool_total_lamports{{{pool}}} 3000"),
+                format!("stake_pool_pool_token_supply{{{pool}}} 2000"),
+                format!("stake_pool_exchange_rate{{{pool}}} 1.5"),
+                format!("stake_pool_reserve_lamports{{{pool}}} 100"),
+                format!("stake_pool_validators{{{pool}}} 1"),
+                format!("stake_pool_max_validators{{{pool}}} 5"),
+                format!("stake_pool_last_update_epoch{{{pool}}} 98"),
+                format!("stake_pool_epochs_since_update{{{pool}}} 2"),
+                format!("stake_pool_validator_active_stake_lamports{{{validator}}} 2500"),
+                format!("stake_pool_validator_transient_stake_lamports{{{validator}}} 400"),
+            ]
+        );
+        assert!(output.contains(
+            "# HELP stake_pool_exchange_rate Lamports per pool token\n\
+             # TYPE stake_pool_exchange_rate gauge\n"
+        ));
+    }
+}
@@ -1,8 +1,20 @@
 pub mod add_validator;
+pub mod audit;
+pub mod batch;
+pub mod crank;
+pub mod decrease_validator_stake;
 pub mod deposit_sol;
 // pub mod create_pool;
 pub mod increase_validator_stake;
+pub mod inspect_tx;
+pub mod list;
+pub mod metrics;
+pub mod quote;
+pub mod rebalance;
 pub mod remove_validator;
+pub mod stats;
+pub mod update;
+pub mod withdraw_sol;
 
 // #[derive(Args)]
 // struct SetPreferredValidatorArgs {
@@ -65,24 +77,6 @@ pub mod remove_validator;
 //
 //
 // #[derive(Args)]
-// struct ListArgs {
-//     /// Stake pool address.
-//     pool: String,
-// }
-//
-// #[derive(Args)]
-// struct UpdateArgs {
-//     /// Stake pool address.
-//     pool: String,
-//     /// Update all balances, even if it has already been performed this epoch.
-//     #[arg(long)]
-//     force: bool,
-//     /// Do not automatically merge transient stakes. Useful if the stake pool is in an expected state, but the balances still need to be updated.
-//     #[arg(long = "no-merge")]
-//     no_merge: bool,
-// }
-//
-// #[derive(Args)]
 // struct WithdrawStakeArgs {
 //     /// Stake pool address.
 //     pool: String,
@@ -111,19 +105,6 @@ pub mod remove_validator;
 // }
 //
 // #[derive(Args)]
-// struct WithdrawSolArgs {
-//     /// Stake pool address.
-//     pool: String,
-//     /// System account to receive SOL from the stake pool. Defaults to the payer.
-//     sol_receiver: String,
-//     /// Amount of pool tokens to withdraw for SOL.
-//     amount: f64,
-//     /// Pool token account to withdraw tokens from. Defaults to the token-owner's associated token account.
-//     #[arg(long = "pool-account", value_name = "ADDRESS")]
-//     pool_account: Option<String>,
-// }
-//
-// #[derive(Args)]
 // struct SetManagerArgs {
 //     /// Stake pool address.
 //     pool: String,
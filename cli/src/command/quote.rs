@@ -0,0 +1,192 @@
+use anyhow::anyhow;
+use clap::{Args, ValueEnum};
+use jito_stake_pool_sdk::{
+    layout::{DecodedStakePool, LayoutVersion},
+    math::{
+        sol_deposit_fee, sol_referral_fee, sol_withdrawal_fee, stake_deposit_fee,
+        stake_pool_lamports_for_withdrawal, stake_pool_tokens_for_deposit, stake_referral_fee,
+        stake_withdrawal_fee,
+    },
+};
+use solana_sdk::{
+    native_token::{LAMPORTS_PER_SOL, Sol, sol_str_to_lamports},
+    pubkey::Pubkey,
+    stake::state::StakeStateV2,
+};
+use spl_stake_pool::state::StakePool;
+
+use crate::{
+    client::{get_epoch, get_minimum_balance_for_rent_exemption, get_stake_pool, get_token_mint},
+    config::JitoStakePoolCliConfig,
+};
+
+#[derive(Args)]
+pub struct QuoteArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// Operation to quote
+    #[arg(value_enum)]
+    pub operation: QuoteOperation,
+
+    /// Amount in SOL for deposits, in pool tokens for withdrawals. Stake deposits include the stake account rent.
+    pub amount: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum QuoteOperation {
+    DepositSol,
+    DepositStake,
+    WithdrawSol,
+    WithdrawStake,
+}
+
+/// Outcome of a deposit, mirroring the program's fee computation
+#[derive(Debug, PartialEq, Eq)]
+pub struct DepositQuote {
+    pub lamports: u64,
+    pub gross_pool_tokens: u64,
+    pub manager_fee: u64,
+    pub referral_fee: u64,
+    pub net_pool_tokens: u64,
+}
+
+/// Outcome of a withdrawal, mirroring the program's fee computation
+#[derive(Debug, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub pool_tokens: u64,
+    pub manager_fee: u64,
+    pub burnt_pool_tokens: u64,
+    pub lamports: u64,
+}
+
+/// Quotes a deposit of `stake_lamports` of active stake and `sol_lamports`
+/// of SOL, as done by `DepositStake` (with the stake account rent as SOL) and
+/// `DepositSol` (without stake), rounded as the `layout` program version.
+pub fn quote_deposit(
+    stake_pool: &StakePool,
+    layout: LayoutVersion,
+    stake_lamports: u64,
+    sol_lamports: u64,
+) -> Option<DepositQuote> {
+    let lamports = stake_lamports.checked_add(sol_lamports)?;
+    let gross_pool_tokens = stake_pool_tokens_for_deposit(stake_pool, lamports)?;
+    let pool_tokens_from_stake = stake_pool_tokens_for_deposit(stake_pool, stake_lamports)?;
+    let pool_tokens_from_sol = gross_pool_tokens.checked_sub(pool_tokens_from_stake)?;
+    let total_fee = stake_deposit_fee(stake_pool, pool_tokens_from_stake, layout)?
+        .checked_add(sol_deposit_fee(stake_pool, pool_tokens_from_sol, layout)?)?;
+    let referral_fee = if stake_lamports == 0 {
+        sol_referral_fee(stake_pool, total_fee)?
+    } else {
+        stake_referral_fee(stake_pool, total_fee)?
+    };
+    Some(DepositQuote {
+        lamports,
+        gross_pool_tokens,
+        manager_fee: total_fee.checked_sub(referral_fee)?,
+        referral_fee,
+        net_pool_tokens: gross_pool_tokens.checked_sub(total_fee)?,
+    })
+}
+
+/// Quotes a withdrawal of `pool_tokens`, as done by `WithdrawSol` or
+/// `WithdrawStake`, rounded as the `layout` program version
+pub fn quote_withdraw(
+    stake_pool: &StakePool,
+    layout: LayoutVersion,
+    pool_tokens: u64,
+    operation: QuoteOperation,
+) -> Option<WithdrawQuote> {
+    let manager_fee = match operation {
+        QuoteOperation::WithdrawSol => sol_withdrawal_fee(stake_pool, pool_tokens, layout)?,
+        _ => stake_withdrawal_fee(stake_pool, pool_tokens, layout)?,
+    };
+    let burnt_pool_tokens = pool_tokens.checked_sub(manager_fee)?;
+    Some(WithdrawQuote {
+        pool_tokens,
+        manager_fee,
+        burnt_pool_tokens,
+        lamports: stake_pool_lamports_for_withdrawal(stake_pool, burnt_pool_tokens, layout)?,
+    })
+}
+
+pub fn command_quote(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    operation: QuoteOperation,
+    amount: f64,
+) -> anyhow::Result<()> {
+    let DecodedStakePool { stake_pool, layout } =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?;
+    if layout == LayoutVersion::Compatible {
+        return Err(anyhow!(
+            "The pool may be 0.6.4 or 2.x, which round fees and withdrawals differently, set the layout of the pool's program version"
+        ));
+    }
+    let epoch = get_epoch(&config.accounts)?;
+    if stake_pool.last_update_epoch < epoch {
+        println!(
+            "Warning: the pool was last updated in epoch {}, the quote changes once it is updated for epoch {epoch}",
+            stake_pool.last_update_epoch
+        );
+    }
+
+    let decimals = get_token_mint(&config.accounts, &stake_pool.pool_mint)?.decimals;
+    let tokens = |amount: u64| spl_token::amount_to_ui_amount_string_trimmed(amount, decimals);
+    let rate = |lamports: u64, pool_tokens: u64| {
+        lamports as f64
+            / LAMPORTS_PER_SOL as f64
+            / spl_token::amount_to_ui_amount(pool_tokens, decimals)
+    };
+
+    match operation {
+        QuoteOperation::DepositSol | QuoteOperation::DepositStake => {
+            let lamports =
+                sol_str_to_lamports(&amount.to_string()).ok_or(anyhow!("Invalid amount"))?;
+            let (stake_lamports, sol_lamports) = if operation == QuoteOperation::DepositStake {
+                // The rent of the deposited stake account is counted as a SOL deposit
+                let stake_rent = get_minimum_balance_for_rent_exemption(
+                    &config.accounts,
+                    StakeStateV2::size_of(),
+                )?;
+                let stake_lamports = lamports.checked_sub(stake_rent).ok_or(anyhow!(
+                    "A stake account holds at least its rent of {}",
+                    Sol(stake_rent)
+                ))?;
+                (stake_lamports, stake_rent)
+            } else {
+                (0, lamports)
+            };
+            let quote = quote_deposit(&stake_pool, layout, stake_lamports, sol_lamports)
+                .ok_or(anyhow!("Deposit amount is too large"))?;
+            println!("Deposit: {}", Sol(quote.lamports));
+            println!("Gross pool tokens: {}", tokens(quote.gross_pool_tokens));
+            println!("Manager fee: {}", tokens(quote.manager_fee));
+            println!("Referral fee: {}", tokens(quote.referral_fee));
+            println!("Net pool tokens: {}", tokens(quote.net_pool_tokens));
+            if quote.net_pool_tokens > 0 {
+                println!(
+                    "Effective rate: {} SOL per pool token",
+                    rate(quote.lamports, quote.net_pool_tokens)
+                );
+            }
+        }
+        QuoteOperation::WithdrawSol | QuoteOperation::WithdrawStake => {
+            let pool_tokens = spl_token::ui_amount_to_amount(amount, decimals);
+            let quote = quote_withdraw(&stake_pool, layout, pool_tokens, operation)
+                .ok_or(anyhow!("Withdrawal amount is too large"))?;
+            println!("Pool tokens: {}", tokens(quote.pool_tokens));
+            println!("Manager fee: {}", tokens(quote.manager_fee));
+            println!("Referral fee: {}", tokens(0));
+            println!("Net pool tokens burnt: {}", tokens(quote.burnt_pool_tokens));
+            println!("Withdrawal: {}", Sol(quote.lamports));
+            if quote.pool_tokens > 0 {
+                println!(
+                    "Effective rate: {} SOL per pool token",
+                    rate(quote.lamports, quote.pool_tokens)
+                );
+            }
+        }
+    }
+    Ok(())
+}
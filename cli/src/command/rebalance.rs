@@ -0,0 +1,438 @@
+use std::{collections::HashMap, fs::File, str::FromStr};
+
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::{
+    address::{
+        MAX_TRANSIENT_STAKE_ACCOUNTS, MINIMUM_ACTIVE_STAKE, minimum_reserve_lamports,
+        minimum_stake_lamports,
+    },
+    sdk::{
+        decrease_validator_stake::decrease_validator_stake_with_vote,
+        increase_validator_stake::increase_validator_stake_with_vote,
+    },
+};
+use serde::Deserialize;
+use solana_sdk::{
+    native_token::{Sol, sol_str_to_lamports},
+    pubkey::Pubkey,
+    stake::state::{Meta, StakeStateV2},
+};
+use spl_stake_pool::state::{StakeStatus, ValidatorList, ValidatorStakeInfo};
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{
+        get_balance, get_minimum_balance_for_rent_exemption, get_stake_pool, get_validator_list,
+    },
+    command::batch::pack_instructions,
+    config::JitoStakePoolCliConfig,
+    send_transaction,
+};
+
+#[derive(Args)]
+pub struct RebalanceArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// CSV file with `vote_account,share` rows. Shares are normalized, validators missing from the file target zero.
+    #[arg(long, value_name = "PATH")]
+    pub targets: String,
+
+    /// Amount in SOL to keep undelegated in the reserve
+    #[arg(long = "reserve-buffer", value_name = "SOL", default_value_t = 0.0)]
+    pub reserve_buffer: f64,
+}
+
+#[derive(Deserialize)]
+struct TargetRow {
+    vote_account: String,
+    share: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebalanceAction {
+    Increase(u64),
+    Decrease(u64),
+}
+
+/// One validator's line in the plan
+#[derive(Debug)]
+pub struct RebalanceMove {
+    pub vote_account: Pubkey,
+    pub current_lamports: u64,
+    pub target_lamports: u64,
+    pub action: Option<RebalanceAction>,
+    pub note: Option<&'static str>,
+}
+
+pub fn read_targets(path: &str) -> anyhow::Result<HashMap<Pubkey, f64>> {
+    let file = File::open(path).map_err(|err| anyhow!("Unable to open {path}: {err}"))?;
+    let mut targets = HashMap::new();
+    for row in csv::Reader::from_reader(file).deserialize::<TargetRow>() {
+        let row = row?;
+        let vote_account = Pubkey::from_str(&row.vote_account)
+            .map_err(|err| anyhow!("Invalid vote account {}: {err}", row.vote_account))?;
+        if !row.share.is_finite() || row.share < 0.0 {
+            return Err(anyhow!("Invalid share {} for {vote_account}", row.share));
+        }
+        *targets.entry(vote_account).or_default() += row.share;
+    }
+    Ok(targets)
+}
+
+/// Computes the moves needed to bring every validator to its target share.
+///
+/// Validators with a transient stake in flight are skipped, increases are
+/// funded from `reserve_available_lamports` only, and at most
+/// `MAX_TRANSIENT_STAKE_ACCOUNTS` moves are planned, largest deviation first.
+/// Targets of validators that are not active are listed without a move.
+pub fn plan_rebalance(
+    validator_list: &ValidatorList,
+    targets: &HashMap<Pubkey, f64>,
+    reserve_available_lamports: u64,
+    stake_rent: u64,
+) -> anyhow::Result<Vec<RebalanceMove>> {
+    if let Some(vote_account) = targets
+        .keys()
+        .find(|vote_account| !validator_list.contains(vote_account))
+    {
+        return Err(anyhow!(
+            "Target {vote_account} is not in the validator list"
+        ));
+    }
+    let total_shares: f64 = targets.values().sum();
+    if total_shares <= 0.0 {
+        return Err(anyhow!("Target shares must add up to more than zero"));
+    }
+
+    let is_active =
+        |validator: &&ValidatorStakeInfo| validator.status == StakeStatus::Active.into();
+    let validators: Vec<_> = validator_list
+        .validators
+        .iter()
+        .filter(|validator| {
+            is_active(validator) || targets.contains_key(&validator.vote_account_address)
+        })
+        .collect();
+    let total_lamports = validators
+        .iter()
+        .filter(|validator| is_active(validator))
+        .map(|validator| {
+            u64::from(validator.active_stake_lamports)
+                + u64::from(validator.transient_stake_lamports)
+        })
+        .sum::<u64>()
+        .saturating_add(reserve_available_lamports);
+
+    let minimum_stake_lamports = minimum_stake_lamports(&Meta {
+        rent_exempt_reserve: stake_rent,
+        ..Meta::default()
+    });
+    let mut moves: Vec<RebalanceMove> = validators
+        .iter()
+        .map(|validator| {
+            let active_lamports = u64::from(validator.active_stake_lamports);
+            let transient_lamports = u64::from(validator.transient_stake_lamports);
+            let current_lamports = active_lamports + transient_lamports;
+            let share = targets
+                .get(&validator.vote_account_address)
+                .copied()
+                .unwrap_or_default();
+            let target_lamports = (total_lamports as f64 * share / total_shares) as u64;
+
+            let (action, note) = if !is_active(validator) {
+                (None, Some("validator is not active"))
+            } else if transient_lamports > 0 {
+                (None, Some("transient stake in flight"))
+            } else if target_lamports > current_lamports {
+                (
+                    Some(RebalanceAction::Increase(
+                        target_lamports - current_lamports,
+                    )),
+                    None,
+                )
+            } else {
+                let lamports = (current_lamports - target_lamports)
+                    .min(active_lamports.saturating_sub(minimum_stake_lamports));
+                if lamports > stake_rent {
+                    (Some(RebalanceAction::Decrease(lamports)), None)
+                } else {
+                    (None, None)
+                }
+            };
+            RebalanceMove {
+                vote_account: validator.vote_account_address,
+                current_lamports,
+                target_lamports,
+                action,
+                note,
+            }
+        })
+        .collect();
+
+    moves.sort_by_key(|m| std::cmp::Reverse(m.current_lamports.abs_diff(m.target_lamports)));
+
+    let mut budget = reserve_available_lamports;
+    let mut planned = 0;
+    for m in moves.iter_mut() {
+        let Some(action) = m.action else {
+            continue;
+        };
+        if planned == MAX_TRANSIENT_STAKE_ACCOUNTS {
+            m.action = None;
+            m.note = Some("transient account limit reached");
+            continue;
+        }
+        if let RebalanceAction::Increase(lamports) = action {
+            // Every increase also moves the transient account rent out of the reserve
+            let lamports = lamports.min(budget.saturating_sub(stake_rent));
+            if lamports < MINIMUM_ACTIVE_STAKE {
+                m.action = None;
+                m.note = Some("not enough in the reserve");
+                continue;
+            }
+            budget -= lamports + stake_rent;
+            m.action = Some(RebalanceAction::Increase(lamports));
+        }
+        planned += 1;
+    }
+
+    Ok(moves)
+}
+
+pub fn command_rebalance(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    targets: &str,
+    reserve_buffer: f64,
+) -> anyhow::Result<()> {
+    let targets = read_targets(targets)?;
+    let reserve_buffer = sol_str_to_lamports(&reserve_buffer.to_string())
+        .ok_or(anyhow!("Invalid reserve buffer"))?;
+
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
+    let stake_rent =
+        get_minimum_balance_for_rent_exemption(&config.accounts, StakeStateV2::size_of())?;
+    let reserve_lamports = get_balance(&config.accounts, &stake_pool.reserve_stake)?;
+    let reserve_available_lamports = reserve_lamports
+        .saturating_sub(minimum_reserve_lamports(&Meta {
+            rent_exempt_reserve: stake_rent,
+            ..Meta::default()
+        }))
+        .saturating_sub(reserve_buffer);
+
+    let moves = plan_rebalance(
+        &validator_list,
+        &targets,
+        reserve_available_lamports,
+        stake_rent,
+    )?;
+
+    println!(
+        "Reserve available for increases: {}",
+        Sol(reserve_available_lamports)
+    );
+    let mut instructions = vec![];
+    for (index, m) in moves.iter().enumerate() {
+        let action = match m.action {
+            Some(RebalanceAction::Increase(lamports)) => format!("increase {}", Sol(lamports)),
+            Some(RebalanceAction::Decrease(lamports)) => format!("decrease {}", Sol(lamports)),
+            None => "none".to_string(),
+        };
+        println!(
+            "{}\tcurrent: {}\ttarget: {}\taction: {action}{}",
+            m.vote_account,
+            Sol(m.current_lamports),
+            Sol(m.target_lamports),
+            m.note.map(|note| format!(" ({note})")).unwrap_or_default(),
+        );
+
+        let Some(action) = m.action else {
+            continue;
+        };
+        let seed: u64 = validator_list
+            .find(&m.vote_account)
+            .map(|validator| validator.transient_seed_suffix.into())
+            .unwrap_or_default();
+        let instruction = match action {
+            RebalanceAction::Increase(lamports) => increase_validator_stake_with_vote(
+                &config.program_id,
+                &stake_pool,
+                stake_pool_address,
+                &m.vote_account,
+                lamports,
+                seed,
+            ),
+            RebalanceAction::Decrease(lamports) => decrease_validator_stake_with_vote(
+                &config.program_id,
+                &stake_pool,
+                stake_pool_address,
+                &m.vote_account,
+                lamports,
+                seed,
+            ),
+        };
+        instructions.push((index, instruction));
+    }
+
+    if instructions.is_empty() {
+        println!("Pool is already balanced, nothing to do");
+        return Ok(());
+    }
+
+    let signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    for (_, transaction_instructions) in pack_instructions(config, instructions)? {
+        let transaction =
+            checked_transaction_with_signers(config, &transaction_instructions, &signers)?;
+        send_transaction(config, transaction, &signers)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+    const STAKE_RENT: u64 = 2_282_880;
+
+    fn validator(active_lamports: u64, transient_lamports: u64) -> ValidatorStakeInfo {
+        ValidatorStakeInfo {
+            vote_account_address: Pubkey::new_unique(),
+            active_stake_lamports: active_lamports.into(),
+            transient_stake_lamports: transient_lamports.into(),
+            ..ValidatorStakeInfo::default()
+        }
+    }
+
+    fn validator_list(validators: Vec<ValidatorStakeInfo>) -> ValidatorList {
+        ValidatorList {
+            validators,
+            ..ValidatorList::new(0)
+        }
+    }
+
+    fn action(moves: &[RebalanceMove], vote_account: &Pubkey) -> Option<RebalanceAction> {
+        moves
+            .iter()
+            .find(|m| &m.vote_account == vote_account)
+            .unwrap()
+            .action
+    }
+
+    #[test]
+    fn skips_validators_with_transient_stake() {
+        let list = validator_list(vec![validator(90 * SOL, 10 * SOL), validator(100 * SOL, 0)]);
+        let (busy, idle) = (
+            list.validators[0].vote_account_address,
+            list.validators[1].vote_account_address,
+        );
+        let targets = HashMap::from([(idle, 1.0)]);
+
+        let moves = plan_rebalance(&list, &targets, 0, STAKE_RENT).unwrap();
+
+        let busy_move = moves.iter().find(|m| m.vote_account == busy).unwrap();
+        assert_eq!(busy_move.action, None);
+        assert_eq!(busy_move.note, Some("transient stake in flight"));
+        assert_eq!(action(&moves, &idle), None);
+    }
+
+    #[test]
+    fn funds_increases_from_the_reserve_only() {
+        let list = validator_list(vec![
+            validator(10 * SOL, 0),
+            validator(10 * SOL, 0),
+            validator(10 * SOL, 0),
+        ]);
+        let vote_accounts: Vec<_> = list
+            .validators
+            .iter()
+            .map(|validator| validator.vote_account_address)
+            .collect();
+        let targets = HashMap::from([
+            (vote_accounts[0], 4.0),
+            (vote_accounts[1], 1.0),
+            (vote_accounts[2], 1.0),
+        ]);
+        let reserve = 6 * SOL;
+
+        let moves = plan_rebalance(&list, &targets, reserve, STAKE_RENT).unwrap();
+
+        // 36 SOL split 4:1:1 targets 24, 6 and 6 SOL, but the reserve only
+        // covers part of the first increase
+        assert_eq!(
+            action(&moves, &vote_accounts[0]),
+            Some(RebalanceAction::Increase(reserve - STAKE_RENT))
+        );
+        assert_eq!(
+            action(&moves, &vote_accounts[1]),
+            Some(RebalanceAction::Decrease(4 * SOL))
+        );
+        let planned: u64 = moves
+            .iter()
+            .filter_map(|m| match m.action {
+                Some(RebalanceAction::Increase(lamports)) => Some(lamports + STAKE_RENT),
+                _ => None,
+            })
+            .sum();
+        assert!(planned <= reserve);
+    }
+
+    #[test]
+    fn plans_at_most_max_transient_stake_accounts_moves() {
+        let list = validator_list(
+            (0..MAX_TRANSIENT_STAKE_ACCOUNTS as u64 + 5)
+                .map(|index| validator((index + 1) * 10 * SOL, 0))
+                .collect(),
+        );
+        let targets = HashMap::from([(list.validators[0].vote_account_address, 1.0)]);
+
+        let moves = plan_rebalance(&list, &targets, 0, STAKE_RENT).unwrap();
+
+        assert_eq!(
+            moves.iter().filter(|m| m.action.is_some()).count(),
+            MAX_TRANSIENT_STAKE_ACCOUNTS
+        );
+        // Without a reserve the first validator cannot grow, so 14 validators
+        // are drained and the 4 smallest wait for the next run
+        let deferred: Vec<_> = moves
+            .iter()
+            .filter(|m| m.note == Some("transient account limit reached"))
+            .map(|m| m.current_lamports)
+            .collect();
+        assert_eq!(deferred, [50 * SOL, 40 * SOL, 30 * SOL, 20 * SOL]);
+    }
+
+    #[test]
+    fn lists_targets_of_inactive_validators() {
+        let mut list = validator_list(vec![validator(10 * SOL, 0), validator(10 * SOL, 0)]);
+        list.validators[1].status = StakeStatus::DeactivatingValidator.into();
+        let (active, deactivating) = (
+            list.validators[0].vote_account_address,
+            list.validators[1].vote_account_address,
+        );
+        let targets = HashMap::from([(active, 1.0), (deactivating, 1.0)]);
+
+        let moves = plan_rebalance(&list, &targets, 0, STAKE_RENT).unwrap();
+
+        assert_eq!(moves.len(), 2);
+        let inactive_move = moves
+            .iter()
+            .find(|m| m.vote_account == deactivating)
+            .unwrap();
+        assert_eq!(inactive_move.action, None);
+        assert_eq!(inactive_move.note, Some("validator is not active"));
+        assert_eq!(inactive_move.target_lamports, 5 * SOL);
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{clock::DEFAULT_MS_PER_SLOT, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use spl_stake_pool::state::StakePool;
+
+use crate::{
+    client::{get_epoch_schedule, get_stake_pool},
+    config::{JitoStakePoolCliConfig, OutputFormat},
+};
+
+/// Directory holding the history, stored next to the solana cli config file
+pub const HISTORY_DIR_NAME: &str = "stake-pool-history";
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// Show the exchange rate and rewards of the last N recorded epochs
+    #[arg(long, value_name = "N")]
+    pub history: Option<usize>,
+
+    /// Directory of the history recorded on every run [default: stake-pool-history next to the configuration file]
+    #[arg(long = "history-dir", value_name = "PATH")]
+    pub history_dir: Option<String>,
+}
+
+/// Pool balances at the end of an update, recorded once per epoch
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochRecord {
+    pub epoch: u64,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+}
+
+impl EpochRecord {
+    pub fn exchange_rate(&self) -> f64 {
+        if self.pool_token_supply == 0 {
+            1.0
+        } else {
+            self.total_lamports as f64 / self.pool_token_supply as f64
+        }
+    }
+}
+
+/// Yield between two records
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochStats {
+    pub epoch: u64,
+    pub exchange_rate: f64,
+    /// Rewards earned by pool token holders, net of the epoch fee
+    pub rewards_lamports: i128,
+    pub epoch_yield: f64,
+    pub apy: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsReport {
+    pub pool: String,
+    pub last_update_epoch: u64,
+    pub exchange_rate: f64,
+    pub last_epoch_exchange_rate: f64,
+    pub epoch_fee: f64,
+    pub epochs_per_year: f64,
+    /// APY of the last epoch, net of the epoch fee
+    pub last_epoch_apy: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<EpochStats>>,
+}
+
+/// Computes the yield from `previous` to `current`, annualized with
+/// `epochs_per_year`
+pub fn epoch_stats(
+    previous: &EpochRecord,
+    current: &EpochRecord,
+    epochs_per_year: f64,
+) -> EpochStats {
+    let growth = current.exchange_rate() / previous.exchange_rate();
+    let epochs = current.epoch.saturating_sub(previous.epoch).max(1) as f64;
+    EpochStats {
+        epoch: current.epoch,
+        exchange_rate: current.exchange_rate(),
+        rewards_lamports: ((current.exchange_rate() - previous.exchange_rate())
+            * current.pool_token_supply as f64) as i128,
+        epoch_yield: growth.powf(1.0 / epochs) - 1.0,
+        apy: growth.powf(epochs_per_year / epochs) - 1.0,
+    }
+}
+
+pub fn command_stats(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    history: Option<usize>,
+    history_dir: Option<&str>,
+) -> anyhow::Result<()> {
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let epochs_per_year = epochs_per_year(config)?;
+
+    let history_dir = history_dir
+        .map(PathBuf::from)
+        .or_else(default_history_dir)
+        .ok_or(anyhow!("No history directory, use --history-dir"))?
+        .join(stake_pool_address.to_string());
+    let current = EpochRecord {
+        epoch: stake_pool.last_update_epoch,
+        total_lamports: stake_pool.total_lamports,
+        pool_token_supply: stake_pool.pool_token_supply,
+    };
+    // A replay shows the history as it was, without adding to it
+    if !config.replay {
+        record_epoch(&history_dir, &current)?;
+    }
+
+    let previous = EpochRecord {
+        epoch: stake_pool.last_update_epoch.saturating_sub(1),
+        total_lamports: stake_pool.last_epoch_total_lamports,
+        pool_token_supply: stake_pool.last_epoch_pool_token_supply,
+    };
+    let last_epoch = epoch_stats(&previous, &current, epochs_per_year);
+
+    let history = history
+        .map(|count| -> anyhow::Result<Vec<EpochStats>> {
+            let records = load_records(&history_dir)?;
+            let skip = records.len().saturating_sub(count + 1);
+            Ok(records[skip..]
+                .windows(2)
+                .map(|pair| epoch_stats(&pair[0], &pair[1], epochs_per_year))
+                .collect())
+        })
+        .transpose()?;
+
+    let report = StatsReport {
+        pool: stake_pool_address.to_string(),
+        last_update_epoch: stake_pool.last_update_epoch,
+        exchange_rate: current.exchange_rate(),
+        last_epoch_exchange_rate: previous.exchange_rate(),
+        epoch_fee: epoch_fee(&stake_pool),
+        epochs_per_year,
+        last_epoch_apy: last_epoch.apy,
+        history,
+    };
+
+    match config.output_format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::JsonCompact => println!("{}", serde_json::to_string(&report)?),
+        OutputFormat::Display => print_report(&report),
+    }
+    Ok(())
+}
+
+fn print_report(report: &StatsReport) {
+    println!("Stake pool: {}", report.pool);
+    println!("Last update epoch: {}", report.last_update_epoch);
+    println!(
+        "Exchange rate: {:.9} SOL per pool token",
+        report.exchange_rate
+    );
+    println!(
+        "Previous exchange rate: {:.9} SOL per pool token",
+        report.last_epoch_exchange_rate
+    );
+    println!("Epoch fee: {:.2}%", report.epoch_fee * 100.0);
+    println!("Epochs per year: {:.1}", report.epochs_per_year);
+    println!("Last epoch APY: {:.2}%", report.last_epoch_apy * 100.0);
+
+    if let Some(history) = &report.history {
+        if history.is_empty() {
+            println!("No history yet, one record is added on every run");
+            return;
+        }
+        println!();
+        println!(
+            "{:>8}  {:>14}  {:>16}  {:>12}  {:>8}",
+            "Epoch", "Exchange rate", "Rewards (SOL)", "Epoch yield", "APY"
+        );
+        for epoch in history {
+            println!(
+                "{:>8}  {:>14.9}  {:>16.9}  {:>11.4}%  {:>7.2}%",
+                epoch.epoch,
+                epoch.exchange_rate,
+                epoch.rewards_lamports as f64 / LAMPORTS_PER_SOL as f64,
+                epoch.epoch_yield * 100.0,
+                epoch.apy * 100.0,
+            );
+        }
+    }
+}
+
+fn epoch_fee(stake_pool: &StakePool) -> f64 {
+    if stake_pool.epoch_fee.denominator == 0 {
+        0.0
+    } else {
+        stake_pool.epoch_fee.numerator as f64 / stake_pool.epoch_fee.denominator as f64
+    }
+}
+
+/// Estimates the number of epochs per year from the recent slot times, or
+/// from the target slot time when replaying a snapshot
+fn epochs_per_year(config: &JitoStakePoolCliConfig) -> anyhow::Result<f64> {
+    let slots_per_epoch = get_epoch_schedule(&config.accounts)?.slots_per_epoch;
+    let samples = if config.replay {
+        vec![]
+    } else {
+        config.rpc_client.get_recent_performance_samples(Some(60))?
+    };
+    let (slots, seconds) = samples
+        .iter()
+        .fold((0u64, 0u64), |(slots, seconds), sample| {
+            (
+                slots + sample.num_slots,
+                seconds + u64::from(sample.sample_period_secs),
+            )
+        });
+    let seconds_per_slot = if slots == 0 {
+        DEFAULT_MS_PER_SLOT as f64 / 1000.0
+    } else {
+        seconds as f64 / slots as f64
+    };
+    Ok(SECONDS_PER_YEAR / (slots_per_epoch as f64 * seconds_per_slot))
+}
+
+fn default_history_dir() -> Option<PathBuf> {
+    let config_file = solana_cli_config::CONFIG_FILE.as_ref()?;
+    Some(Path::new(config_file).parent()?.join(HISTORY_DIR_NAME))
+}
+
+/// Writes `record` to `<dir>/<epoch>.json`, replacing the one of the same
+/// epoch
+fn record_epoch(dir: &Path, record: &EpochRecord) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).map_err(|err| anyhow!("Unable to create {}: {err}", dir.display()))?;
+    let path = dir.join(format!("{}.json", record.epoch));
+    serde_json::to_writer_pretty(File::create(&path)?, record)?;
+    Ok(())
+}
+
+/// Loads every record of `dir`, sorted by epoch. A missing directory has no
+/// records yet.
+fn load_records(dir: &Path) -> anyhow::Result<Vec<EpochRecord>> {
+    let mut records = vec![];
+    if !dir.exists() {
+        return Ok(records);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let record: EpochRecord = serde_json::from_reader(File::open(&path)?)
+                .map_err(|err| anyhow!("Invalid history record {}: {err}", path.display()))?;
+            records.push(record);
+        }
+    }
+    records.sort_by_key(|record| record.epoch);
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, test_config},
+    };
+
+    #[test]
+    fn replay_records_no_history() {
+        let (mut config, _) = test_config(MemoryTransactions::default());
+        let pool = TestPool::new(&config);
+        config.accounts = Box::new(pool.accounts());
+        config.replay = true;
+        let history_dir = std::env::temp_dir().join(format!("history-{}", Pubkey::new_unique()));
+
+        command_stats(
+            &config,
+            &pool.address,
+            Some(10),
+            Some(history_dir.to_str().unwrap()),
+        )
+        .unwrap();
+
+        assert!(!history_dir.exists());
+        // The default schedule at the target slot time
+        let expected = SECONDS_PER_YEAR / (432_000.0 * DEFAULT_MS_PER_SLOT as f64 / 1000.0);
+        assert_eq!(epochs_per_year(&config).unwrap(), expected);
+    }
+}
@@ -0,0 +1,81 @@
+use clap::Args;
+use jito_stake_pool_sdk::update_plan::{UpdateOptions, UpdateStep, plan_update};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{get_epoch, get_stake_pool, get_validator_list},
+    config::JitoStakePoolCliConfig,
+    send_transaction,
+};
+
+#[derive(Args)]
+pub struct UpdateArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// Update all balances, even if it has already been performed this epoch
+    #[arg(long)]
+    pub force: bool,
+
+    /// Do not automatically merge transient stakes. Useful if the stake pool is in an expected state, but the balances still need to be updated
+    #[arg(long = "no-merge")]
+    pub no_merge: bool,
+}
+
+pub fn command_update(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    force: bool,
+    no_merge: bool,
+) -> anyhow::Result<()> {
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let validator_list = get_validator_list(
+        &config.accounts,
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        config.layout,
+    )?
+    .validator_list;
+    let epoch = get_epoch(&config.accounts)?;
+
+    let batches = plan_update(
+        &config.program_id,
+        stake_pool_address,
+        &stake_pool,
+        &validator_list,
+        epoch,
+        UpdateOptions { force, no_merge },
+    );
+    if batches.is_empty() {
+        println!("Stake pool already updated for epoch {epoch}");
+        return Ok(());
+    }
+
+    let signers = vec![config.fee_payer.as_ref()];
+    let mut updated_validators = false;
+    for batch in batches {
+        match batch.step {
+            UpdateStep::ValidatorList {
+                start_index,
+                end_index,
+            } => {
+                println!(
+                    "Updating validators {start_index}..{end_index} of {}",
+                    validator_list.validators.len()
+                );
+                updated_validators = true;
+            }
+            // Simulating the pool balance update would fail on the validator
+            // list the dry run did not update
+            UpdateStep::StakePoolBalance if config.dry_run && updated_validators => break,
+            UpdateStep::StakePoolBalance => println!("Updating stake pool balance"),
+            UpdateStep::Cleanup => println!("Cleaning up removed validator entries"),
+        }
+        let transaction = checked_transaction_with_signers(config, &batch.instructions, &signers)?;
+        send_transaction(config, transaction, &signers)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+use clap::Args;
+use jito_stake_pool_sdk::{
+    address::find_withdraw_authority_program_address,
+    sdk::withdraw_sol::{withdraw_sol, withdraw_sol_with_authority},
+};
+use solana_sdk::{
+    native_token::{Sol, sol_str_to_lamports},
+    pubkey::Pubkey,
+    signer::Signer,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    checked_transaction_with_signers,
+    client::{get_stake_pool, get_token_account, get_token_mint},
+    config::JitoStakePoolCliConfig,
+    send_transaction,
+    simulate::simulate_balance_delta,
+};
+
+#[derive(Args)]
+pub struct WithdrawSolArgs {
+    /// Stake pool address or profile name
+    pub pool: String,
+
+    /// System account to receive SOL from the stake pool
+    pub sol_receiver: String,
+
+    /// Amount of pool tokens to withdraw for SOL
+    pub amount: f64,
+
+    /// Pool token account to withdraw tokens from. Defaults to the token-owner's associated token account.
+    #[arg(long = "pool-account", value_name = "ADDRESS")]
+    pub pool_account: Option<String>,
+
+    /// Abort if the withdrawal, simulated first, pays out less than this amount in SOL
+    #[arg(long = "min-lamports-out", value_name = "SOL")]
+    pub min_lamports_out: Option<f64>,
+}
+
+pub fn command_withdraw_sol(
+    config: &JitoStakePoolCliConfig,
+    stake_pool_address: &Pubkey,
+    pool_token_account: &Option<Pubkey>,
+    sol_receiver: &Pubkey,
+    amount: f64,
+    min_lamports_out: Option<f64>,
+) -> anyhow::Result<()> {
+    let stake_pool =
+        get_stake_pool(&config.accounts, stake_pool_address, config.layout)?.stake_pool;
+    let pool_mint = get_token_mint(&config.accounts, &stake_pool.pool_mint)?;
+    let pool_amount = spl_token::ui_amount_to_amount(amount, pool_mint.decimals);
+
+    let pool_token_account = pool_token_account.unwrap_or(get_associated_token_address(
+        &config.token_owner.pubkey(),
+        &stake_pool.pool_mint,
+    ));
+    let token_account =
+        get_token_account(&config.accounts, &pool_token_account, &stake_pool.pool_mint)?;
+    if token_account.amount < pool_amount {
+        return Err(anyhow!(
+            "Not enough token balance to withdraw {} pool tokens.\nMaximum withdraw amount is {} pool tokens.",
+            spl_token::amount_to_ui_amount_string_trimmed(pool_amount, pool_mint.decimals),
+            spl_token::amount_to_ui_amount_string_trimmed(token_account.amount, pool_mint.decimals)
+        ));
+    }
+
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(&config.program_id, stake_pool_address).0;
+    let mut signers = vec![config.fee_payer.as_ref(), config.token_owner.as_ref()];
+
+    let withdraw_instruction = if let Some(withdraw_authority) = config.funding_authority.as_ref() {
+        let expected_sol_withdraw_authority =
+            stake_pool.sol_withdraw_authority.ok_or_else(|| {
+                anyhow!("SOL withdraw authority specified in arguments but stake pool has none")
+            })?;
+        if withdraw_authority.pubkey() != expected_sol_withdraw_authority {
+            return Err(anyhow!(
+                "Invalid withdraw authority specified, expected {}, received {}",
+                expected_sol_withdraw_authority,
+                withdraw_authority.pubkey()
+            ));
+        }
+        signers.push(withdraw_authority.as_ref());
+
+        withdraw_sol_with_authority(
+            &config.program_id,
+            stake_pool_address,
+            &withdraw_authority.pubkey(),
+            &pool_withdraw_authority,
+            &config.token_owner.pubkey(),
+            &pool_token_account,
+            &stake_pool.reserve_stake,
+            sol_receiver,
+            &stake_pool.manager_fee_account,
+            &stake_pool.pool_mint,
+            &spl_token::id(),
+            pool_amount,
+        )
+    } else {
+        withdraw_sol(
+            &config.program_id,
+            stake_pool_address,
+            &pool_withdraw_authority,
+            &config.token_owner.pubkey(),
+            &pool_token_account,
+            &stake_pool.reserve_stake,
+            sol_receiver,
+            &stake_pool.manager_fee_account,
+            &stake_pool.pool_mint,
+            &spl_token::id(),
+            pool_amount,
+        )
+    };
+
+    let transaction = checked_transaction_with_signers(config, &[withdraw_instruction], &signers)?;
+
+    if let Some(min_lamports_out) = min_lamports_out {
+        let minimum = sol_str_to_lamports(&min_lamports_out.to_string())
+            .ok_or(anyhow!("Invalid minimum amount"))?;
+        let mut lamports_out = simulate_balance_delta(config, &transaction, sol_receiver)?;
+        if *sol_receiver == config.fee_payer.pubkey() {
+            // The receiver also pays the transaction fee
+            lamports_out += config
+                .transactions
+                .get_fee_for_message(&transaction.message)? as i128;
+        }
+        if lamports_out < minimum as i128 {
+            return Err(anyhow!(
+                "Withdrawal would pay out {}, below the minimum of {}",
+                Sol(lamports_out.max(0) as u64),
+                Sol(minimum)
+            ));
+        }
+    }
+
+    send_transaction(config, transaction, &signers)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+    use super::*;
+    use crate::{
+        sink::MemoryTransactions,
+        test_utils::{TestPool, assert_instructions, system_account, test_config},
+    };
+
+    #[test]
+    fn withdraws_from_associated_token_account() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let token_owner = config.token_owner.pubkey();
+        let pool_tokens = get_associated_token_address(&token_owner, &pool.stake_pool.pool_mint);
+        pool.add_token_account(pool_tokens, &token_owner, 5 * LAMPORTS_PER_SOL);
+        config.accounts = Box::new(pool.accounts());
+        let receiver = Pubkey::new_unique();
+
+        command_withdraw_sol(&config, &pool.address, &None, &receiver, 2.0, None).unwrap();
+
+        let sent = transactions.sent();
+        assert_eq!(sent.len(), 1);
+        let fee_payer = config.fee_payer.pubkey();
+        assert_eq!(sent[0].signers, [fee_payer, token_owner]);
+        assert_instructions(
+            &sent[0],
+            &fee_payer,
+            &[withdraw_sol(
+                &config.program_id,
+                &pool.address,
+                &find_withdraw_authority_program_address(&config.program_id, &pool.address).0,
+                &token_owner,
+                &pool_tokens,
+                &pool.stake_pool.reserve_stake,
+                &receiver,
+                &pool.stake_pool.manager_fee_account,
+                &pool.stake_pool.pool_mint,
+                &spl_token::id(),
+                2 * LAMPORTS_PER_SOL,
+            )],
+        );
+    }
+
+    #[test]
+    fn rejects_withdrawal_above_token_balance() {
+        let (mut config, transactions) = test_config(MemoryTransactions::default());
+        let mut pool = TestPool::new(&config);
+        let pool_tokens = Pubkey::new_unique();
+        pool.add_token_account(pool_tokens, &config.token_owner.pubkey(), LAMPORTS_PER_SOL);
+        config.accounts = Box::new(pool.accounts());
+
+        let error = command_withdraw_sol(
+            &config,
+            &pool.address,
+            &Some(pool_tokens),
+            &Pubkey::new_unique(),
+            1.5,
+            None,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Not enough token balance to withdraw 1.5 pool tokens.\nMaximum withdraw amount is 1 pool tokens."
+        );
+        assert!(transactions.sent().is_empty());
+    }
+
+    #[test]
+    fn aborts_below_minimum_payout() {
+        let receiver = Pubkey::new_unique();
+        let (mut config, transactions) = test_config(
+            MemoryTransactions::default()
+                .with_post_account(receiver, system_account(LAMPORTS_PER_SOL + 900_000_000)),
+        );
+        let mut pool = TestPool::new(&config);
+        let pool_tokens = Pubkey::new_unique();
+        pool.add_token_account(pool_tokens, &config.token_owner.pubkey(), LAMPORTS_PER_SOL);
+        pool.add_account(receiver, system_account(LAMPORTS_PER_SOL));
+        config.accounts = Box::new(pool.accounts());
+
+        let error = command_withdraw_sol(
+            &config,
+            &pool.address,
+            &Some(pool_tokens),
+            &receiver,
+            1.0,
+            Some(0.95),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Withdrawal would pay out ◎0.900000000, below the minimum of ◎0.950000000"
+        );
+        assert_eq!(transactions.simulated().len(), 1);
+        assert!(transactions.sent().is_empty());
+    }
+}
@@ -1,14 +1,44 @@
+use std::sync::Arc;
+
+use jito_stake_pool_sdk::layout::LayoutVersion;
 use solana_rpc_client::rpc_client::RpcClient;
-use solana_sdk::signer::Signer;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use crate::{
+    client::{AccountSource, TransactionSink},
+    profile::Profiles,
+};
 
 pub struct JitoStakePoolCliConfig {
     /// RPC Client
-    pub rpc_client: RpcClient,
+    pub rpc_client: Arc<RpcClient>,
+
+    /// Where accounts are read from, the RPC client unless replaying a snapshot
+    pub accounts: Box<dyn AccountSource>,
+
+    /// Accounts are replayed from a snapshot, so nothing else may be read
+    /// from the cluster or written to disk
+    pub replay: bool,
+
+    /// Where transactions are simulated and sent, the RPC client outside of tests
+    pub transactions: Box<dyn TransactionSink>,
+
+    /// Stake pool program id
+    pub program_id: Pubkey,
+
+    /// Layout of the program version that owns the pool, detected from the
+    /// account contents when not configured
+    pub layout: Option<LayoutVersion>,
+
+    /// Named pool profiles
+    pub profiles: Profiles,
 
     /// Verbose
     pub verbose: bool,
 
-    // output_format: OutputFormat,
+    /// Output format of reports
+    pub output_format: OutputFormat,
+
     /// Manager
     pub manager: Box<dyn Signer>,
 
@@ -29,4 +59,16 @@ pub struct JitoStakePoolCliConfig {
 
     /// No update
     pub no_update: bool,
+
+    /// Maximum number of times a transaction is re-signed and sent again
+    pub max_retries: usize,
+}
+
+/// How commands that print a report format it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Display,
+    Json,
+    JsonCompact,
 }
@@ -0,0 +1,72 @@
+use jito_stake_pool_sdk::error::{StakePoolError, decode_transaction_error};
+use solana_sdk::{message::Message, pubkey::Pubkey, transaction::TransactionError};
+
+/// Explains `error` if it was returned by the stake pool program, including a
+/// suggested fix when one is known.
+pub fn explain_transaction_error(
+    message: &Message,
+    error: &TransactionError,
+    program_id: &Pubkey,
+) -> Option<String> {
+    let (index, error) = decode_transaction_error(error)?;
+    if message.program_id(index as usize) != Some(program_id) {
+        return None;
+    }
+    let mut explanation = format!("Stake pool error in instruction {index}: {error}");
+    if let Some(fix) = suggested_fix(&error) {
+        explanation.push_str(&format!("\nSuggested fix: {fix}"));
+    }
+    Some(explanation)
+}
+
+/// What the user can do about a stake pool error
+pub fn suggested_fix(error: &StakePoolError) -> Option<&'static str> {
+    match error {
+        StakePoolError::AlreadyInUse => Some("use new keypairs for the pool accounts"),
+        StakePoolError::FeeTooHigh => Some("the fee numerator must not exceed the denominator"),
+        StakePoolError::WrongManager => Some("sign with the pool manager using `--manager`"),
+        StakePoolError::SignatureMissing => {
+            Some("pass the missing signer with `--staker`, `--manager` or `--funding-authority`")
+        }
+        StakePoolError::UserStakeNotActive => {
+            Some("wait for the stake account to be fully active before depositing it")
+        }
+        StakePoolError::ValidatorAlreadyAdded => Some("the validator is already in the pool"),
+        StakePoolError::ValidatorNotFound => Some("add the validator with `add-validator` first"),
+        StakePoolError::StakeListOutOfDate | StakePoolError::StakeListAndPoolOutOfDate => {
+            Some("run `update` first")
+        }
+        StakePoolError::WrongStaker => Some("sign with the pool staker using `--staker`"),
+        StakePoolError::StakeLamportsNotEqualToMinimum => {
+            Some("decrease the validator stake to the minimum before removing it")
+        }
+        StakePoolError::IncorrectDepositVoteAddress => {
+            Some("deposit stake delegated to the pool's preferred deposit validator")
+        }
+        StakePoolError::IncorrectWithdrawVoteAddress => {
+            Some("withdraw from the pool's preferred withdraw validator")
+        }
+        StakePoolError::FeeIncreaseTooHigh => {
+            Some("withdrawal fees can increase by at most 1.5x per epoch, use a smaller fee")
+        }
+        StakePoolError::WithdrawalTooSmall => Some("withdraw a larger amount of pool tokens"),
+        StakePoolError::DepositTooSmall => Some("deposit a larger amount"),
+        StakePoolError::InvalidStakeDepositAuthority
+        | StakePoolError::InvalidSolDepositAuthority => {
+            Some("sign with the pool's deposit authority using `--funding-authority`")
+        }
+        StakePoolError::InvalidSolWithdrawAuthority => {
+            Some("sign with the pool's SOL withdraw authority using `--funding-authority`")
+        }
+        StakePoolError::InvalidPreferredValidator => {
+            Some("the preferred validator must be an active validator in the pool")
+        }
+        StakePoolError::TransientAccountInUse => {
+            Some("wait for the transient stake to be merged by `update` in the next epoch")
+        }
+        StakePoolError::SolWithdrawalTooLarge => {
+            Some("the reserve does not hold enough SOL, withdraw less or withdraw stake instead")
+        }
+        _ => None,
+    }
+}
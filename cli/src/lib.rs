@@ -1,29 +1,55 @@
+use anyhow::anyhow;
 use solana_sdk::{
-    commitment_config::CommitmentConfig, instruction::Instruction, message::Message,
-    program_pack::Pack, pubkey::Pubkey, signers::Signers, transaction::Transaction,
+    instruction::Instruction, message::Message, program_pack::Pack, pubkey::Pubkey,
+    signers::Signers, transaction::Transaction,
 };
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
 
-use crate::{client::get_token_account, config::JitoStakePoolCliConfig};
+use crate::{
+    client::{get_minimum_balance_for_rent_exemption, get_token_account},
+    config::JitoStakePoolCliConfig,
+    error::explain_transaction_error,
+    send::{TransactionStatus, send_with_retries},
+    simulate::simulate_transaction,
+};
 
 pub mod client;
 pub mod command;
 pub mod config;
+pub mod error;
+pub mod profile;
+pub mod send;
+pub mod simulate;
+pub mod sink;
+pub mod snapshot;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 pub fn send_transaction(
     config: &JitoStakePoolCliConfig,
     transaction: Transaction,
+    signers: &dyn Signers,
 ) -> anyhow::Result<()> {
     if config.dry_run {
-        let result = config.rpc_client.simulate_transaction(&transaction)?;
-        println!("Simulate result: {result:?}");
+        simulate_transaction(config, &transaction)?;
     } else {
-        let signature = config
-            .rpc_client
-            .send_and_confirm_transaction_with_spinner(&transaction)?;
-        println!("Signature: {signature}");
+        let message = transaction.message.clone();
+        let status = send_with_retries(config, transaction, signers)?;
+        println!("{status}");
+        if let TransactionStatus::Failed { error, .. } = &status
+            && let Some(explanation) =
+                explain_transaction_error(&message, error, &config.program_id)
+        {
+            println!("{explanation}");
+        }
+        if !matches!(status, TransactionStatus::Confirmed(_)) {
+            return Err(anyhow!(
+                "Transaction {} was not confirmed",
+                status.signature()
+            ));
+        }
     }
     Ok(())
 }
@@ -33,10 +59,7 @@ pub fn checked_transaction_with_signers<T: Signers>(
     instructions: &[Instruction],
     signers: &T,
 ) -> anyhow::Result<Transaction> {
-    let recent_blockhash = config
-        .rpc_client
-        .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())?
-        .0;
+    let recent_blockhash = config.transactions.get_latest_blockhash()?;
     // let recent_blockhash = get_latest_blockhash(&config.rpc_client)?;
     let message = Message::new_with_blockhash(
         instructions,
@@ -57,16 +80,17 @@ fn add_associated_token_account(
 ) -> Pubkey {
     // Account for tokens not specified, creating one
     let account = get_associated_token_address(owner, mint);
-    if get_token_account(&config.rpc_client, &account, mint).is_err() {
+    if get_token_account(&config.accounts, &account, mint).is_err() {
         println!(
             "Creating associated token account {} to receive stake pool tokens of mint {}, owned by {}",
             account, mint, owner
         );
 
-        let min_account_balance = config
-            .rpc_client
-            .get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
-            .unwrap();
+        let min_account_balance = get_minimum_balance_for_rent_exemption(
+            &config.accounts,
+            spl_token::state::Account::LEN,
+        )
+        .unwrap();
 
         #[allow(deprecated)]
         instructions.push(create_associated_token_account(
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, fs::File, path::Path, str::FromStr};
+
+use anyhow::anyhow;
+use jito_stake_pool_sdk::layout::LayoutVersion;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// File name of the profile file, stored next to the solana cli config file
+pub const PROFILES_FILE_NAME: &str = "stake-pool-profiles.yml";
+
+/// Named stake pool, so that commands can take `jitosol` instead of an address
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PoolProfile {
+    /// Stake pool address
+    pub pool: String,
+
+    /// JSON RPC URL for the cluster the pool lives on
+    pub url: Option<String>,
+
+    /// Stake pool program id
+    pub program_id: Option<String>,
+
+    /// Layout of the program version that owns the pool, `0.6.4` or `2.x`
+    pub layout: Option<String>,
+
+    /// Default staker keypair path
+    pub staker: Option<String>,
+
+    /// Default manager keypair path
+    pub manager: Option<String>,
+}
+
+/// Profiles file, mapping profile names to pools
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Profiles {
+    pub profiles: BTreeMap<String, PoolProfile>,
+}
+
+impl Profiles {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .map_err(|err| anyhow!("Unable to open profiles {}: {err}", path.display()))?;
+        serde_yaml::from_reader(file)
+            .map_err(|err| anyhow!("Invalid profiles {}: {err}", path.display()))
+    }
+
+    /// Loads the profiles next to `config_file`, or next to the default solana
+    /// cli config. A missing default file means no profiles.
+    pub fn load_default(config_file: Option<&str>) -> anyhow::Result<Self> {
+        let config_file = config_file
+            .map(str::to_string)
+            .or_else(|| solana_cli_config::CONFIG_FILE.clone());
+        let Some(path) = config_file
+            .as_deref()
+            .and_then(|config_file| Path::new(config_file).parent())
+            .map(|directory| directory.join(PROFILES_FILE_NAME))
+        else {
+            return Ok(Self::default());
+        };
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PoolProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Parses `pool` as an address, falling back to a profile name
+    pub fn resolve_pool(&self, pool: &str) -> anyhow::Result<Pubkey> {
+        if let Ok(address) = Pubkey::from_str(pool) {
+            return Ok(address);
+        }
+        let profile = self
+            .get(pool)
+            .ok_or_else(|| anyhow!("{pool} is neither a pool address nor a profile name"))?;
+        Pubkey::from_str(&profile.pool)
+            .map_err(|err| anyhow!("Invalid pool address in profile {pool}: {err}"))
+    }
+}
+
+impl PoolProfile {
+    pub fn program_id(&self) -> anyhow::Result<Option<Pubkey>> {
+        self.program_id
+            .as_deref()
+            .map(|program_id| {
+                Pubkey::from_str(program_id)
+                    .map_err(|err| anyhow!("Invalid program id {program_id}: {err}"))
+            })
+            .transpose()
+    }
+
+    pub fn layout(&self) -> anyhow::Result<Option<LayoutVersion>> {
+        self.layout
+            .as_deref()
+            .map(|layout| {
+                LayoutVersion::from_str(layout).map_err(|err| anyhow!("Invalid layout: {err}"))
+            })
+            .transpose()
+    }
+}
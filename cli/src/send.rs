@@ -0,0 +1,302 @@
+use std::{
+    fmt::{Display, Formatter},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, signature::Signature, signers::Signers,
+    transaction::Transaction, transaction::TransactionError,
+};
+
+use crate::config::JitoStakePoolCliConfig;
+
+/// How often the signature status is polled while waiting for confirmation
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often an unconfirmed transaction is sent again to the cluster
+const REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Base delay before retrying after an RPC error, doubled on every attempt
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// How long an attempt waits for a status or for its blockhash to expire. A
+/// blockhash is valid for about 60 seconds, so this only runs out when the
+/// block height cannot be fetched.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Final outcome of sending a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Landed and reached the requested commitment level
+    Confirmed(Signature),
+
+    /// Landed or failed preflight with an error
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    /// Every attempt expired before being confirmed
+    Expired(Signature),
+}
+
+impl TransactionStatus {
+    pub fn signature(&self) -> &Signature {
+        match self {
+            Self::Confirmed(signature) => signature,
+            Self::Failed { signature, .. } => signature,
+            Self::Expired(signature) => signature,
+        }
+    }
+}
+
+impl Display for TransactionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Confirmed(signature) => write!(f, "Confirmed: {signature}"),
+            Self::Failed { signature, error } => write!(f, "Failed: {signature}: {error}"),
+            Self::Expired(signature) => write!(f, "Expired: {signature}"),
+        }
+    }
+}
+
+/// Sends `transaction` through the configured sink until it is confirmed,
+/// fails, or runs out of retries
+pub fn send_with_retries(
+    config: &JitoStakePoolCliConfig,
+    transaction: Transaction,
+    signers: &dyn Signers,
+) -> anyhow::Result<TransactionStatus> {
+    config
+        .transactions
+        .send_transaction(transaction, signers, config.max_retries)
+}
+
+/// Sends `transaction` to the cluster until it is confirmed, fails, or runs
+/// out of retries.
+///
+/// Each attempt re-signs the transaction with a fresh blockhash and keeps
+/// rebroadcasting it until the blockhash expires. The next attempt only starts
+/// once the previous blockhash is no longer valid, so a transaction can never
+/// land twice.
+pub(crate) fn send_with_rpc(
+    rpc_client: &RpcClient,
+    transaction: Transaction,
+    signers: &dyn Signers,
+    max_retries: usize,
+) -> anyhow::Result<TransactionStatus> {
+    send_with_timeout(
+        rpc_client,
+        transaction,
+        signers,
+        max_retries,
+        CONFIRMATION_TIMEOUT,
+    )
+}
+
+/// Fails when an attempt sees neither a status nor the expiry of its
+/// blockhash within `confirmation_timeout`: the transaction may still land,
+/// so it is not safe to send it again
+fn send_with_timeout(
+    rpc_client: &RpcClient,
+    mut transaction: Transaction,
+    signers: &dyn Signers,
+    max_retries: usize,
+    confirmation_timeout: Duration,
+) -> anyhow::Result<TransactionStatus> {
+    let commitment = rpc_client.commitment();
+    let send_config = RpcSendTransactionConfig {
+        preflight_commitment: Some(commitment.commitment),
+        ..RpcSendTransactionConfig::default()
+    };
+    let rebroadcast_config = RpcSendTransactionConfig {
+        skip_preflight: true,
+        max_retries: Some(0),
+        ..send_config
+    };
+
+    let mut last_signature = transaction.signatures.first().copied().unwrap_or_default();
+
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            sleep(RETRY_BACKOFF * 2u32.saturating_pow(attempt as u32 - 1));
+            println!("Retrying transaction, attempt {attempt} of {max_retries}");
+        }
+
+        let (recent_blockhash, last_valid_block_height) =
+            match rpc_client.get_latest_blockhash_with_commitment(commitment) {
+                Ok(blockhash) => blockhash,
+                Err(err) => {
+                    eprintln!("Failed to fetch blockhash: {err}");
+                    continue;
+                }
+            };
+        transaction.try_sign(signers, recent_blockhash)?;
+        last_signature = transaction.signatures[0];
+
+        if let Err(err) = rpc_client.send_transaction_with_config(&transaction, send_config) {
+            match err.get_transaction_error() {
+                Some(TransactionError::BlockhashNotFound) => {
+                    eprintln!("Blockhash not found, refreshing");
+                    continue;
+                }
+                Some(error) => {
+                    return Ok(TransactionStatus::Failed {
+                        signature: last_signature,
+                        error,
+                    });
+                }
+                None => {
+                    // The transaction may still have reached a leader, so keep
+                    // polling and rebroadcasting until its blockhash expires
+                    eprintln!("Failed to send transaction: {err}");
+                }
+            }
+        }
+
+        let sent_at = Instant::now();
+        let mut last_broadcast = sent_at;
+        loop {
+            if sent_at.elapsed() >= confirmation_timeout {
+                return Err(anyhow!(
+                    "Transaction {last_signature} was neither confirmed nor expired after {}s, check its status before sending it again",
+                    confirmation_timeout.as_secs()
+                ));
+            }
+            sleep(POLL_INTERVAL);
+
+            if let Some(status) = get_status(rpc_client, commitment, &last_signature) {
+                return Ok(status);
+            }
+
+            match rpc_client.get_block_height_with_commitment(commitment) {
+                Ok(block_height) if block_height > last_valid_block_height => {
+                    // The transaction may have landed right before the blockhash expired
+                    if let Some(status) = get_status(rpc_client, commitment, &last_signature) {
+                        return Ok(status);
+                    }
+                    eprintln!("Transaction {last_signature} expired");
+                    break;
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("Failed to fetch block height: {err}"),
+            }
+
+            if last_broadcast.elapsed() >= REBROADCAST_INTERVAL {
+                // Errors are ignored, the status poll decides the outcome
+                let _ = rpc_client.send_transaction_with_config(&transaction, rebroadcast_config);
+                last_broadcast = Instant::now();
+            }
+        }
+    }
+
+    Ok(TransactionStatus::Expired(last_signature))
+}
+
+fn get_status(
+    rpc_client: &RpcClient,
+    commitment: CommitmentConfig,
+    signature: &Signature,
+) -> Option<TransactionStatus> {
+    match rpc_client.get_signature_status_with_commitment(signature, commitment) {
+        Ok(Some(Ok(()))) => Some(TransactionStatus::Confirmed(*signature)),
+        Ok(Some(Err(error))) => Some(TransactionStatus::Failed {
+            signature: *signature,
+            error,
+        }),
+        Ok(None) => None,
+        Err(err) => {
+            eprintln!("Failed to fetch signature status: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use solana_rpc_client::mock_sender::MocksMap;
+    use solana_rpc_client_api::request::RpcRequest;
+    use solana_sdk::{
+        instruction::InstructionError, message::Message, pubkey::Pubkey, signature::Keypair,
+        signer::Signer,
+    };
+
+    use super::*;
+
+    fn transaction(payer: &Keypair) -> Transaction {
+        let instruction = solana_system_interface::instruction::transfer(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            1,
+        );
+        Transaction::new_unsigned(Message::new(&[instruction], Some(&payer.pubkey())))
+    }
+
+    fn send(rpc_client: RpcClient, max_retries: usize) -> anyhow::Result<TransactionStatus> {
+        let payer = Keypair::new();
+        send_with_timeout(
+            &rpc_client,
+            transaction(&payer),
+            &[&payer],
+            max_retries,
+            Duration::from_secs(5),
+        )
+    }
+
+    #[test]
+    fn confirms_landed_transaction() {
+        let status = send(RpcClient::new_mock("succeeds"), 0).unwrap();
+        assert!(matches!(status, TransactionStatus::Confirmed(_)));
+    }
+
+    #[test]
+    fn reports_failed_transaction() {
+        let status = send(RpcClient::new_mock("instruction_error"), 0).unwrap();
+        assert_eq!(
+            status,
+            TransactionStatus::Failed {
+                signature: *status.signature(),
+                error: TransactionError::InstructionError(
+                    0,
+                    InstructionError::UninitializedAccount
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn resends_after_expiry_until_out_of_retries() {
+        // The mock blockhash is valid until height 1234
+        let mut mocks = MocksMap::default();
+        mocks.insert(RpcRequest::GetBlockHeight, json!(1235));
+        mocks.insert(RpcRequest::GetBlockHeight, json!(1235));
+        let rpc_client = RpcClient::new_mock_with_mocks_map("sig_not_found", mocks);
+
+        let status = send(rpc_client, 1).unwrap();
+        assert!(matches!(status, TransactionStatus::Expired(_)));
+    }
+
+    #[test]
+    fn fails_when_expiry_cannot_be_observed() {
+        let payer = Keypair::new();
+        let error = send_with_timeout(
+            &RpcClient::new_mock("sig_not_found"),
+            transaction(&payer),
+            &[&payer],
+            3,
+            Duration::from_secs(1),
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("was neither confirmed nor expired after 1s"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::anyhow;
+use jito_stake_pool_sdk::layout::{LayoutVersion, decode_stake_pool, decode_validator_list};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey, transaction::Transaction};
+use spl_stake_pool::state::AccountType;
+
+use crate::{config::JitoStakePoolCliConfig, error::explain_transaction_error};
+
+/// Simulates `transaction` and prints the logs, compute units, decoded error
+/// and the predicted changes to every writable account.
+pub fn simulate_transaction(
+    config: &JitoStakePoolCliConfig,
+    transaction: &Transaction,
+) -> anyhow::Result<()> {
+    let message = &transaction.message;
+    let addresses: Vec<Pubkey> = message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| message.is_maybe_writable(*index, None))
+        .map(|(_, address)| *address)
+        .collect();
+    let pre_accounts = config.accounts.get_multiple_accounts(&addresses)?;
+
+    let result = config
+        .transactions
+        .simulate_transaction(transaction, &addresses)?;
+
+    match &result.err {
+        None => println!("Simulation succeeded"),
+        Some(err) => {
+            println!("Simulation failed: {err}");
+            if let Some(explanation) = explain_transaction_error(message, err, &config.program_id) {
+                println!("{explanation}");
+            }
+        }
+    }
+    if let Some(units_consumed) = result.units_consumed {
+        println!("Compute units consumed: {units_consumed}");
+    }
+
+    if let Some(logs) = &result.logs {
+        println!("Program logs:");
+        for log in logs {
+            println!("  {log}");
+        }
+    }
+
+    if result.err.is_none() {
+        println!("Predicted account changes:");
+        for ((address, pre), post) in addresses.iter().zip(pre_accounts).zip(result.accounts) {
+            print_account_changes(config, address, pre.as_ref(), post.as_ref());
+        }
+    }
+
+    match result.err {
+        None => Ok(()),
+        Some(err) => Err(anyhow!("Simulation failed: {err}")),
+    }
+}
+
+/// Simulates `transaction` and returns how much the balance of `address`
+/// changes: the token amount for token accounts, lamports otherwise
+pub fn simulate_balance_delta(
+    config: &JitoStakePoolCliConfig,
+    transaction: &Transaction,
+    address: &Pubkey,
+) -> anyhow::Result<i128> {
+    let pre = config
+        .accounts
+        .get_multiple_accounts(std::slice::from_ref(address))?
+        .remove(0);
+    let result = config
+        .transactions
+        .simulate_transaction(transaction, std::slice::from_ref(address))?;
+    if let Some(err) = result.err {
+        let mut error = format!("Simulation failed: {err}");
+        if let Some(explanation) =
+            explain_transaction_error(&transaction.message, &err, &config.program_id)
+        {
+            error.push_str(&format!("\n{explanation}"));
+        }
+        return Err(anyhow!(error));
+    }
+    let post = result.accounts.into_iter().next().flatten();
+    Ok(balance(post.as_ref()) as i128 - balance(pre.as_ref()) as i128)
+}
+
+fn balance(account: Option<&Account>) -> u64 {
+    match account {
+        Some(account)
+            if account.owner == spl_token::id()
+                && account.data.len() == spl_token::state::Account::LEN =>
+        {
+            spl_token::state::Account::unpack_unchecked(&account.data)
+                .map_or(0, |token_account| token_account.amount)
+        }
+        Some(account) => account.lamports,
+        None => 0,
+    }
+}
+
+fn print_account_changes(
+    config: &JitoStakePoolCliConfig,
+    address: &Pubkey,
+    pre: Option<&Account>,
+    post: Option<&Account>,
+) {
+    let Some(post) = post else {
+        if pre.is_some() {
+            println!("  {address}: closed");
+        }
+        return;
+    };
+    let pre_lamports = pre.map_or(0, |account| account.lamports);
+
+    if post.owner == config.program_id {
+        match post.data.first().copied() {
+            Some(t) if t == AccountType::StakePool as u8 => {
+                print_stake_pool_changes(address, pre, post, config.layout);
+                return;
+            }
+            Some(t) if t == AccountType::ValidatorList as u8 => {
+                print_validator_list_changes(address, pre, post, config.layout);
+                return;
+            }
+            _ => {}
+        }
+    } else if post.owner == spl_token::id() {
+        if post.data.len() == spl_token::state::Mint::LEN {
+            let supply = |account: &Account| {
+                spl_token::state::Mint::unpack_from_slice(&account.data)
+                    .map_or(0, |mint| mint.supply)
+            };
+            let (before, after) = (pre.map_or(0, supply), supply(post));
+            if before != after {
+                println!(
+                    "  Mint {address}: supply {before} -> {after} ({})",
+                    delta(before, after)
+                );
+            }
+            return;
+        }
+        if post.data.len() == spl_token::state::Account::LEN {
+            let amount = |account: &Account| {
+                spl_token::state::Account::unpack_unchecked(&account.data)
+                    .map_or(0, |token_account| token_account.amount)
+            };
+            let (before, after) = (pre.map_or(0, amount), amount(post));
+            if before != after {
+                println!(
+                    "  Token account {address}: amount {before} -> {after} ({})",
+                    delta(before, after)
+                );
+            }
+            return;
+        }
+    }
+
+    if pre_lamports != post.lamports {
+        println!(
+            "  {address}: lamports {pre_lamports} -> {} ({})",
+            post.lamports,
+            delta(pre_lamports, post.lamports)
+        );
+    }
+}
+
+fn print_stake_pool_changes(
+    address: &Pubkey,
+    pre: Option<&Account>,
+    post: &Account,
+    layout: Option<LayoutVersion>,
+) {
+    let decode = |account: &Account| {
+        decode_stake_pool(&account.data, layout)
+            .ok()
+            .map(|decoded| decoded.stake_pool)
+    };
+    let (Some(before), Some(after)) = (pre.and_then(decode), decode(post)) else {
+        println!("  Stake pool {address}: unable to decode");
+        return;
+    };
+    println!("  Stake pool {address}");
+    println!(
+        "    Total lamports: {} -> {} ({})",
+        before.total_lamports,
+        after.total_lamports,
+        delta(before.total_lamports, after.total_lamports)
+    );
+    println!(
+        "    Pool token supply: {} -> {} ({})",
+        before.pool_token_supply,
+        after.pool_token_supply,
+        delta(before.pool_token_supply, after.pool_token_supply)
+    );
+}
+
+/// Active stake of 0.6.4 lists is compared as recorded
+fn print_validator_list_changes(
+    address: &Pubkey,
+    pre: Option<&Account>,
+    post: &Account,
+    layout: Option<LayoutVersion>,
+) {
+    let decode = |account: &Account| {
+        decode_validator_list(&account.data, layout)
+            .ok()
+            .map(|decoded| decoded.validator_list)
+    };
+    let (Some(before), Some(after)) = (pre.and_then(decode), decode(post)) else {
+        println!("  Validator list {address}: unable to decode");
+        return;
+    };
+    println!("  Validator list {address}");
+    for validator in &after.validators {
+        let active: u64 = validator.active_stake_lamports.into();
+        let transient: u64 = validator.transient_stake_lamports.into();
+        match before.find(&validator.vote_account_address) {
+            None => println!(
+                "    {}: added, active {active}, transient {transient}",
+                validator.vote_account_address
+            ),
+            Some(previous) => {
+                let previous_active: u64 = previous.active_stake_lamports.into();
+                let previous_transient: u64 = previous.transient_stake_lamports.into();
+                if previous_active != active || previous_transient != transient {
+                    println!(
+                        "    {}: active {previous_active} -> {active} ({}), transient {previous_transient} -> {transient} ({})",
+                        validator.vote_account_address,
+                        delta(previous_active, active),
+                        delta(previous_transient, transient),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn delta(before: u64, after: u64) -> String {
+    format!("{:+}", after as i128 - before as i128)
+}
@@ -0,0 +1,153 @@
+//! Transaction sinks that never reach a cluster, so commands can be tested
+//! against accounts held in memory
+
+use std::{collections::HashMap, sync::Mutex};
+
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    signers::Signers,
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::{
+    client::{Simulation, TransactionSink},
+    send::TransactionStatus,
+};
+
+/// Lamports charged per signature by `MemoryTransactions`
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Confirms every transaction it is given without executing it.
+///
+/// Simulations return the post-transaction accounts registered with
+/// `with_post_account`, and every simulation and send fails with the error
+/// given to `with_error`.
+#[derive(Debug, Default)]
+pub struct MemoryTransactions {
+    blockhash: Hash,
+    error: Option<TransactionError>,
+    post_accounts: HashMap<Pubkey, Account>,
+}
+
+impl MemoryTransactions {
+    pub fn with_error(mut self, error: TransactionError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn with_post_account(mut self, address: Pubkey, account: Account) -> Self {
+        self.post_accounts.insert(address, account);
+        self
+    }
+}
+
+impl TransactionSink for MemoryTransactions {
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64> {
+        Ok(u64::from(message.header.num_required_signatures) * LAMPORTS_PER_SIGNATURE)
+    }
+
+    fn simulate_transaction(
+        &self,
+        _transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation> {
+        Ok(Simulation {
+            err: self.error.clone(),
+            accounts: addresses
+                .iter()
+                .map(|address| self.post_accounts.get(address).cloned())
+                .collect(),
+            ..Simulation::default()
+        })
+    }
+
+    fn send_transaction(
+        &self,
+        mut transaction: Transaction,
+        signers: &dyn Signers,
+        _max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus> {
+        transaction.try_sign(signers, self.blockhash)?;
+        let signature = transaction.signatures[0];
+        Ok(match &self.error {
+            None => TransactionStatus::Confirmed(signature),
+            Some(error) => TransactionStatus::Failed {
+                signature,
+                error: error.clone(),
+            },
+        })
+    }
+}
+
+/// A transaction passed to `TransactionSink::send_transaction`, with the
+/// signers it was sent with
+#[derive(Clone, Debug)]
+pub struct SentTransaction {
+    pub transaction: Transaction,
+    pub signers: Vec<Pubkey>,
+}
+
+/// Wraps another sink and keeps every transaction simulated or sent through it
+pub struct RecordingTransactions {
+    inner: Box<dyn TransactionSink + Send + Sync>,
+    simulated: Mutex<Vec<Transaction>>,
+    sent: Mutex<Vec<SentTransaction>>,
+}
+
+impl RecordingTransactions {
+    pub fn new(inner: Box<dyn TransactionSink + Send + Sync>) -> Self {
+        Self {
+            inner,
+            simulated: Mutex::default(),
+            sent: Mutex::default(),
+        }
+    }
+
+    pub fn simulated(&self) -> Vec<Transaction> {
+        self.simulated.lock().unwrap().clone()
+    }
+
+    pub fn sent(&self) -> Vec<SentTransaction> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl TransactionSink for RecordingTransactions {
+    fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        self.inner.get_latest_blockhash()
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> anyhow::Result<u64> {
+        self.inner.get_fee_for_message(message)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> anyhow::Result<Simulation> {
+        self.simulated.lock().unwrap().push(transaction.clone());
+        self.inner.simulate_transaction(transaction, addresses)
+    }
+
+    fn send_transaction(
+        &self,
+        transaction: Transaction,
+        signers: &dyn Signers,
+        max_retries: usize,
+    ) -> anyhow::Result<TransactionStatus> {
+        self.sent.lock().unwrap().push(SentTransaction {
+            transaction: transaction.clone(),
+            signers: signers.try_pubkeys()?,
+        });
+        self.inner
+            .send_transaction(transaction, signers, max_retries)
+    }
+}
@@ -0,0 +1,206 @@
+//! Account snapshots on disk, so a command can be replayed without a cluster
+//!
+//! Each account is stored as `<dir>/<pubkey>.json` in the format written by
+//! `solana account --output json`, with base64 account data.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::{UiAccount, UiAccountEncoding, encode_ui_account};
+use solana_rpc_client_api::filter::RpcFilterType;
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+
+use crate::client::AccountSource;
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    pubkey: String,
+    account: UiAccount,
+}
+
+/// Accounts loaded from a snapshot directory; anything missing reads as
+/// nonexistent
+#[derive(Debug, Default)]
+pub struct SnapshotAccounts {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl SnapshotAccounts {
+    pub fn new(accounts: impl IntoIterator<Item = (Pubkey, Account)>) -> Self {
+        Self {
+            accounts: accounts.into_iter().collect(),
+        }
+    }
+
+    /// Loads every `*.json` file in `dir`
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut accounts = HashMap::new();
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("Cannot read snapshot {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let (address, account) = read_account(&path)
+                .with_context(|| format!("Invalid snapshot file {}", path.display()))?;
+            accounts.insert(address, account);
+        }
+        Ok(Self { accounts })
+    }
+
+    pub fn insert(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+}
+
+impl AccountSource for SnapshotAccounts {
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        Ok(addresses
+            .iter()
+            .map(|address| self.accounts.get(address).cloned())
+            .collect())
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| &account.owner == program_id)
+            .filter(|(_, account)| filters.iter().all(|filter| filter_allows(filter, account)))
+            .map(|(address, account)| (*address, account.clone()))
+            .collect();
+        accounts.sort_by_key(|(address, _)| *address);
+        Ok(accounts)
+    }
+}
+
+/// Wraps another source and writes every account it returns to a snapshot
+/// directory
+pub struct RecordingAccounts {
+    inner: Box<dyn AccountSource>,
+    dir: PathBuf,
+}
+
+impl RecordingAccounts {
+    pub fn new(inner: Box<dyn AccountSource>, dir: PathBuf) -> anyhow::Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Cannot create snapshot {}", dir.display()))?;
+        Ok(Self { inner, dir })
+    }
+
+    fn record(&self, address: &Pubkey, account: &Account) -> anyhow::Result<()> {
+        let path = self.dir.join(format!("{address}.json"));
+        write_account(&path, address, account)
+            .with_context(|| format!("Cannot write snapshot file {}", path.display()))
+    }
+}
+
+impl AccountSource for RecordingAccounts {
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        let accounts = self.inner.get_multiple_accounts(addresses)?;
+        for (address, account) in addresses.iter().zip(&accounts) {
+            if let Some(account) = account {
+                self.record(address, account)?;
+            }
+        }
+        Ok(accounts)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        let accounts = self.inner.get_program_accounts(program_id, filters)?;
+        for (address, account) in &accounts {
+            self.record(address, account)?;
+        }
+        Ok(accounts)
+    }
+}
+
+/// Applies a `getProgramAccounts` filter the way the RPC node does
+fn filter_allows(filter: &RpcFilterType, account: &Account) -> bool {
+    match filter {
+        RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+        RpcFilterType::Memcmp(compare) => compare.bytes_match(&account.data),
+        RpcFilterType::TokenAccountState => {
+            spl_token::state::Account::unpack(&account.data).is_ok()
+        }
+    }
+}
+
+fn read_account(path: &Path) -> anyhow::Result<(Pubkey, Account)> {
+    let file: SnapshotFile = serde_json::from_slice(&fs::read(path)?)?;
+    let address = file.pubkey.parse()?;
+    let account = file
+        .account
+        .decode()
+        .ok_or_else(|| anyhow!("Cannot decode account data of {address}"))?;
+    Ok((address, account))
+}
+
+fn write_account(path: &Path, address: &Pubkey, account: &Account) -> anyhow::Result<()> {
+    let file = SnapshotFile {
+        pubkey: address.to_string(),
+        account: encode_ui_account(address, account, UiAccountEncoding::Base64, None, None),
+    };
+    fs::write(path, serde_json::to_vec_pretty(&file)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_rpc_client_api::filter::Memcmp;
+
+    use super::*;
+
+    #[test]
+    fn recorded_accounts_load_back() {
+        let dir = std::env::temp_dir().join(format!("snapshot-{}", Pubkey::new_unique()));
+        let owner = Pubkey::new_unique();
+        let (first, second, missing) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let account = |data: Vec<u8>| Account {
+            lamports: 42,
+            data,
+            owner,
+            ..Account::default()
+        };
+        let source =
+            SnapshotAccounts::new([(first, account(vec![1, 2])), (second, account(vec![3]))]);
+        let recording = RecordingAccounts::new(Box::new(source), dir.clone()).unwrap();
+
+        let fetched = recording.get_multiple_accounts(&[first, missing]).unwrap();
+        assert_eq!(fetched, [Some(account(vec![1, 2])), None]);
+        let filtered = recording
+            .get_program_accounts(
+                &owner,
+                &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![3]))],
+            )
+            .unwrap();
+        assert_eq!(filtered, [(second, account(vec![3]))]);
+
+        let loaded = SnapshotAccounts::load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            loaded
+                .get_multiple_accounts(&[first, second, missing])
+                .unwrap(),
+            [Some(account(vec![1, 2])), Some(account(vec![3])), None]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use jito_stake_pool_sdk::layout::LayoutVersion;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    epoch_schedule::EpochSchedule,
+    instruction::Instruction,
+    message::Message,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    stake::{
+        stake_flags::StakeFlags,
+        state::{Authorized, Delegation, Meta, Stake, StakeStateV2},
+    },
+    sysvar::Sysvar,
+};
+use spl_stake_pool::state::{AccountType, StakePool, ValidatorList, ValidatorStakeInfo};
+
+use crate::{
+    config::{JitoStakePoolCliConfig, OutputFormat},
+    profile::Profiles,
+    sink::{MemoryTransactions, RecordingTransactions, SentTransaction},
+    snapshot::SnapshotAccounts,
+};
+
+/// Epoch of the clock sysvar in `TestPool::accounts`
+pub(crate) const EPOCH: u64 = 500;
+
+/// Capacity of the validator list in `TestPool`
+pub(crate) const MAX_VALIDATORS: u32 = 10;
+
+/// Config with fresh keypairs, no accounts, and a recording in-memory sink
+pub(crate) fn test_config(
+    transactions: MemoryTransactions,
+) -> (JitoStakePoolCliConfig, Arc<RecordingTransactions>) {
+    let recording = Arc::new(RecordingTransactions::new(Box::new(transactions)));
+    let config = JitoStakePoolCliConfig {
+        rpc_client: Arc::new(RpcClient::new_mock("fails")),
+        accounts: Box::new(SnapshotAccounts::default()),
+        replay: false,
+        transactions: Box::new(recording.clone()),
+        program_id: spl_stake_pool::id(),
+        layout: Some(LayoutVersion::Current),
+        profiles: Profiles::default(),
+        verbose: false,
+        output_format: OutputFormat::Display,
+        manager: Box::new(Keypair::new()),
+        staker: Box::new(Keypair::new()),
+        funding_authority: None,
+        token_owner: Box::new(Keypair::new()),
+        fee_payer: Box::new(Keypair::new()),
+        dry_run: false,
+        no_update: false,
+        max_retries: 0,
+    };
+    (config, recording)
+}
+
+/// A stake pool managed by the keypairs of a test config
+pub(crate) struct TestPool {
+    pub address: Pubkey,
+    pub stake_pool: StakePool,
+    pub validator_list: ValidatorList,
+    pub extra_accounts: Vec<(Pubkey, Account)>,
+}
+
+impl TestPool {
+    pub(crate) fn new(config: &JitoStakePoolCliConfig) -> Self {
+        let stake_pool = StakePool {
+            account_type: AccountType::StakePool,
+            manager: config.manager.pubkey(),
+            staker: config.staker.pubkey(),
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            manager_fee_account: Pubkey::new_unique(),
+            token_program_id: spl_token::id(),
+            total_lamports: 1_000_000_000_000,
+            pool_token_supply: 1_000_000_000_000,
+            ..StakePool::default()
+        };
+        Self {
+            address: Pubkey::new_unique(),
+            stake_pool,
+            validator_list: ValidatorList {
+                validators: vec![],
+                ..ValidatorList::new(MAX_VALIDATORS)
+            },
+            extra_accounts: vec![],
+        }
+    }
+
+    /// Adds a validator with the given transient seed, returning its vote account
+    pub(crate) fn add_validator(&mut self, transient_seed_suffix: u64) -> Pubkey {
+        let vote_account_address = Pubkey::new_unique();
+        self.validator_list.validators.push(ValidatorStakeInfo {
+            vote_account_address,
+            transient_seed_suffix: transient_seed_suffix.into(),
+            active_stake_lamports: 10_000_000_000.into(),
+            ..ValidatorStakeInfo::default()
+        });
+        vote_account_address
+    }
+
+    pub(crate) fn add_account(&mut self, address: Pubkey, account: Account) {
+        self.extra_accounts.push((address, account));
+    }
+
+    /// Adds a pool token account holding `amount`
+    pub(crate) fn add_token_account(&mut self, address: Pubkey, owner: &Pubkey, amount: u64) {
+        let account = token_account(&self.stake_pool.pool_mint, owner, amount);
+        self.add_account(address, account);
+    }
+
+    /// The pool, its validator list and mint, and the clock, rent and epoch
+    /// schedule sysvars
+    pub(crate) fn accounts(&self) -> SnapshotAccounts {
+        let mint = spl_token::state::Mint {
+            supply: self.stake_pool.pool_token_supply,
+            decimals: 9,
+            is_initialized: true,
+            ..spl_token::state::Mint::default()
+        };
+        let mut mint_data = vec![0; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut mint_data);
+
+        // The list account is allocated for its capacity, like on chain
+        let mut validator_list_data =
+            vec![0; borsh::object_length(&ValidatorList::new(MAX_VALIDATORS)).unwrap()];
+        borsh::to_writer(&mut validator_list_data[..], &self.validator_list).unwrap();
+
+        let mut accounts = SnapshotAccounts::new(self.extra_accounts.clone());
+        accounts.insert(
+            self.address,
+            owned_account(
+                &spl_stake_pool::id(),
+                borsh::to_vec(&self.stake_pool).unwrap(),
+            ),
+        );
+        accounts.insert(
+            self.stake_pool.validator_list,
+            owned_account(&spl_stake_pool::id(), validator_list_data),
+        );
+        accounts.insert(
+            self.stake_pool.pool_mint,
+            owned_account(&spl_token::id(), mint_data),
+        );
+        accounts.insert(
+            solana_sdk::sysvar::clock::id(),
+            sysvar_account(&Clock {
+                epoch: EPOCH,
+                ..Clock::default()
+            }),
+        );
+        accounts.insert(
+            solana_sdk::sysvar::rent::id(),
+            sysvar_account(&Rent::default()),
+        );
+        accounts.insert(
+            solana_sdk::sysvar::epoch_schedule::id(),
+            sysvar_account(&EpochSchedule::default()),
+        );
+        accounts
+    }
+}
+
+pub(crate) fn owned_account(owner: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        ..Account::default()
+    }
+}
+
+pub(crate) fn system_account(lamports: u64) -> Account {
+    Account {
+        lamports,
+        owner: solana_system_interface::program::id(),
+        ..Account::default()
+    }
+}
+
+pub(crate) fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let token_account = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    token_account.pack_into_slice(&mut data);
+    owned_account(&spl_token::id(), data)
+}
+
+/// Stake account of `lamports` with `authority` as staker and withdrawer,
+/// delegated to `voter` when given and initialized otherwise
+pub(crate) fn stake_account(authority: &Pubkey, lamports: u64, voter: Option<&Pubkey>) -> Account {
+    let meta = Meta {
+        rent_exempt_reserve: Rent::default().minimum_balance(StakeStateV2::size_of()),
+        authorized: Authorized::auto(authority),
+        ..Meta::default()
+    };
+    let stake_state = match voter {
+        Some(voter) => StakeStateV2::Stake(
+            meta,
+            Stake {
+                delegation: Delegation::new(voter, lamports - meta.rent_exempt_reserve, 0),
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        ),
+        None => StakeStateV2::Initialized(meta),
+    };
+    let mut data = vec![0; StakeStateV2::size_of()];
+    bincode::serialize_into(&mut data[..], &stake_state).unwrap();
+    Account {
+        lamports,
+        data,
+        owner: solana_sdk::stake::program::id(),
+        ..Account::default()
+    }
+}
+
+fn sysvar_account<T: Sysvar>(sysvar: &T) -> Account {
+    owned_account(
+        &solana_sdk::sysvar::id(),
+        bincode::serialize(sysvar).unwrap(),
+    )
+}
+
+/// Checks that `sent` carries exactly `instructions`, paid by `fee_payer`
+pub(crate) fn assert_instructions(
+    sent: &SentTransaction,
+    fee_payer: &Pubkey,
+    instructions: &[Instruction],
+) {
+    let expected = Message::new_with_blockhash(
+        instructions,
+        Some(fee_payer),
+        &sent.transaction.message.recent_blockhash,
+    );
+    assert_eq!(sent.transaction.message, expected);
+}
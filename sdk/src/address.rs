@@ -1,4 +1,6 @@
-use solana_sdk::pubkey::Pubkey;
+use std::num::NonZeroU32;
+
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, stake::state::Meta};
 
 /// Seed for deposit authority seed
 const AUTHORITY_DEPOSIT: &[u8] = b"deposit";
@@ -9,9 +11,9 @@ const AUTHORITY_WITHDRAW: &[u8] = b"withdraw";
 /// Seed for transient stake account
 const TRANSIENT_STAKE_SEED_PREFIX: &[u8] = b"transient";
 
-// Minimum amount of staked SOL required in a validator stake account to allow
-// for merges without a mismatch on credits observed
-// pub const MINIMUM_ACTIVE_STAKE: u64 = LAMPORTS_PER_SOL / 1_000;
+/// Minimum amount of staked SOL required in a validator stake account to allow
+/// for merges without a mismatch on credits observed
+pub const MINIMUM_ACTIVE_STAKE: u64 = LAMPORTS_PER_SOL / 1_000;
 
 /// Maximum amount of validator stake accounts to update per
 /// `UpdateValidatorListBalance` instruction, based on compute limits
@@ -34,20 +36,20 @@ pub const MAX_VALIDATORS_TO_UPDATE: usize = 5;
 /// transaction account limits.
 pub const MAX_TRANSIENT_STAKE_ACCOUNTS: usize = 10;
 
-// /// Get the stake amount under consideration when calculating pool token
-// /// conversions
-// #[inline]
-// pub fn minimum_stake_lamports(meta: &Meta) -> u64 {
-//     meta.rent_exempt_reserve
-//         .saturating_add(MINIMUM_ACTIVE_STAKE)
-// }
-
-// /// Get the stake amount under consideration when calculating pool token
-// /// conversions
-// #[inline]
-// pub fn minimum_reserve_lamports(meta: &Meta) -> u64 {
-//     meta.rent_exempt_reserve.saturating_add(1)
-// }
+/// Get the stake amount under consideration when calculating pool token
+/// conversions
+#[inline]
+pub fn minimum_stake_lamports(meta: &Meta) -> u64 {
+    meta.rent_exempt_reserve
+        .saturating_add(MINIMUM_ACTIVE_STAKE)
+}
+
+/// Get the stake amount under consideration when calculating pool token
+/// conversions
+#[inline]
+pub fn minimum_reserve_lamports(meta: &Meta) -> u64 {
+    meta.rent_exempt_reserve.saturating_add(1)
+}
 
 /// Generates the deposit authority program address for the stake pool
 pub fn find_deposit_authority_program_address(
@@ -71,20 +73,26 @@ pub fn find_withdraw_authority_program_address(
     )
 }
 
-// /// Generates the stake program address for a validator's vote account
-// pub fn find_stake_program_address(
-//     program_id: &Pubkey,
-//     vote_account_address: &Pubkey,
-//     stake_pool_address: &Pubkey,
-// ) -> (Pubkey, u8) {
-//     Pubkey::find_program_address(
-//         &[
-//             &vote_account_address.to_bytes(),
-//             &stake_pool_address.to_bytes(),
-//         ],
-//         program_id,
-//     )
-// }
+/// Generates the stake program address for a validator's vote account.
+///
+/// The vendored program never sets a validator seed, `None` derives the same
+/// address as its `find_stake_program_address`.
+pub fn find_stake_program_address(
+    program_id: &Pubkey,
+    vote_account_address: &Pubkey,
+    stake_pool_address: &Pubkey,
+    seed: Option<NonZeroU32>,
+) -> (Pubkey, u8) {
+    let seed = seed.map(|seed| seed.get().to_le_bytes());
+    Pubkey::find_program_address(
+        &[
+            &vote_account_address.to_bytes(),
+            &stake_pool_address.to_bytes(),
+            seed.as_ref().map_or(&[][..], |seed| &seed[..]),
+        ],
+        program_id,
+    )
+}
 
 /// Generates the stake program address for a validator's vote account
 pub fn find_transient_stake_program_address(
@@ -103,3 +111,119 @@ pub fn find_transient_stake_program_address(
         program_id,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::legacy_pubkey;
+
+    fn assert_same_address(
+        (address, bump): (Pubkey, u8),
+        (legacy_address, legacy_bump): (spl_stake_pool_legacy::solana_program::pubkey::Pubkey, u8),
+    ) {
+        assert_eq!(address.to_bytes(), legacy_address.to_bytes());
+        assert_eq!(bump, legacy_bump);
+    }
+
+    #[test]
+    fn addresses_match_program() {
+        let program_id = Pubkey::new_unique();
+        let stake_pool = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let (legacy_program_id, legacy_stake_pool, legacy_vote_account) = (
+            legacy_pubkey(&program_id),
+            legacy_pubkey(&stake_pool),
+            legacy_pubkey(&vote_account),
+        );
+
+        assert_same_address(
+            find_deposit_authority_program_address(&program_id, &stake_pool),
+            spl_stake_pool_legacy::find_deposit_authority_program_address(
+                &legacy_program_id,
+                &legacy_stake_pool,
+            ),
+        );
+        assert_same_address(
+            find_withdraw_authority_program_address(&program_id, &stake_pool),
+            spl_stake_pool_legacy::find_withdraw_authority_program_address(
+                &legacy_program_id,
+                &legacy_stake_pool,
+            ),
+        );
+        assert_same_address(
+            find_stake_program_address(&program_id, &vote_account, &stake_pool, None),
+            spl_stake_pool_legacy::find_stake_program_address(
+                &legacy_program_id,
+                &legacy_vote_account,
+                &legacy_stake_pool,
+            ),
+        );
+        for seed in [0, 1, u64::MAX] {
+            assert_same_address(
+                find_transient_stake_program_address(&program_id, &vote_account, &stake_pool, seed),
+                spl_stake_pool_legacy::find_transient_stake_program_address(
+                    &legacy_program_id,
+                    &legacy_vote_account,
+                    &legacy_stake_pool,
+                    seed,
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn validator_seed_matches_current_program() {
+        let program_id = Pubkey::new_unique();
+        let stake_pool = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        for seed in [None, NonZeroU32::new(1), NonZeroU32::new(u32::MAX)] {
+            assert_eq!(
+                find_stake_program_address(&program_id, &vote_account, &stake_pool, seed),
+                spl_stake_pool::find_stake_program_address(
+                    &program_id,
+                    &vote_account,
+                    &stake_pool,
+                    seed
+                ),
+            );
+        }
+        assert_ne!(
+            find_stake_program_address(&program_id, &vote_account, &stake_pool, None),
+            find_stake_program_address(&program_id, &vote_account, &stake_pool, NonZeroU32::new(1)),
+        );
+    }
+
+    #[test]
+    fn minimum_balances_match_program() {
+        for rent_exempt_reserve in [0, 2_282_880, u64::MAX] {
+            let meta = Meta {
+                rent_exempt_reserve,
+                ..Meta::default()
+            };
+            let legacy_meta = spl_stake_pool_legacy::solana_program::stake::state::Meta {
+                rent_exempt_reserve,
+                ..Default::default()
+            };
+            assert_eq!(
+                minimum_stake_lamports(&meta),
+                spl_stake_pool_legacy::minimum_stake_lamports(&legacy_meta)
+            );
+            assert_eq!(
+                minimum_reserve_lamports(&meta),
+                spl_stake_pool_legacy::minimum_reserve_lamports(&legacy_meta)
+            );
+        }
+        assert_eq!(
+            MINIMUM_ACTIVE_STAKE,
+            spl_stake_pool_legacy::MINIMUM_ACTIVE_STAKE
+        );
+        assert_eq!(
+            MAX_VALIDATORS_TO_UPDATE,
+            spl_stake_pool_legacy::MAX_VALIDATORS_TO_UPDATE
+        );
+        assert_eq!(
+            MAX_TRANSIENT_STAKE_ACCOUNTS,
+            spl_stake_pool_legacy::MAX_TRANSIENT_STAKE_ACCOUNTS
+        );
+    }
+}
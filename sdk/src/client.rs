@@ -0,0 +1,404 @@
+//! Async account fetching on the nonblocking `RpcClient`.
+//!
+//! The blocking CLI helpers drive these through the runtime of the blocking
+//! `RpcClient`, so both share one implementation.
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    num::NonZeroU32,
+};
+
+use futures::future::try_join_all;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{client_error::Error as RpcError, request::MAX_MULTIPLE_ACCOUNTS};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+use spl_stake_pool::state::{StakePool, ValidatorList, ValidatorStakeInfo};
+
+use crate::{
+    address::{find_stake_program_address, find_transient_stake_program_address},
+    layout::{
+        DecodedStakePool, DecodedValidatorList, LayoutError, LayoutVersion, decode_stake_pool,
+        decode_validator_list,
+    },
+};
+
+/// Errors returned while fetching stake pool accounts
+#[derive(Debug)]
+pub enum ClientError {
+    /// The RPC request failed
+    Rpc(Box<RpcError>),
+    /// A stake pool or validator list does not decode under either layout
+    Layout { address: Pubkey, error: LayoutError },
+    /// An account does not hold the expected data
+    InvalidAccount { address: Pubkey, error: String },
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Rpc(err) => write!(f, "{err}"),
+            Self::Layout { address, error } => write!(f, "Invalid account {address}: {error}"),
+            Self::InvalidAccount { address, error } => {
+                write!(f, "Invalid account {address}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Rpc(err) => Some(err.as_ref()),
+            Self::Layout { error, .. } => Some(error),
+            Self::InvalidAccount { .. } => None,
+        }
+    }
+}
+
+impl From<RpcError> for ClientError {
+    fn from(err: RpcError) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+/// Validator list entry with its stake accounts
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidatorSnapshot {
+    pub info: ValidatorStakeInfo,
+    pub stake_address: Pubkey,
+    pub stake_account: Option<Account>,
+    pub transient_stake_address: Pubkey,
+    pub transient_stake_account: Option<Account>,
+}
+
+/// Stake pool with its validator list, reserve and every validator and
+/// transient stake account, in validator list order
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolSnapshot {
+    pub stake_pool_address: Pubkey,
+    pub layout: LayoutVersion,
+    pub stake_pool: StakePool,
+    pub validator_list: ValidatorList,
+    pub reserve_stake: Option<Account>,
+    pub validators: Vec<ValidatorSnapshot>,
+}
+
+impl PoolSnapshot {
+    /// Lamports held by the reserve stake account
+    pub fn reserve_lamports(&self) -> u64 {
+        self.reserve_stake
+            .as_ref()
+            .map_or(0, |account| account.lamports)
+    }
+
+    /// Looks up a validator by vote account
+    pub fn validator(&self, vote_account_address: &Pubkey) -> Option<&ValidatorSnapshot> {
+        self.validators
+            .iter()
+            .find(|validator| validator.info.vote_account_address == *vote_account_address)
+    }
+}
+
+/// Fetches and decodes a stake pool, as `layout` when the caller knows the
+/// program version that owns it
+pub async fn get_stake_pool(
+    rpc_client: &RpcClient,
+    stake_pool_address: &Pubkey,
+    layout: Option<LayoutVersion>,
+) -> Result<DecodedStakePool, ClientError> {
+    let account_data = rpc_client.get_account_data(stake_pool_address).await?;
+    decode_stake_pool(&account_data, layout).map_err(|error| ClientError::Layout {
+        address: *stake_pool_address,
+        error,
+    })
+}
+
+/// Fetches and decodes a validator list, as `layout` when the caller knows
+/// the program version that owns it. 0.6.4 active stake is as the program
+/// recorded it, [`get_pool_snapshot`] also normalizes it.
+pub async fn get_validator_list(
+    rpc_client: &RpcClient,
+    validator_list_address: &Pubkey,
+    layout: Option<LayoutVersion>,
+) -> Result<DecodedValidatorList, ClientError> {
+    let account_data = rpc_client.get_account_data(validator_list_address).await?;
+    decode_validator_list(&account_data, layout).map_err(|error| ClientError::Layout {
+        address: *validator_list_address,
+        error,
+    })
+}
+
+pub async fn get_token_account(
+    rpc_client: &RpcClient,
+    token_account_address: &Pubkey,
+    expected_token_mint: &Pubkey,
+) -> Result<spl_token::state::Account, ClientError> {
+    let account_data = rpc_client.get_account_data(token_account_address).await?;
+    decode_token_account(token_account_address, &account_data, expected_token_mint)
+}
+
+/// Decodes a token account and checks its mint
+pub fn decode_token_account(
+    token_account_address: &Pubkey,
+    account_data: &[u8],
+    expected_token_mint: &Pubkey,
+) -> Result<spl_token::state::Account, ClientError> {
+    let token_account =
+        spl_token::state::Account::unpack_unchecked(account_data).map_err(|err| {
+            ClientError::InvalidAccount {
+                address: *token_account_address,
+                error: err.to_string(),
+            }
+        })?;
+    if token_account.mint != *expected_token_mint {
+        return Err(ClientError::InvalidAccount {
+            address: *token_account_address,
+            error: format!("invalid token mint, expected mint is {expected_token_mint}"),
+        });
+    }
+    Ok(token_account)
+}
+
+/// Fetches any number of accounts, `MAX_MULTIPLE_ACCOUNTS` per request, with
+/// the requests running concurrently
+pub async fn get_multiple_accounts_chunked(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<Option<Account>>, ClientError> {
+    let chunks = try_join_all(
+        addresses
+            .chunks(MAX_MULTIPLE_ACCOUNTS)
+            .map(|chunk| rpc_client.get_multiple_accounts(chunk)),
+    )
+    .await?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+/// Fetches the stake pool, then its validator list, then the reserve and all
+/// validator and transient stake accounts in one batch
+pub async fn get_pool_snapshot(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    layout: Option<LayoutVersion>,
+) -> Result<PoolSnapshot, ClientError> {
+    let pool = get_stake_pool(rpc_client, stake_pool_address, layout).await?;
+    let list = get_validator_list(rpc_client, &pool.stake_pool.validator_list, layout).await?;
+    let addresses = pool_snapshot_addresses(
+        program_id,
+        stake_pool_address,
+        &pool.stake_pool,
+        &list.validator_list,
+    );
+    let accounts = get_multiple_accounts_chunked(rpc_client, &addresses).await?;
+    build_pool_snapshot(program_id, stake_pool_address, pool, list, accounts)
+}
+
+/// Addresses of the reserve, then of the validator and transient stake
+/// accounts of every validator, as expected by [`build_pool_snapshot`]
+pub fn pool_snapshot_addresses(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+) -> Vec<Pubkey> {
+    let mut addresses = Vec::with_capacity(1 + 2 * validator_list.validators.len());
+    addresses.push(stake_pool.reserve_stake);
+    addresses.extend(validator_list.validators.iter().flat_map(|info| {
+        let (stake, transient) = validator_stake_addresses(program_id, stake_pool_address, info);
+        [stake, transient]
+    }));
+    addresses
+}
+
+/// Assembles a snapshot from the decoded pool and list, and the accounts
+/// fetched at [`pool_snapshot_addresses`]
+pub fn build_pool_snapshot(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    pool: DecodedStakePool,
+    mut list: DecodedValidatorList,
+    accounts: Vec<Option<Account>>,
+) -> Result<PoolSnapshot, ClientError> {
+    let layout = pool
+        .layout
+        .merge(list.layout)
+        .map_err(|error| ClientError::Layout {
+            address: *stake_pool_address,
+            error,
+        })?;
+    let mut accounts = accounts.into_iter();
+
+    let reserve_stake = accounts.next().flatten();
+    let stake_accounts: Vec<(Option<Account>, Option<Account>)> = list
+        .validator_list
+        .validators
+        .iter()
+        .map(|_| (accounts.next().flatten(), accounts.next().flatten()))
+        .collect();
+    let validator_stake_accounts: Vec<Option<Account>> = stake_accounts
+        .iter()
+        .map(|(stake_account, _)| stake_account.clone())
+        .collect();
+    list.normalize_active_stake(&validator_stake_accounts);
+    let validators = list
+        .validator_list
+        .validators
+        .iter()
+        .zip(stake_accounts)
+        .map(|(info, (stake_account, transient_stake_account))| {
+            let (stake_address, transient_stake_address) =
+                validator_stake_addresses(program_id, stake_pool_address, info);
+            ValidatorSnapshot {
+                info: *info,
+                stake_address,
+                stake_account,
+                transient_stake_address,
+                transient_stake_account,
+            }
+        })
+        .collect();
+
+    Ok(PoolSnapshot {
+        stake_pool_address: *stake_pool_address,
+        layout,
+        stake_pool: pool.stake_pool,
+        validator_list: list.validator_list,
+        reserve_stake,
+        validators,
+    })
+}
+
+/// Validator and transient stake account addresses of a validator list entry
+pub fn validator_stake_addresses(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    info: &ValidatorStakeInfo,
+) -> (Pubkey, Pubkey) {
+    let (stake_address, _) = find_stake_program_address(
+        program_id,
+        &info.vote_account_address,
+        stake_pool_address,
+        NonZeroU32::new(info.validator_seed_suffix.into()),
+    );
+    let (transient_stake_address, _) = find_transient_stake_program_address(
+        program_id,
+        &info.vote_account_address,
+        stake_pool_address,
+        info.transient_seed_suffix.into(),
+    );
+    (stake_address, transient_stake_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::{Value, json};
+    use solana_account_decoder::{UiAccountEncoding, encode_ui_account};
+    use solana_rpc_client::mock_sender::MocksMap;
+    use solana_rpc_client_api::request::RpcRequest;
+    use spl_stake_pool::state::AccountType;
+
+    use super::*;
+
+    fn account(data: Vec<u8>, lamports: u64) -> Account {
+        Account {
+            lamports,
+            data,
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        }
+    }
+
+    fn encode(address: &Pubkey, account: Option<&Account>) -> Value {
+        account.map_or(Value::Null, |account| {
+            json!(encode_ui_account(
+                address,
+                account,
+                UiAccountEncoding::Base64,
+                None,
+                None
+            ))
+        })
+    }
+
+    fn response(value: Value) -> Value {
+        json!({ "context": { "slot": 1 }, "value": value })
+    }
+
+    #[test]
+    fn snapshot_hydrates_every_account() {
+        let program_id = spl_stake_pool::id();
+        let stake_pool_address = Pubkey::new_unique();
+        let stake_pool = StakePool {
+            account_type: AccountType::StakePool,
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = (0..2)
+            .map(|index| ValidatorStakeInfo {
+                transient_seed_suffix: index.into(),
+                vote_account_address: Pubkey::new_unique(),
+                ..ValidatorStakeInfo::default()
+            })
+            .collect();
+        let pool_account = account(borsh::to_vec(&stake_pool).unwrap(), 1);
+        let list_account = account(borsh::to_vec(&validator_list).unwrap(), 1);
+
+        let addresses: Vec<(Pubkey, Pubkey)> = validator_list
+            .validators
+            .iter()
+            .map(|info| validator_stake_addresses(&program_id, &stake_pool_address, info))
+            .collect();
+        let reserve = account(vec![], 100);
+        let first_stake = account(vec![], 10);
+        let second_transient = account(vec![], 20);
+        let multiple_accounts = json!([
+            encode(&stake_pool.reserve_stake, Some(&reserve)),
+            encode(&addresses[0].0, Some(&first_stake)),
+            encode(&addresses[0].1, None),
+            encode(&addresses[1].0, None),
+            encode(&addresses[1].1, Some(&second_transient)),
+        ]);
+
+        let mut mocks = MocksMap::default();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            response(encode(&stake_pool_address, Some(&pool_account))),
+        );
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            response(encode(&stake_pool.validator_list, Some(&list_account))),
+        );
+        mocks.insert(RpcRequest::GetMultipleAccounts, response(multiple_accounts));
+        let rpc_client = RpcClient::new_mock_with_mocks_map("succeeds", mocks);
+
+        let snapshot = block_on(get_pool_snapshot(
+            &rpc_client,
+            &program_id,
+            &stake_pool_address,
+            Some(LayoutVersion::Current),
+        ))
+        .unwrap();
+        assert_eq!(snapshot.layout, LayoutVersion::Current);
+        assert_eq!(snapshot.stake_pool, stake_pool);
+        assert_eq!(snapshot.validator_list, validator_list);
+        assert_eq!(snapshot.reserve_lamports(), 100);
+        assert_eq!(snapshot.validators.len(), 2);
+        assert_eq!(snapshot.validators[0].stake_address, addresses[0].0);
+        assert_eq!(snapshot.validators[0].stake_account, Some(first_stake));
+        assert_eq!(snapshot.validators[0].transient_stake_account, None);
+        assert_eq!(snapshot.validators[1].stake_account, None);
+        assert_eq!(
+            snapshot.validators[1].transient_stake_account,
+            Some(second_transient)
+        );
+        let vote = validator_list.validators[1].vote_account_address;
+        assert_eq!(
+            snapshot.validator(&vote).unwrap().transient_stake_address,
+            addresses[1].1
+        );
+    }
+}
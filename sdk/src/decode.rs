@@ -0,0 +1,1350 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use borsh_legacy::BorshDeserialize;
+use solana_sdk::{
+    instruction::{CompiledInstruction, Instruction},
+    pubkey::Pubkey,
+};
+use spl_stake_pool::{
+    instruction::{FundingType, PreferredValidatorType},
+    state::{Fee, FeeType},
+};
+use spl_stake_pool_legacy::instruction::StakePoolInstruction as LegacyInstruction;
+
+/// Errors returned when decoding a stake pool instruction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The instruction is addressed to another program
+    WrongProgram(Pubkey),
+    /// The instruction data is not a `StakePoolInstruction`
+    InvalidData(String),
+    /// The instruction has fewer accounts than its variant requires
+    MissingAccounts { expected: usize, found: usize },
+    /// A compiled instruction refers to an account outside of the keys
+    InvalidAccountIndex(u8),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::WrongProgram(program_id) => {
+                write!(f, "Instruction is addressed to program {program_id}")
+            }
+            Self::InvalidData(err) => write!(f, "Invalid instruction data: {err}"),
+            Self::MissingAccounts { expected, found } => {
+                write!(f, "Expected at least {expected} accounts, found {found}")
+            }
+            Self::InvalidAccountIndex(index) => write!(f, "Account index {index} out of range"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Stake pool instruction with its arguments and named accounts.
+///
+/// Mirrors `StakePoolInstruction` of `program/src/instruction.rs`, the fields
+/// of every variant list the accounts in the documented order, followed by the
+/// instruction arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedInstruction {
+    Initialize {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        staker: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        reserve_stake: Pubkey,
+        pool_mint: Pubkey,
+        manager_fee_account: Pubkey,
+        token_program: Pubkey,
+        deposit_authority: Option<Pubkey>,
+        fee: Fee,
+        withdrawal_fee: Fee,
+        deposit_fee: Fee,
+        referral_fee: u8,
+        max_validators: u32,
+    },
+    AddValidatorToPool {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        funder: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        validator_stake: Pubkey,
+        vote_account: Pubkey,
+        rent_sysvar: Pubkey,
+        clock_sysvar: Pubkey,
+        stake_history_sysvar: Pubkey,
+        stake_config: Pubkey,
+        system_program: Pubkey,
+        stake_program: Pubkey,
+    },
+    RemoveValidatorFromPool {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        withdraw_authority: Pubkey,
+        new_stake_authority: Pubkey,
+        validator_list: Pubkey,
+        validator_stake: Pubkey,
+        transient_stake: Pubkey,
+        destination_stake: Pubkey,
+        clock_sysvar: Pubkey,
+        stake_program: Pubkey,
+    },
+    DecreaseValidatorStake {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        validator_stake: Pubkey,
+        transient_stake: Pubkey,
+        clock_sysvar: Pubkey,
+        rent_sysvar: Pubkey,
+        system_program: Pubkey,
+        stake_program: Pubkey,
+        lamports: u64,
+        transient_stake_seed: u64,
+    },
+    IncreaseValidatorStake {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        reserve_stake: Pubkey,
+        transient_stake: Pubkey,
+        vote_account: Pubkey,
+        clock_sysvar: Pubkey,
+        rent_sysvar: Pubkey,
+        stake_history_sysvar: Pubkey,
+        stake_config: Pubkey,
+        system_program: Pubkey,
+        stake_program: Pubkey,
+        lamports: u64,
+        transient_stake_seed: u64,
+    },
+    SetPreferredValidator {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        validator_list: Pubkey,
+        validator_type: PreferredValidatorType,
+        validator_vote_address: Option<Pubkey>,
+    },
+    UpdateValidatorListBalance {
+        stake_pool: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        reserve_stake: Pubkey,
+        clock_sysvar: Pubkey,
+        stake_history_sysvar: Pubkey,
+        stake_program: Pubkey,
+        /// Pairs of validator and transient stake accounts
+        stake_accounts: Vec<(Pubkey, Pubkey)>,
+        start_index: u32,
+        no_merge: bool,
+    },
+    UpdateStakePoolBalance {
+        stake_pool: Pubkey,
+        withdraw_authority: Pubkey,
+        validator_list: Pubkey,
+        reserve_stake: Pubkey,
+        manager_fee_account: Pubkey,
+        pool_mint: Pubkey,
+        token_program: Pubkey,
+    },
+    CleanupRemovedValidatorEntries {
+        stake_pool: Pubkey,
+        validator_list: Pubkey,
+    },
+    DepositStake {
+        stake_pool: Pubkey,
+        validator_list: Pubkey,
+        deposit_authority: Pubkey,
+        withdraw_authority: Pubkey,
+        deposit_stake: Pubkey,
+        validator_stake: Pubkey,
+        reserve_stake: Pubkey,
+        pool_tokens_to: Pubkey,
+        manager_fee_account: Pubkey,
+        referrer_pool_tokens_account: Pubkey,
+        pool_mint: Pubkey,
+        clock_sysvar: Pubkey,
+        stake_history_sysvar: Pubkey,
+        token_program: Pubkey,
+        stake_program: Pubkey,
+    },
+    WithdrawStake {
+        stake_pool: Pubkey,
+        validator_list: Pubkey,
+        withdraw_authority: Pubkey,
+        stake_to_split: Pubkey,
+        stake_to_receive: Pubkey,
+        user_stake_authority: Pubkey,
+        user_transfer_authority: Pubkey,
+        pool_tokens_from: Pubkey,
+        manager_fee_account: Pubkey,
+        pool_mint: Pubkey,
+        clock_sysvar: Pubkey,
+        token_program: Pubkey,
+        stake_program: Pubkey,
+        pool_tokens: u64,
+    },
+    SetManager {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        new_manager: Pubkey,
+        new_fee_receiver: Pubkey,
+    },
+    SetFee {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        fee: FeeType,
+    },
+    SetStaker {
+        stake_pool: Pubkey,
+        set_staker_authority: Pubkey,
+        new_staker: Pubkey,
+    },
+    DepositSol {
+        stake_pool: Pubkey,
+        withdraw_authority: Pubkey,
+        reserve_stake: Pubkey,
+        lamports_from: Pubkey,
+        pool_tokens_to: Pubkey,
+        manager_fee_account: Pubkey,
+        referrer_pool_tokens_account: Pubkey,
+        pool_mint: Pubkey,
+        system_program: Pubkey,
+        token_program: Pubkey,
+        sol_deposit_authority: Option<Pubkey>,
+        lamports: u64,
+    },
+    SetFundingAuthority {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        new_funding_authority: Option<Pubkey>,
+        funding_type: FundingType,
+    },
+    WithdrawSol {
+        stake_pool: Pubkey,
+        withdraw_authority: Pubkey,
+        user_transfer_authority: Pubkey,
+        pool_tokens_from: Pubkey,
+        reserve_stake: Pubkey,
+        lamports_to: Pubkey,
+        manager_fee_account: Pubkey,
+        pool_mint: Pubkey,
+        clock_sysvar: Pubkey,
+        stake_history_sysvar: Pubkey,
+        stake_program: Pubkey,
+        token_program: Pubkey,
+        sol_withdraw_authority: Option<Pubkey>,
+        pool_tokens: u64,
+    },
+}
+
+/// Decodes an instruction addressed to the stake pool program `program_id`
+pub fn decode_instruction(
+    program_id: &Pubkey,
+    instruction: &Instruction,
+) -> Result<DecodedInstruction, DecodeError> {
+    if instruction.program_id != *program_id {
+        return Err(DecodeError::WrongProgram(instruction.program_id));
+    }
+    let accounts: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    decode(&instruction.data, &accounts)
+}
+
+/// Decodes a compiled instruction of a message with `account_keys`, including
+/// the keys loaded from address lookup tables
+pub fn decode_compiled_instruction(
+    program_id: &Pubkey,
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<DecodedInstruction, DecodeError> {
+    let key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or(DecodeError::InvalidAccountIndex(index))
+    };
+    let instruction_program_id = key(instruction.program_id_index)?;
+    if instruction_program_id != *program_id {
+        return Err(DecodeError::WrongProgram(instruction_program_id));
+    }
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|index| key(*index))
+        .collect::<Result<Vec<_>, _>>()?;
+    decode(&instruction.data, &accounts)
+}
+
+/// Decodes instruction `data` with the legacy borsh layout, naming
+/// `accounts` in the order expected by the program
+pub fn decode(data: &[u8], accounts: &[Pubkey]) -> Result<DecodedInstruction, DecodeError> {
+    let instruction = LegacyInstruction::try_from_slice(data)
+        .map_err(|err| DecodeError::InvalidData(err.to_string()))?;
+    let mut accounts = Accounts {
+        keys: accounts,
+        position: 0,
+    };
+
+    let decoded = match instruction {
+        LegacyInstruction::Initialize {
+            fee,
+            withdrawal_fee,
+            deposit_fee,
+            referral_fee,
+            max_validators,
+        } => DecodedInstruction::Initialize {
+            stake_pool: accounts.next(10)?,
+            manager: accounts.next(10)?,
+            staker: accounts.next(10)?,
+            withdraw_authority: accounts.next(10)?,
+            validator_list: accounts.next(10)?,
+            reserve_stake: accounts.next(10)?,
+            pool_mint: accounts.next(10)?,
+            manager_fee_account: accounts.next(10)?,
+            token_program: accounts.next(10)?,
+            deposit_authority: accounts.optional(),
+            fee: convert_fee(&fee),
+            withdrawal_fee: convert_fee(&withdrawal_fee),
+            deposit_fee: convert_fee(&deposit_fee),
+            referral_fee,
+            max_validators,
+        },
+        LegacyInstruction::AddValidatorToPool => DecodedInstruction::AddValidatorToPool {
+            stake_pool: accounts.next(13)?,
+            staker: accounts.next(13)?,
+            funder: accounts.next(13)?,
+            withdraw_authority: accounts.next(13)?,
+            validator_list: accounts.next(13)?,
+            validator_stake: accounts.next(13)?,
+            vote_account: accounts.next(13)?,
+            rent_sysvar: accounts.next(13)?,
+            clock_sysvar: accounts.next(13)?,
+            stake_history_sysvar: accounts.next(13)?,
+            stake_config: accounts.next(13)?,
+            system_program: accounts.next(13)?,
+            stake_program: accounts.next(13)?,
+        },
+        LegacyInstruction::RemoveValidatorFromPool => DecodedInstruction::RemoveValidatorFromPool {
+            stake_pool: accounts.next(10)?,
+            staker: accounts.next(10)?,
+            withdraw_authority: accounts.next(10)?,
+            new_stake_authority: accounts.next(10)?,
+            validator_list: accounts.next(10)?,
+            validator_stake: accounts.next(10)?,
+            transient_stake: accounts.next(10)?,
+            destination_stake: accounts.next(10)?,
+            clock_sysvar: accounts.next(10)?,
+            stake_program: accounts.next(10)?,
+        },
+        LegacyInstruction::DecreaseValidatorStake {
+            lamports,
+            transient_stake_seed,
+        } => DecodedInstruction::DecreaseValidatorStake {
+            stake_pool: accounts.next(10)?,
+            staker: accounts.next(10)?,
+            withdraw_authority: accounts.next(10)?,
+            validator_list: accounts.next(10)?,
+            validator_stake: accounts.next(10)?,
+            transient_stake: accounts.next(10)?,
+            clock_sysvar: accounts.next(10)?,
+            rent_sysvar: accounts.next(10)?,
+            system_program: accounts.next(10)?,
+            stake_program: accounts.next(10)?,
+            lamports,
+            transient_stake_seed,
+        },
+        LegacyInstruction::IncreaseValidatorStake {
+            lamports,
+            transient_stake_seed,
+        } => DecodedInstruction::IncreaseValidatorStake {
+            stake_pool: accounts.next(13)?,
+            staker: accounts.next(13)?,
+            withdraw_authority: accounts.next(13)?,
+            validator_list: accounts.next(13)?,
+            reserve_stake: accounts.next(13)?,
+            transient_stake: accounts.next(13)?,
+            vote_account: accounts.next(13)?,
+            clock_sysvar: accounts.next(13)?,
+            rent_sysvar: accounts.next(13)?,
+            stake_history_sysvar: accounts.next(13)?,
+            stake_config: accounts.next(13)?,
+            system_program: accounts.next(13)?,
+            stake_program: accounts.next(13)?,
+            lamports,
+            transient_stake_seed,
+        },
+        LegacyInstruction::SetPreferredValidator {
+            validator_type,
+            validator_vote_address,
+        } => DecodedInstruction::SetPreferredValidator {
+            stake_pool: accounts.next(3)?,
+            staker: accounts.next(3)?,
+            validator_list: accounts.next(3)?,
+            validator_type: match validator_type {
+                spl_stake_pool_legacy::instruction::PreferredValidatorType::Deposit => {
+                    PreferredValidatorType::Deposit
+                }
+                spl_stake_pool_legacy::instruction::PreferredValidatorType::Withdraw => {
+                    PreferredValidatorType::Withdraw
+                }
+            },
+            validator_vote_address: validator_vote_address
+                .map(|address| Pubkey::new_from_array(address.to_bytes())),
+        },
+        LegacyInstruction::UpdateValidatorListBalance {
+            start_index,
+            no_merge,
+        } => DecodedInstruction::UpdateValidatorListBalance {
+            stake_pool: accounts.next(7)?,
+            withdraw_authority: accounts.next(7)?,
+            validator_list: accounts.next(7)?,
+            reserve_stake: accounts.next(7)?,
+            clock_sysvar: accounts.next(7)?,
+            stake_history_sysvar: accounts.next(7)?,
+            stake_program: accounts.next(7)?,
+            stake_accounts: accounts
+                .remaining()
+                .as_chunks::<2>()
+                .0
+                .iter()
+                .map(|[validator_stake, transient_stake]| (*validator_stake, *transient_stake))
+                .collect(),
+            start_index,
+            no_merge,
+        },
+        LegacyInstruction::UpdateStakePoolBalance => DecodedInstruction::UpdateStakePoolBalance {
+            stake_pool: accounts.next(7)?,
+            withdraw_authority: accounts.next(7)?,
+            validator_list: accounts.next(7)?,
+            reserve_stake: accounts.next(7)?,
+            manager_fee_account: accounts.next(7)?,
+            pool_mint: accounts.next(7)?,
+            token_program: accounts.next(7)?,
+        },
+        LegacyInstruction::CleanupRemovedValidatorEntries => {
+            DecodedInstruction::CleanupRemovedValidatorEntries {
+                stake_pool: accounts.next(2)?,
+                validator_list: accounts.next(2)?,
+            }
+        }
+        LegacyInstruction::DepositStake => DecodedInstruction::DepositStake {
+            stake_pool: accounts.next(15)?,
+            validator_list: accounts.next(15)?,
+            deposit_authority: accounts.next(15)?,
+            withdraw_authority: accounts.next(15)?,
+            deposit_stake: accounts.next(15)?,
+            validator_stake: accounts.next(15)?,
+            reserve_stake: accounts.next(15)?,
+            pool_tokens_to: accounts.next(15)?,
+            manager_fee_account: accounts.next(15)?,
+            referrer_pool_tokens_account: accounts.next(15)?,
+            pool_mint: accounts.next(15)?,
+            clock_sysvar: accounts.next(15)?,
+            stake_history_sysvar: accounts.next(15)?,
+            token_program: accounts.next(15)?,
+            stake_program: accounts.next(15)?,
+        },
+        LegacyInstruction::WithdrawStake(pool_tokens) => DecodedInstruction::WithdrawStake {
+            stake_pool: accounts.next(13)?,
+            validator_list: accounts.next(13)?,
+            withdraw_authority: accounts.next(13)?,
+            stake_to_split: accounts.next(13)?,
+            stake_to_receive: accounts.next(13)?,
+            user_stake_authority: accounts.next(13)?,
+            user_transfer_authority: accounts.next(13)?,
+            pool_tokens_from: accounts.next(13)?,
+            manager_fee_account: accounts.next(13)?,
+            pool_mint: accounts.next(13)?,
+            clock_sysvar: accounts.next(13)?,
+            token_program: accounts.next(13)?,
+            stake_program: accounts.next(13)?,
+            pool_tokens,
+        },
+        LegacyInstruction::SetManager => DecodedInstruction::SetManager {
+            stake_pool: accounts.next(4)?,
+            manager: accounts.next(4)?,
+            new_manager: accounts.next(4)?,
+            new_fee_receiver: accounts.next(4)?,
+        },
+        LegacyInstruction::SetFee { fee } => DecodedInstruction::SetFee {
+            stake_pool: accounts.next(2)?,
+            manager: accounts.next(2)?,
+            fee: convert_fee_type(&fee),
+        },
+        LegacyInstruction::SetStaker => DecodedInstruction::SetStaker {
+            stake_pool: accounts.next(3)?,
+            set_staker_authority: accounts.next(3)?,
+            new_staker: accounts.next(3)?,
+        },
+        LegacyInstruction::DepositSol(lamports) => DecodedInstruction::DepositSol {
+            stake_pool: accounts.next(10)?,
+            withdraw_authority: accounts.next(10)?,
+            reserve_stake: accounts.next(10)?,
+            lamports_from: accounts.next(10)?,
+            pool_tokens_to: accounts.next(10)?,
+            manager_fee_account: accounts.next(10)?,
+            referrer_pool_tokens_account: accounts.next(10)?,
+            pool_mint: accounts.next(10)?,
+            system_program: accounts.next(10)?,
+            token_program: accounts.next(10)?,
+            sol_deposit_authority: accounts.optional(),
+            lamports,
+        },
+        LegacyInstruction::SetFundingAuthority(funding_type) => {
+            DecodedInstruction::SetFundingAuthority {
+                stake_pool: accounts.next(2)?,
+                manager: accounts.next(2)?,
+                new_funding_authority: accounts.optional(),
+                funding_type: match funding_type {
+                    spl_stake_pool_legacy::instruction::FundingType::StakeDeposit => {
+                        FundingType::StakeDeposit
+                    }
+                    spl_stake_pool_legacy::instruction::FundingType::SolDeposit => {
+                        FundingType::SolDeposit
+                    }
+                    spl_stake_pool_legacy::instruction::FundingType::SolWithdraw => {
+                        FundingType::SolWithdraw
+                    }
+                },
+            }
+        }
+        LegacyInstruction::WithdrawSol(pool_tokens) => DecodedInstruction::WithdrawSol {
+            stake_pool: accounts.next(12)?,
+            withdraw_authority: accounts.next(12)?,
+            user_transfer_authority: accounts.next(12)?,
+            pool_tokens_from: accounts.next(12)?,
+            reserve_stake: accounts.next(12)?,
+            lamports_to: accounts.next(12)?,
+            manager_fee_account: accounts.next(12)?,
+            pool_mint: accounts.next(12)?,
+            clock_sysvar: accounts.next(12)?,
+            stake_history_sysvar: accounts.next(12)?,
+            stake_program: accounts.next(12)?,
+            token_program: accounts.next(12)?,
+            sol_withdraw_authority: accounts.optional(),
+            pool_tokens,
+        },
+    };
+    Ok(decoded)
+}
+
+impl DecodedInstruction {
+    /// Name of the `StakePoolInstruction` variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Initialize { .. } => "Initialize",
+            Self::AddValidatorToPool { .. } => "AddValidatorToPool",
+            Self::RemoveValidatorFromPool { .. } => "RemoveValidatorFromPool",
+            Self::DecreaseValidatorStake { .. } => "DecreaseValidatorStake",
+            Self::IncreaseValidatorStake { .. } => "IncreaseValidatorStake",
+            Self::SetPreferredValidator { .. } => "SetPreferredValidator",
+            Self::UpdateValidatorListBalance { .. } => "UpdateValidatorListBalance",
+            Self::UpdateStakePoolBalance { .. } => "UpdateStakePoolBalance",
+            Self::CleanupRemovedValidatorEntries { .. } => "CleanupRemovedValidatorEntries",
+            Self::DepositStake { .. } => "DepositStake",
+            Self::WithdrawStake { .. } => "WithdrawStake",
+            Self::SetManager { .. } => "SetManager",
+            Self::SetFee { .. } => "SetFee",
+            Self::SetStaker { .. } => "SetStaker",
+            Self::DepositSol { .. } => "DepositSol",
+            Self::SetFundingAuthority { .. } => "SetFundingAuthority",
+            Self::WithdrawSol { .. } => "WithdrawSol",
+        }
+    }
+
+    /// Accounts of the instruction with their field name, in instruction order
+    pub fn labeled_accounts(&self) -> Vec<(String, Pubkey)> {
+        let labeled = |accounts: &[(&str, &Pubkey)]| {
+            accounts
+                .iter()
+                .map(|(label, key)| (label.to_string(), **key))
+                .collect::<Vec<_>>()
+        };
+        let mut accounts = match self {
+            Self::Initialize {
+                stake_pool,
+                manager,
+                staker,
+                withdraw_authority,
+                validator_list,
+                reserve_stake,
+                pool_mint,
+                manager_fee_account,
+                token_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("manager", manager),
+                ("staker", staker),
+                ("withdraw_authority", withdraw_authority),
+                ("validator_list", validator_list),
+                ("reserve_stake", reserve_stake),
+                ("pool_mint", pool_mint),
+                ("manager_fee_account", manager_fee_account),
+                ("token_program", token_program),
+            ]),
+            Self::AddValidatorToPool {
+                stake_pool,
+                staker,
+                funder,
+                withdraw_authority,
+                validator_list,
+                validator_stake,
+                vote_account,
+                rent_sysvar,
+                clock_sysvar,
+                stake_history_sysvar,
+                stake_config,
+                system_program,
+                stake_program,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("staker", staker),
+                ("funder", funder),
+                ("withdraw_authority", withdraw_authority),
+                ("validator_list", validator_list),
+                ("validator_stake", validator_stake),
+                ("vote_account", vote_account),
+                ("rent_sysvar", rent_sysvar),
+                ("clock_sysvar", clock_sysvar),
+                ("stake_history_sysvar", stake_history_sysvar),
+                ("stake_config", stake_config),
+                ("system_program", system_program),
+                ("stake_program", stake_program),
+            ]),
+            Self::RemoveValidatorFromPool {
+                stake_pool,
+                staker,
+                withdraw_authority,
+                new_stake_authority,
+                validator_list,
+                validator_stake,
+                transient_stake,
+                destination_stake,
+                clock_sysvar,
+                stake_program,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("staker", staker),
+                ("withdraw_authority", withdraw_authority),
+                ("new_stake_authority", new_stake_authority),
+                ("validator_list", validator_list),
+                ("validator_stake", validator_stake),
+                ("transient_stake", transient_stake),
+                ("destination_stake", destination_stake),
+                ("clock_sysvar", clock_sysvar),
+                ("stake_program", stake_program),
+            ]),
+            Self::DecreaseValidatorStake {
+                stake_pool,
+                staker,
+                withdraw_authority,
+                validator_list,
+                validator_stake,
+                transient_stake,
+                clock_sysvar,
+                rent_sysvar,
+                system_program,
+                stake_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("staker", staker),
+                ("withdraw_authority", withdraw_authority),
+                ("validator_list", validator_list),
+                ("validator_stake", validator_stake),
+                ("transient_stake", transient_stake),
+                ("clock_sysvar", clock_sysvar),
+                ("rent_sysvar", rent_sysvar),
+                ("system_program", system_program),
+                ("stake_program", stake_program),
+            ]),
+            Self::IncreaseValidatorStake {
+                stake_pool,
+                staker,
+                withdraw_authority,
+                validator_list,
+                reserve_stake,
+                transient_stake,
+                vote_account,
+                clock_sysvar,
+                rent_sysvar,
+                stake_history_sysvar,
+                stake_config,
+                system_program,
+                stake_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("staker", staker),
+                ("withdraw_authority", withdraw_authority),
+                ("validator_list", validator_list),
+                ("reserve_stake", reserve_stake),
+                ("transient_stake", transient_stake),
+                ("vote_account", vote_account),
+                ("clock_sysvar", clock_sysvar),
+                ("rent_sysvar", rent_sysvar),
+                ("stake_history_sysvar", stake_history_sysvar),
+                ("stake_config", stake_config),
+                ("system_program", system_program),
+                ("stake_program", stake_program),
+            ]),
+            Self::SetPreferredValidator {
+                stake_pool,
+                staker,
+                validator_list,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("staker", staker),
+                ("validator_list", validator_list),
+            ]),
+            Self::UpdateValidatorListBalance {
+                stake_pool,
+                withdraw_authority,
+                validator_list,
+                reserve_stake,
+                clock_sysvar,
+                stake_history_sysvar,
+                stake_program,
+                stake_accounts,
+                ..
+            } => {
+                let mut accounts = labeled(&[
+                    ("stake_pool", stake_pool),
+                    ("withdraw_authority", withdraw_authority),
+                    ("validator_list", validator_list),
+                    ("reserve_stake", reserve_stake),
+                    ("clock_sysvar", clock_sysvar),
+                    ("stake_history_sysvar", stake_history_sysvar),
+                    ("stake_program", stake_program),
+                ]);
+                for (index, (validator_stake, transient_stake)) in stake_accounts.iter().enumerate()
+                {
+                    accounts.push((format!("validator_stake_{index}"), *validator_stake));
+                    accounts.push((format!("transient_stake_{index}"), *transient_stake));
+                }
+                accounts
+            }
+            Self::UpdateStakePoolBalance {
+                stake_pool,
+                withdraw_authority,
+                validator_list,
+                reserve_stake,
+                manager_fee_account,
+                pool_mint,
+                token_program,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("withdraw_authority", withdraw_authority),
+                ("validator_list", validator_list),
+                ("reserve_stake", reserve_stake),
+                ("manager_fee_account", manager_fee_account),
+                ("pool_mint", pool_mint),
+                ("token_program", token_program),
+            ]),
+            Self::CleanupRemovedValidatorEntries {
+                stake_pool,
+                validator_list,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("validator_list", validator_list),
+            ]),
+            Self::DepositStake {
+                stake_pool,
+                validator_list,
+                deposit_authority,
+                withdraw_authority,
+                deposit_stake,
+                validator_stake,
+                reserve_stake,
+                pool_tokens_to,
+                manager_fee_account,
+                referrer_pool_tokens_account,
+                pool_mint,
+                clock_sysvar,
+                stake_history_sysvar,
+                token_program,
+                stake_program,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("validator_list", validator_list),
+                ("deposit_authority", deposit_authority),
+                ("withdraw_authority", withdraw_authority),
+                ("deposit_stake", deposit_stake),
+                ("validator_stake", validator_stake),
+                ("reserve_stake", reserve_stake),
+                ("pool_tokens_to", pool_tokens_to),
+                ("manager_fee_account", manager_fee_account),
+                ("referrer_pool_tokens_account", referrer_pool_tokens_account),
+                ("pool_mint", pool_mint),
+                ("clock_sysvar", clock_sysvar),
+                ("stake_history_sysvar", stake_history_sysvar),
+                ("token_program", token_program),
+                ("stake_program", stake_program),
+            ]),
+            Self::WithdrawStake {
+                stake_pool,
+                validator_list,
+                withdraw_authority,
+                stake_to_split,
+                stake_to_receive,
+                user_stake_authority,
+                user_transfer_authority,
+                pool_tokens_from,
+                manager_fee_account,
+                pool_mint,
+                clock_sysvar,
+                token_program,
+                stake_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("validator_list", validator_list),
+                ("withdraw_authority", withdraw_authority),
+                ("stake_to_split", stake_to_split),
+                ("stake_to_receive", stake_to_receive),
+                ("user_stake_authority", user_stake_authority),
+                ("user_transfer_authority", user_transfer_authority),
+                ("pool_tokens_from", pool_tokens_from),
+                ("manager_fee_account", manager_fee_account),
+                ("pool_mint", pool_mint),
+                ("clock_sysvar", clock_sysvar),
+                ("token_program", token_program),
+                ("stake_program", stake_program),
+            ]),
+            Self::SetManager {
+                stake_pool,
+                manager,
+                new_manager,
+                new_fee_receiver,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("manager", manager),
+                ("new_manager", new_manager),
+                ("new_fee_receiver", new_fee_receiver),
+            ]),
+            Self::SetFee {
+                stake_pool,
+                manager,
+                ..
+            } => labeled(&[("stake_pool", stake_pool), ("manager", manager)]),
+            Self::SetStaker {
+                stake_pool,
+                set_staker_authority,
+                new_staker,
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("set_staker_authority", set_staker_authority),
+                ("new_staker", new_staker),
+            ]),
+            Self::DepositSol {
+                stake_pool,
+                withdraw_authority,
+                reserve_stake,
+                lamports_from,
+                pool_tokens_to,
+                manager_fee_account,
+                referrer_pool_tokens_account,
+                pool_mint,
+                system_program,
+                token_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("withdraw_authority", withdraw_authority),
+                ("reserve_stake", reserve_stake),
+                ("lamports_from", lamports_from),
+                ("pool_tokens_to", pool_tokens_to),
+                ("manager_fee_account", manager_fee_account),
+                ("referrer_pool_tokens_account", referrer_pool_tokens_account),
+                ("pool_mint", pool_mint),
+                ("system_program", system_program),
+                ("token_program", token_program),
+            ]),
+            Self::SetFundingAuthority {
+                stake_pool,
+                manager,
+                ..
+            } => labeled(&[("stake_pool", stake_pool), ("manager", manager)]),
+            Self::WithdrawSol {
+                stake_pool,
+                withdraw_authority,
+                user_transfer_authority,
+                pool_tokens_from,
+                reserve_stake,
+                lamports_to,
+                manager_fee_account,
+                pool_mint,
+                clock_sysvar,
+                stake_history_sysvar,
+                stake_program,
+                token_program,
+                ..
+            } => labeled(&[
+                ("stake_pool", stake_pool),
+                ("withdraw_authority", withdraw_authority),
+                ("user_transfer_authority", user_transfer_authority),
+                ("pool_tokens_from", pool_tokens_from),
+                ("reserve_stake", reserve_stake),
+                ("lamports_to", lamports_to),
+                ("manager_fee_account", manager_fee_account),
+                ("pool_mint", pool_mint),
+                ("clock_sysvar", clock_sysvar),
+                ("stake_history_sysvar", stake_history_sysvar),
+                ("stake_program", stake_program),
+                ("token_program", token_program),
+            ]),
+        };
+
+        // Trailing optional accounts
+        let optional = match self {
+            Self::Initialize {
+                deposit_authority, ..
+            } => deposit_authority.map(|key| ("deposit_authority", key)),
+            Self::SetFundingAuthority {
+                new_funding_authority,
+                ..
+            } => new_funding_authority.map(|key| ("new_funding_authority", key)),
+            Self::DepositSol {
+                sol_deposit_authority,
+                ..
+            } => sol_deposit_authority.map(|key| ("sol_deposit_authority", key)),
+            Self::WithdrawSol {
+                sol_withdraw_authority,
+                ..
+            } => sol_withdraw_authority.map(|key| ("sol_withdraw_authority", key)),
+            _ => None,
+        };
+        accounts.extend(optional.map(|(label, key)| (label.to_string(), key)));
+        accounts
+    }
+}
+
+/// Cursor over the accounts of an instruction
+struct Accounts<'a> {
+    keys: &'a [Pubkey],
+    position: usize,
+}
+
+impl Accounts<'_> {
+    /// Next required account, out of the `expected` accounts of the variant
+    fn next(&mut self, expected: usize) -> Result<Pubkey, DecodeError> {
+        let key = self
+            .keys
+            .get(self.position)
+            .copied()
+            .ok_or(DecodeError::MissingAccounts {
+                expected,
+                found: self.keys.len(),
+            })?;
+        self.position += 1;
+        Ok(key)
+    }
+
+    /// Next account if present, for trailing optional accounts
+    fn optional(&mut self) -> Option<Pubkey> {
+        let key = self.keys.get(self.position).copied();
+        self.position += 1;
+        key
+    }
+
+    fn remaining(&self) -> &[Pubkey] {
+        self.keys.get(self.position..).unwrap_or_default()
+    }
+}
+
+fn convert_fee(fee: &spl_stake_pool_legacy::state::Fee) -> Fee {
+    Fee {
+        denominator: fee.denominator,
+        numerator: fee.numerator,
+    }
+}
+
+fn convert_fee_type(fee: &spl_stake_pool_legacy::state::FeeType) -> FeeType {
+    use spl_stake_pool_legacy::state::FeeType as LegacyFeeType;
+    match fee {
+        LegacyFeeType::SolReferral(pct) => FeeType::SolReferral(*pct),
+        LegacyFeeType::StakeReferral(pct) => FeeType::StakeReferral(*pct),
+        LegacyFeeType::Epoch(fee) => FeeType::Epoch(convert_fee(fee)),
+        LegacyFeeType::StakeWithdrawal(fee) => FeeType::StakeWithdrawal(convert_fee(fee)),
+        LegacyFeeType::SolDeposit(fee) => FeeType::SolDeposit(convert_fee(fee)),
+        LegacyFeeType::StakeDeposit(fee) => FeeType::StakeDeposit(convert_fee(fee)),
+        LegacyFeeType::SolWithdrawal(fee) => FeeType::SolWithdrawal(convert_fee(fee)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::message::Message;
+
+    use spl_stake_pool::{
+        instruction::{FundingType, PreferredValidatorType},
+        state::{Fee, FeeType, ValidatorList, ValidatorStakeInfo},
+    };
+
+    use super::*;
+    use crate::sdk::{
+        add_validator::add_validator_to_pool,
+        decrease_validator_stake::decrease_validator_stake,
+        deposit_sol::deposit_sol_with_authority,
+        deposit_stake::deposit_stake_with_authority,
+        increase_validator_stake::increase_validator_stake,
+        initialize::initialize,
+        remove_validator::remove_validator_from_pool,
+        set_fee::set_fee,
+        set_funding_authority::set_funding_authority,
+        set_manager::set_manager,
+        set_preferred_validator::set_preferred_validator,
+        set_staker::set_staker,
+        test_utils::keys as test_keys,
+        update_stake_pool::{
+            cleanup_removed_validator_entries, update_stake_pool_balance,
+            update_validator_list_balance,
+        },
+        withdraw_sol::{withdraw_sol, withdraw_sol_with_authority},
+        withdraw_stake::withdraw_stake,
+    };
+
+    fn keys(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn decode_withdraw_sol() {
+        let program_id = spl_stake_pool::id();
+        let k = keys(9);
+        let instruction = withdraw_sol_with_authority(
+            &program_id,
+            &k[0],
+            &k[8],
+            &k[1],
+            &k[2],
+            &k[3],
+            &k[4],
+            &k[5],
+            &k[6],
+            &k[7],
+            &Pubkey::new_unique(),
+            42,
+        );
+        let decoded = decode_instruction(&program_id, &instruction).unwrap();
+        let DecodedInstruction::WithdrawSol {
+            stake_pool,
+            lamports_to,
+            sol_withdraw_authority,
+            pool_tokens,
+            ..
+        } = &decoded
+        else {
+            panic!("unexpected {decoded:?}");
+        };
+        assert_eq!((*stake_pool, *lamports_to), (k[0], k[5]));
+        assert_eq!(*sol_withdraw_authority, Some(k[8]));
+        assert_eq!(*pool_tokens, 42);
+
+        let labeled = decoded.labeled_accounts();
+        assert_eq!(labeled.len(), instruction.accounts.len());
+        for ((_, key), meta) in labeled.iter().zip(instruction.accounts.iter()) {
+            assert_eq!(*key, meta.pubkey);
+        }
+        assert_eq!(labeled[12].0, "sol_withdraw_authority");
+
+        assert_eq!(
+            decode_instruction(&Pubkey::new_unique(), &instruction),
+            Err(DecodeError::WrongProgram(program_id))
+        );
+    }
+
+    #[test]
+    fn decode_compiled() {
+        let program_id = spl_stake_pool::id();
+        let k = keys(8);
+        let instruction = withdraw_sol(
+            &program_id,
+            &k[0],
+            &k[1],
+            &k[2],
+            &k[3],
+            &k[4],
+            &k[5],
+            &k[6],
+            &k[7],
+            &Pubkey::new_unique(),
+            7,
+        );
+        let message = Message::new(std::slice::from_ref(&instruction), Some(&k[2]));
+        let decoded = decode_compiled_instruction(
+            &program_id,
+            &message.instructions[0],
+            &message.account_keys,
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            decode_instruction(&program_id, &instruction).unwrap()
+        );
+        assert_eq!(decoded.name(), "WithdrawSol");
+    }
+
+    /// Output of every builder, with the name it decodes to
+    fn built_instructions(program_id: &Pubkey) -> Vec<(&'static str, Instruction)> {
+        let [
+            pool,
+            authority,
+            list,
+            reserve,
+            mint,
+            manager_fee,
+            token_program,
+        ] = test_keys();
+        let [from, to, stake, transient, vote, referrer, new_authority] = test_keys();
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = vec![ValidatorStakeInfo {
+            vote_account_address: vote,
+            ..ValidatorStakeInfo::default()
+        }];
+        let fee = Fee {
+            denominator: 100,
+            numerator: 1,
+        };
+        let deposit_stake_instructions = deposit_stake_with_authority(
+            program_id,
+            &pool,
+            &list,
+            &new_authority,
+            &authority,
+            &stake,
+            &from,
+            &transient,
+            &reserve,
+            &to,
+            &manager_fee,
+            &referrer,
+            &mint,
+            &token_program,
+        );
+        let deposit_stake_instruction = deposit_stake_instructions
+            .into_iter()
+            .find(|instruction| instruction.program_id == *program_id)
+            .unwrap();
+
+        vec![
+            (
+                "Initialize",
+                initialize(
+                    program_id,
+                    &pool,
+                    &from,
+                    &to,
+                    &authority,
+                    &list,
+                    &reserve,
+                    &mint,
+                    &manager_fee,
+                    &token_program,
+                    Some(new_authority),
+                    fee,
+                    fee,
+                    fee,
+                    5,
+                    10,
+                ),
+            ),
+            (
+                "AddValidatorToPool",
+                add_validator_to_pool(
+                    program_id, &pool, &from, &to, &authority, &list, &stake, &vote,
+                ),
+            ),
+            (
+                "RemoveValidatorFromPool",
+                remove_validator_from_pool(
+                    program_id,
+                    &pool,
+                    &from,
+                    &authority,
+                    &new_authority,
+                    &list,
+                    &stake,
+                    &transient,
+                    &to,
+                ),
+            ),
+            (
+                "DecreaseValidatorStake",
+                decrease_validator_stake(
+                    program_id, &pool, &from, &authority, &list, &stake, &transient, 42, 3,
+                ),
+            ),
+            (
+                "IncreaseValidatorStake",
+                increase_validator_stake(
+                    program_id, &pool, &from, &authority, &list, &reserve, &transient, &vote, 42, 3,
+                ),
+            ),
+            (
+                "SetPreferredValidator",
+                set_preferred_validator(
+                    program_id,
+                    &pool,
+                    &from,
+                    &list,
+                    PreferredValidatorType::Withdraw,
+                    Some(vote),
+                ),
+            ),
+            (
+                "UpdateValidatorListBalance",
+                update_validator_list_balance(
+                    program_id,
+                    &pool,
+                    &authority,
+                    &list,
+                    &reserve,
+                    &validator_list,
+                    &[vote],
+                    0,
+                    true,
+                ),
+            ),
+            (
+                "UpdateStakePoolBalance",
+                update_stake_pool_balance(
+                    program_id,
+                    &pool,
+                    &authority,
+                    &list,
+                    &reserve,
+                    &manager_fee,
+                    &mint,
+                    &token_program,
+                ),
+            ),
+            (
+                "CleanupRemovedValidatorEntries",
+                cleanup_removed_validator_entries(program_id, &pool, &list),
+            ),
+            ("DepositStake", deposit_stake_instruction),
+            (
+                "WithdrawStake",
+                withdraw_stake(
+                    program_id,
+                    &pool,
+                    &list,
+                    &authority,
+                    &stake,
+                    &to,
+                    &new_authority,
+                    &from,
+                    &referrer,
+                    &manager_fee,
+                    &mint,
+                    &token_program,
+                    42,
+                ),
+            ),
+            (
+                "SetManager",
+                set_manager(program_id, &pool, &from, &new_authority, &manager_fee),
+            ),
+            (
+                "SetFee",
+                set_fee(program_id, &pool, &from, FeeType::SolDeposit(fee)),
+            ),
+            (
+                "SetStaker",
+                set_staker(program_id, &pool, &from, &new_authority),
+            ),
+            (
+                "DepositSol",
+                deposit_sol_with_authority(
+                    program_id,
+                    &pool,
+                    &new_authority,
+                    &authority,
+                    &reserve,
+                    &from,
+                    &to,
+                    &manager_fee,
+                    &referrer,
+                    &mint,
+                    &token_program,
+                    42,
+                ),
+            ),
+            (
+                "SetFundingAuthority",
+                set_funding_authority(
+                    program_id,
+                    &pool,
+                    &from,
+                    Some(&new_authority),
+                    FundingType::SolWithdraw,
+                ),
+            ),
+            (
+                "WithdrawSol",
+                withdraw_sol_with_authority(
+                    program_id,
+                    &pool,
+                    &new_authority,
+                    &authority,
+                    &from,
+                    &referrer,
+                    &reserve,
+                    &to,
+                    &manager_fee,
+                    &mint,
+                    &token_program,
+                    42,
+                ),
+            ),
+        ]
+    }
+
+    #[test]
+    fn builders_round_trip() {
+        let program_id = spl_stake_pool::id();
+        for (name, instruction) in built_instructions(&program_id) {
+            let decoded = decode_instruction(&program_id, &instruction)
+                .unwrap_or_else(|err| panic!("{name}: {err}"));
+            assert_eq!(decoded.name(), name);
+            let labeled: Vec<Pubkey> = decoded
+                .labeled_accounts()
+                .into_iter()
+                .map(|(_, address)| address)
+                .collect();
+            let metas: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect();
+            assert_eq!(labeled, metas, "{name}");
+        }
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(matches!(
+            decode(&[200], &[]),
+            Err(DecodeError::InvalidData(_))
+        ));
+        assert_eq!(
+            decode(&[13], &keys(2)),
+            Err(DecodeError::MissingAccounts {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use num_traits::FromPrimitive;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// Errors that may be returned by the stake pool program, the discriminant is
+/// the custom error code
+pub use spl_stake_pool_legacy::error::StakePoolError;
+
+/// Decodes `InstructionError(index, Custom(code))` into the failing
+/// instruction index and the matching [`StakePoolError`].
+///
+/// The caller is responsible for checking that the instruction at `index`
+/// targets the stake pool program, other programs reuse the same codes.
+pub fn decode_transaction_error(error: &TransactionError) -> Option<(u8, StakePoolError)> {
+    match error {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            StakePoolError::from_u32(*code).map(|error| (*index, error))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_program_error_name() {
+        assert_eq!(
+            StakePoolError::from_u32(35).unwrap().to_string(),
+            "SolWithdrawalTooLarge"
+        );
+        assert_eq!(StakePoolError::from_u32(36), None);
+    }
+
+    #[test]
+    fn decode_custom_error() {
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(17));
+        assert_eq!(
+            decode_transaction_error(&error),
+            Some((1, StakePoolError::StakeListAndPoolOutOfDate))
+        );
+        let error = TransactionError::InstructionError(0, InstructionError::InvalidArgument);
+        assert_eq!(decode_transaction_error(&error), None);
+    }
+}
@@ -0,0 +1,523 @@
+//! Layout-version-aware decoding of stake pool accounts.
+//!
+//! The vendored program (`program/`, spl-stake-pool 0.6.4) and spl-stake-pool
+//! 2.x allocate accounts of the same size, but give a different meaning to
+//! some of their bytes:
+//!
+//! * `StakePool` pending fees are `Option<Fee>` in 0.6.4 and `FutureEpoch<Fee>`
+//!   in 2.x. `Some` and `FutureEpoch::One` share a tag, only `Two` is 2.x.
+//! * `ValidatorStakeInfo` bytes 24..40 are `transient_seed_suffix_start` and
+//!   `transient_seed_suffix_end` in 0.6.4, and `transient_seed_suffix`,
+//!   `unused` and `validator_seed_suffix` in 2.x.
+//! * `StakeStatus` has 3 variants in 0.6.4 and 5 in 2.x.
+//! * `ValidatorStakeInfo::active_stake_lamports` is the delegation above
+//!   `MINIMUM_ACTIVE_STAKE` in 0.6.4, and the whole stake account balance in
+//!   2.x.
+//!
+//! Neither the account size nor the program id tell the two apart, and 0.6.4
+//! leaves `transient_seed_suffix_end` at 0, so the contents only decide when
+//! they use something one of the layouts lacks. Callers that know which
+//! program version owns the pool pass it as the expected layout, and a
+//! validator list whose version stays unknown is refused rather than guessed.
+//! Both layouts are normalized into the 2.x `StakePool` and `ValidatorList`,
+//! which is what the rest of the SDK and the CLI work with. 0.6.4 active
+//! stake needs the validator stake accounts for that, see
+//! [`DecodedValidatorList::normalize_active_stake`].
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use borsh::BorshDeserialize;
+use solana_sdk::{account::Account, stake::state::StakeStateV2};
+use spl_stake_pool::state::{
+    AccountType, FutureEpoch, StakePool, StakeStatus, ValidatorList, ValidatorStakeInfo,
+};
+
+use crate::address::MINIMUM_ACTIVE_STAKE;
+
+/// Size of the `ValidatorList` header and the length prefix of its entries
+const VALIDATOR_LIST_HEADER_LEN: usize = 1 + 4 + 4;
+
+/// Size of one `ValidatorStakeInfo`, identical in both layouts
+const VALIDATOR_STAKE_INFO_LEN: usize = std::mem::size_of::<ValidatorStakeInfo>();
+
+/// On-chain layout an account was written with
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LayoutVersion {
+    /// spl-stake-pool 0.6.4, the vendored program
+    Legacy,
+    /// spl-stake-pool 2.x
+    Current,
+    /// The contents decode identically under both layouts
+    Compatible,
+}
+
+impl LayoutVersion {
+    /// Combines the versions detected on two parts of the same pool, fails if
+    /// one is `Legacy` and the other `Current`
+    pub fn merge(self, other: Self) -> Result<Self, LayoutError> {
+        match (self, other) {
+            (Self::Compatible, version) | (version, Self::Compatible) => Ok(version),
+            (left, right) if left == right => Ok(left),
+            _ => Err(LayoutError::ConflictingLayouts),
+        }
+    }
+}
+
+impl FromStr for LayoutVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0.6.4" | "legacy" => Ok(Self::Legacy),
+            "2.x" | "2" | "current" => Ok(Self::Current),
+            _ => Err(format!("Unknown layout {s}, expected 0.6.4 or 2.x")),
+        }
+    }
+}
+
+impl Display for LayoutVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Legacy => write!(f, "0.6.4"),
+            Self::Current => write!(f, "2.x"),
+            Self::Compatible => write!(f, "0.6.4 / 2.x"),
+        }
+    }
+}
+
+/// Errors returned when decoding a stake pool account
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LayoutError {
+    /// The account is not of the expected type
+    WrongAccountType,
+    /// The account data does not deserialize under either layout
+    InvalidData(String),
+    /// The account is smaller than its header requires
+    UnexpectedSize { expected: usize, found: usize },
+    /// Some contents are only valid for 0.6.4 and others only for 2.x
+    ConflictingLayouts,
+    /// The contents are valid for both layouts and no layout was expected
+    UnknownLayout,
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::WrongAccountType => write!(f, "Unexpected account type"),
+            Self::InvalidData(err) => write!(f, "Invalid account data: {err}"),
+            Self::UnexpectedSize { expected, found } => {
+                write!(f, "Expected at least {expected} bytes, found {found}")
+            }
+            Self::ConflictingLayouts => {
+                write!(f, "Account mixes the 0.6.4 and 2.x layouts")
+            }
+            Self::UnknownLayout => write!(
+                f,
+                "Account may be 0.6.4 or 2.x, set the layout of the pool's program version"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Stake pool decoded from either layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedStakePool {
+    pub layout: LayoutVersion,
+    pub stake_pool: StakePool,
+}
+
+/// Validator list decoded from either layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedValidatorList {
+    pub layout: LayoutVersion,
+    pub validator_list: ValidatorList,
+}
+
+impl DecodedValidatorList {
+    /// Gives 0.6.4 active stake its 2.x meaning, the whole validator stake
+    /// account balance, by adding back the minimum active stake and the
+    /// rent-exempt reserve of each active entry's stake account.
+    /// `stake_accounts` holds the validator stake account of every entry, in
+    /// list order. 2.x lists are left as they are.
+    pub fn normalize_active_stake(&mut self, stake_accounts: &[Option<Account>]) {
+        if self.layout != LayoutVersion::Legacy {
+            return;
+        }
+        for (validator, account) in self
+            .validator_list
+            .validators
+            .iter_mut()
+            .zip(stake_accounts)
+        {
+            let meta = account
+                .as_ref()
+                .and_then(|account| bincode::deserialize::<StakeStateV2>(&account.data).ok())
+                .and_then(|stake_state| stake_state.meta());
+            if let (Ok(StakeStatus::Active), Some(meta)) =
+                (StakeStatus::try_from(validator.status), meta)
+            {
+                let active = u64::from(validator.active_stake_lamports);
+                validator.active_stake_lamports =
+                    (active + MINIMUM_ACTIVE_STAKE + meta.rent_exempt_reserve).into();
+            }
+        }
+    }
+}
+
+/// Decodes a `StakePool` account, as `expected` when given.
+///
+/// A 0.6.4 `Some(fee)` becomes `FutureEpoch::One(fee)`, which the 2.x program
+/// also applies at the next epoch boundary. `total_lamports` is kept as the
+/// program recorded it, since it prices the pool token.
+pub fn decode_stake_pool(
+    data: &[u8],
+    expected: Option<LayoutVersion>,
+) -> Result<DecodedStakePool, LayoutError> {
+    check_account_type(data, AccountType::StakePool)?;
+    let stake_pool = StakePool::deserialize(&mut &data[..])
+        .map_err(|err| LayoutError::InvalidData(err.to_string()))?;
+    let two_epochs_out = [
+        &stake_pool.next_epoch_fee,
+        &stake_pool.next_stake_withdrawal_fee,
+        &stake_pool.next_sol_withdrawal_fee,
+    ]
+    .into_iter()
+    .any(|fee| matches!(fee, FutureEpoch::Two(_)));
+    let detected = if two_epochs_out {
+        LayoutVersion::Current
+    } else {
+        LayoutVersion::Compatible
+    };
+    let layout = merge_expected(detected, expected)?;
+    Ok(DecodedStakePool { layout, stake_pool })
+}
+
+/// Decodes a `ValidatorList` account, as `expected` when given.
+///
+/// 0.6.4 entries keep `transient_seed_suffix_start` as the transient seed, the
+/// unused `transient_seed_suffix_end` is dropped and the validator seed is
+/// `None`, as the 0.6.4 program derives validator stake accounts without one.
+/// 0.6.4 active stake is kept as recorded until
+/// [`DecodedValidatorList::normalize_active_stake`]. A list with entries that
+/// both layouts decode the same way needs `expected`.
+pub fn decode_validator_list(
+    data: &[u8],
+    expected: Option<LayoutVersion>,
+) -> Result<DecodedValidatorList, LayoutError> {
+    check_account_type(data, AccountType::ValidatorList)?;
+    let mut validator_list = ValidatorList::deserialize(&mut &data[..])
+        .map_err(|err| LayoutError::InvalidData(err.to_string()))?;
+    let expected_len = VALIDATOR_LIST_HEADER_LEN
+        + validator_list.header.max_validators as usize * VALIDATOR_STAKE_INFO_LEN;
+    if data.len() < expected_len {
+        return Err(LayoutError::UnexpectedSize {
+            expected: expected_len,
+            found: data.len(),
+        });
+    }
+
+    let mut detected = LayoutVersion::Compatible;
+    for validator in &validator_list.validators {
+        detected = detected.merge(validator_layout(validator)?)?;
+    }
+    let layout = merge_expected(detected, expected)?;
+    // The active stake of either version would be read as 2.x
+    if layout == LayoutVersion::Compatible && !validator_list.validators.is_empty() {
+        return Err(LayoutError::UnknownLayout);
+    }
+    if layout == LayoutVersion::Legacy {
+        for validator in &mut validator_list.validators {
+            validator.unused = 0.into();
+            validator.validator_seed_suffix = 0.into();
+        }
+    }
+    Ok(DecodedValidatorList {
+        layout,
+        validator_list,
+    })
+}
+
+/// Applies the layout the caller expects to what the contents show
+fn merge_expected(
+    detected: LayoutVersion,
+    expected: Option<LayoutVersion>,
+) -> Result<LayoutVersion, LayoutError> {
+    expected.map_or(Ok(detected), |expected| detected.merge(expected))
+}
+
+/// Checks the leading `AccountType`, which both layouts share
+fn check_account_type(data: &[u8], expected: AccountType) -> Result<(), LayoutError> {
+    let account_type = AccountType::deserialize(&mut &data[..])
+        .map_err(|err| LayoutError::InvalidData(err.to_string()))?;
+    if account_type == expected {
+        Ok(())
+    } else {
+        Err(LayoutError::WrongAccountType)
+    }
+}
+
+/// Detects the layout of one entry from the bytes the layouts disagree on
+fn validator_layout(validator: &ValidatorStakeInfo) -> Result<LayoutVersion, LayoutError> {
+    let status = StakeStatus::try_from(validator.status)
+        .map_err(|_| LayoutError::InvalidData("unknown validator status".to_string()))?;
+    let unused = u32::from(validator.unused);
+    let validator_seed_suffix = u32::from(validator.validator_seed_suffix);
+    let current_only = matches!(
+        status,
+        StakeStatus::DeactivatingValidator | StakeStatus::DeactivatingAll
+    ) || validator_seed_suffix != 0;
+    // 2.x never writes `unused`, it is the low half of 0.6.4's
+    // `transient_seed_suffix_end`
+    match (current_only, unused != 0) {
+        (true, true) => Err(LayoutError::ConflictingLayouts),
+        (true, false) => Ok(LayoutVersion::Current),
+        (false, true) => Ok(LayoutVersion::Legacy),
+        (false, false) => Ok(LayoutVersion::Compatible),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh_legacy::BorshSerialize;
+    use solana_sdk::{pubkey::Pubkey, stake::state::Meta};
+    use spl_stake_pool::state::Fee;
+
+    use super::*;
+    use crate::sdk::test_utils::legacy_pubkey;
+
+    fn legacy_validator_list(
+        validators: &[spl_stake_pool_legacy::state::ValidatorStakeInfo],
+    ) -> Vec<u8> {
+        let mut validator_list = spl_stake_pool_legacy::state::ValidatorList::new(4);
+        validator_list.validators = validators.to_vec();
+        let mut data = validator_list.try_to_vec().unwrap();
+        data.resize(VALIDATOR_LIST_HEADER_LEN + 4 * VALIDATOR_STAKE_INFO_LEN, 0);
+        data
+    }
+
+    #[test]
+    fn legacy_stake_pool_is_compatible() {
+        let legacy_stake_pool = spl_stake_pool_legacy::state::StakePool {
+            account_type: spl_stake_pool_legacy::state::AccountType::StakePool,
+            staker: legacy_pubkey(&Pubkey::new_unique()),
+            total_lamports: 42,
+            next_epoch_fee: Some(spl_stake_pool_legacy::state::Fee {
+                denominator: 100,
+                numerator: 3,
+            }),
+            ..Default::default()
+        };
+        let data = legacy_stake_pool.try_to_vec().unwrap();
+        let decoded = decode_stake_pool(&data, None).unwrap();
+        assert_eq!(decoded.layout, LayoutVersion::Compatible);
+        assert_eq!(
+            decode_stake_pool(&data, Some(LayoutVersion::Legacy))
+                .unwrap()
+                .layout,
+            LayoutVersion::Legacy
+        );
+        assert_eq!(
+            decoded.stake_pool.staker.to_bytes(),
+            legacy_stake_pool.staker.to_bytes()
+        );
+        assert_eq!(decoded.stake_pool.total_lamports, 42);
+        assert_eq!(
+            decoded.stake_pool.next_epoch_fee,
+            FutureEpoch::One(Fee {
+                denominator: 100,
+                numerator: 3
+            })
+        );
+    }
+
+    #[test]
+    fn current_stake_pool() {
+        let stake_pool = StakePool {
+            account_type: AccountType::StakePool,
+            next_sol_withdrawal_fee: FutureEpoch::Two(Fee {
+                denominator: 100,
+                numerator: 1,
+            }),
+            ..StakePool::default()
+        };
+        let decoded = decode_stake_pool(&borsh::to_vec(&stake_pool).unwrap(), None).unwrap();
+        assert_eq!(decoded.layout, LayoutVersion::Current);
+        assert_eq!(decoded.stake_pool, stake_pool);
+
+        let validator_list = borsh::to_vec(&ValidatorList::new(1)).unwrap();
+        assert_eq!(
+            decode_stake_pool(&validator_list, None),
+            Err(LayoutError::WrongAccountType)
+        );
+    }
+
+    /// Stake account whose rent-exempt reserve is not the default one, so the
+    /// normalized active stake shows where the reserve was taken from
+    fn stake_account(rent_exempt_reserve: u64) -> Account {
+        let stake_state = StakeStateV2::Initialized(Meta {
+            rent_exempt_reserve,
+            ..Meta::default()
+        });
+        Account {
+            data: bincode::serialize(&stake_state).unwrap(),
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn legacy_validator_list_is_normalized() {
+        let vote = Pubkey::new_unique();
+        let legacy = spl_stake_pool_legacy::state::ValidatorStakeInfo {
+            active_stake_lamports: 10,
+            transient_seed_suffix_start: 7,
+            transient_seed_suffix_end: 9,
+            vote_account_address: legacy_pubkey(&vote),
+            ..Default::default()
+        };
+        let mut decoded = decode_validator_list(
+            &legacy_validator_list(&[
+                legacy,
+                spl_stake_pool_legacy::state::ValidatorStakeInfo::default(),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(decoded.layout, LayoutVersion::Legacy);
+        let validator = decoded.validator_list.find(&vote).unwrap();
+        assert_eq!(u64::from(validator.active_stake_lamports), 10);
+        assert_eq!(u64::from(validator.transient_seed_suffix), 7);
+        assert_eq!(u32::from(validator.unused), 0);
+        assert_eq!(u32::from(validator.validator_seed_suffix), 0);
+
+        // The second entry has no stake account and keeps what was recorded
+        decoded.normalize_active_stake(&[Some(stake_account(1_234)), None]);
+        let validators = &decoded.validator_list.validators;
+        assert_eq!(
+            u64::from(validators[0].active_stake_lamports),
+            10 + MINIMUM_ACTIVE_STAKE + 1_234
+        );
+        assert_eq!(u64::from(validators[1].active_stake_lamports), 0);
+    }
+
+    #[test]
+    fn ambiguous_validator_list_needs_expected_layout() {
+        // 0.6.4 leaves `transient_seed_suffix_end` at 0, so the entry decodes
+        // the same under both layouts
+        let vote = Pubkey::new_unique();
+        let legacy = spl_stake_pool_legacy::state::ValidatorStakeInfo {
+            active_stake_lamports: 10,
+            transient_seed_suffix_start: 7,
+            vote_account_address: legacy_pubkey(&vote),
+            ..Default::default()
+        };
+        let data = legacy_validator_list(&[legacy]);
+        assert_eq!(
+            decode_validator_list(&data, None),
+            Err(LayoutError::UnknownLayout)
+        );
+
+        let mut decoded = decode_validator_list(&data, Some(LayoutVersion::Legacy)).unwrap();
+        assert_eq!(decoded.layout, LayoutVersion::Legacy);
+        decoded.normalize_active_stake(&[Some(stake_account(1_234))]);
+        let validator = decoded.validator_list.find(&vote).unwrap();
+        assert_eq!(
+            u64::from(validator.active_stake_lamports),
+            10 + MINIMUM_ACTIVE_STAKE + 1_234
+        );
+        assert_eq!(u64::from(validator.transient_seed_suffix), 7);
+
+        let mut decoded = decode_validator_list(&data, Some(LayoutVersion::Current)).unwrap();
+        decoded.normalize_active_stake(&[Some(stake_account(1_234))]);
+        let validator = decoded.validator_list.find(&vote).unwrap();
+        assert_eq!(u64::from(validator.active_stake_lamports), 10);
+
+        // Without entries there is nothing to misread
+        let empty = legacy_validator_list(&[]);
+        assert_eq!(
+            decode_validator_list(&empty, None).unwrap().layout,
+            LayoutVersion::Compatible
+        );
+    }
+
+    #[test]
+    fn current_validator_list() {
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = vec![ValidatorStakeInfo {
+            validator_seed_suffix: 3.into(),
+            ..ValidatorStakeInfo::default()
+        }];
+        let mut data = borsh::to_vec(&validator_list).unwrap();
+        data.resize(VALIDATOR_LIST_HEADER_LEN + 2 * VALIDATOR_STAKE_INFO_LEN, 0);
+        let decoded = decode_validator_list(&data, None).unwrap();
+        assert_eq!(decoded.layout, LayoutVersion::Current);
+        assert_eq!(
+            decode_validator_list(&data, Some(LayoutVersion::Legacy)),
+            Err(LayoutError::ConflictingLayouts)
+        );
+        assert_eq!(decoded.validator_list, validator_list);
+
+        validator_list.validators = vec![ValidatorStakeInfo {
+            status: StakeStatus::DeactivatingAll.into(),
+            ..ValidatorStakeInfo::default()
+        }];
+        let mut data = borsh::to_vec(&validator_list).unwrap();
+        data.resize(VALIDATOR_LIST_HEADER_LEN + 2 * VALIDATOR_STAKE_INFO_LEN, 0);
+        assert_eq!(
+            decode_validator_list(&data, Some(LayoutVersion::Current))
+                .unwrap()
+                .layout,
+            LayoutVersion::Current
+        );
+
+        assert_eq!(
+            decode_validator_list(&data[..data.len() - 1], None),
+            Err(LayoutError::UnexpectedSize {
+                expected: data.len(),
+                found: data.len() - 1
+            })
+        );
+    }
+
+    #[test]
+    fn conflicting_validator_list() {
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = vec![
+            ValidatorStakeInfo {
+                validator_seed_suffix: 3.into(),
+                ..ValidatorStakeInfo::default()
+            },
+            ValidatorStakeInfo {
+                unused: 1.into(),
+                ..ValidatorStakeInfo::default()
+            },
+        ];
+        let data = borsh::to_vec(&validator_list).unwrap();
+        assert_eq!(
+            decode_validator_list(&data, None),
+            Err(LayoutError::ConflictingLayouts)
+        );
+        assert_eq!(
+            LayoutVersion::Legacy.merge(LayoutVersion::Current),
+            Err(LayoutError::ConflictingLayouts)
+        );
+        assert_eq!(
+            LayoutVersion::Compatible.merge(LayoutVersion::Legacy),
+            Ok(LayoutVersion::Legacy)
+        );
+    }
+
+    #[test]
+    fn layout_from_str() {
+        assert_eq!("0.6.4".parse(), Ok(LayoutVersion::Legacy));
+        assert_eq!("2.x".parse(), Ok(LayoutVersion::Current));
+        for layout in [LayoutVersion::Legacy, LayoutVersion::Current] {
+            assert_eq!(layout.to_string().parse(), Ok(layout));
+        }
+        assert!("1.0".parse::<LayoutVersion>().is_err());
+    }
+}
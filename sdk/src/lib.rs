@@ -1,2 +1,10 @@
 pub mod address;
+pub mod client;
+pub mod decode;
+pub mod error;
+pub mod layout;
+pub mod math;
+pub mod output;
 pub mod sdk;
+pub mod stake_account;
+pub mod update_plan;
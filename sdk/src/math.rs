@@ -0,0 +1,500 @@
+//! Exchange and fee math of the stake pool program, for offline quotes.
+//!
+//! Every function mirrors the matching `StakePool::calc_*` method of the
+//! program version given by `layout`: products are computed in u128, and
+//! `None` is returned on overflow or if the result does not fit in a u64.
+//!
+//! 2.x rounds fees up and withdrawals down. 0.6.4, as vendored in `program/`,
+//! rounds fees down and withdrawals up. `LayoutVersion::Compatible` does not
+//! tell which applies, so the functions that round return `None` for it.
+
+use spl_stake_pool::state::{Fee, StakePool};
+
+use crate::layout::LayoutVersion;
+
+/// Whether `layout` rounds as 0.6.4, `None` if it is unknown
+fn rounds_as_legacy(layout: LayoutVersion) -> Option<bool> {
+    match layout {
+        LayoutVersion::Legacy => Some(true),
+        LayoutVersion::Current => Some(false),
+        LayoutVersion::Compatible => None,
+    }
+}
+
+/// Fee to take from `amount`, rounded down by 0.6.4 and up by 2.x. A zero
+/// denominator means no fee.
+pub fn fee_amount(fee: &Fee, amount: u64, layout: LayoutVersion) -> Option<u64> {
+    u64::try_from(fee_amount_u128(fee, amount, layout)?).ok()
+}
+
+fn fee_amount_u128(fee: &Fee, amount: u64, layout: LayoutVersion) -> Option<u128> {
+    let legacy = rounds_as_legacy(layout)?;
+    if fee.denominator == 0 {
+        return Some(0);
+    }
+    let product = (amount as u128).checked_mul(fee.numerator as u128)?;
+    if legacy {
+        product.checked_div(fee.denominator as u128)
+    } else {
+        product
+            .checked_next_multiple_of(fee.denominator as u128)?
+            .checked_div(fee.denominator as u128)
+    }
+}
+
+/// Pool tokens minted for a deposit of `lamports`, before fees. An empty pool
+/// mints one token per lamport.
+pub fn pool_tokens_for_deposit(
+    total_lamports: u64,
+    pool_token_supply: u64,
+    lamports: u64,
+) -> Option<u64> {
+    if total_lamports == 0 || pool_token_supply == 0 {
+        return Some(lamports);
+    }
+    u64::try_from(
+        (lamports as u128)
+            .checked_mul(pool_token_supply as u128)?
+            .checked_div(total_lamports as u128)?,
+    )
+    .ok()
+}
+
+/// Lamports paid out for burning `pool_tokens`, rounded up by 0.6.4 and down
+/// by 2.x. Less than one lamport is always rounded down.
+pub fn lamports_for_withdrawal(
+    total_lamports: u64,
+    pool_token_supply: u64,
+    pool_tokens: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    let legacy = rounds_as_legacy(layout)?;
+    let numerator = (pool_tokens as u128).checked_mul(total_lamports as u128)?;
+    let denominator = pool_token_supply as u128;
+    if numerator < denominator || denominator == 0 {
+        return Some(0);
+    }
+    let lamports = if legacy {
+        numerator.div_ceil(denominator)
+    } else {
+        numerator.checked_div(denominator)?
+    };
+    u64::try_from(lamports).ok()
+}
+
+/// Share of a deposit fee paid to the referrer, `referral_fee` being a
+/// percentage
+pub fn referral_fee(deposit_fee: u64, referral_fee: u8) -> Option<u64> {
+    u64::try_from(
+        (deposit_fee as u128)
+            .checked_mul(referral_fee as u128)?
+            .checked_div(100)?,
+    )
+    .ok()
+}
+
+/// Pool tokens minted to the manager for `reward_lamports` of epoch rewards,
+/// with `total_lamports` not including the rewards yet
+pub fn epoch_fee_amount(
+    total_lamports: u64,
+    pool_token_supply: u64,
+    epoch_fee: &Fee,
+    reward_lamports: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    rounds_as_legacy(layout)?;
+    if reward_lamports == 0 {
+        return Some(0);
+    }
+    let total_lamports = (total_lamports as u128).checked_add(reward_lamports as u128)?;
+    let fee_lamports = fee_amount_u128(epoch_fee, reward_lamports, layout)?;
+    if total_lamports == fee_lamports || pool_token_supply == 0 {
+        return Some(reward_lamports);
+    }
+    u64::try_from(
+        (pool_token_supply as u128)
+            .checked_mul(fee_lamports)?
+            .checked_div(total_lamports.checked_sub(fee_lamports)?)?,
+    )
+    .ok()
+}
+
+/// Pool tokens minted for a deposit into `stake_pool`, before fees
+pub fn stake_pool_tokens_for_deposit(stake_pool: &StakePool, lamports: u64) -> Option<u64> {
+    pool_tokens_for_deposit(
+        stake_pool.total_lamports,
+        stake_pool.pool_token_supply,
+        lamports,
+    )
+}
+
+/// Lamports paid out by `stake_pool` for burning `pool_tokens`
+pub fn stake_pool_lamports_for_withdrawal(
+    stake_pool: &StakePool,
+    pool_tokens: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    lamports_for_withdrawal(
+        stake_pool.total_lamports,
+        stake_pool.pool_token_supply,
+        pool_tokens,
+        layout,
+    )
+}
+
+/// Pool tokens taken from `pool_tokens_minted` by `DepositStake`
+pub fn stake_deposit_fee(
+    stake_pool: &StakePool,
+    pool_tokens_minted: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    fee_amount(&stake_pool.stake_deposit_fee, pool_tokens_minted, layout)
+}
+
+/// Pool tokens taken from `pool_tokens_minted` by `DepositSol`
+pub fn sol_deposit_fee(
+    stake_pool: &StakePool,
+    pool_tokens_minted: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    fee_amount(&stake_pool.sol_deposit_fee, pool_tokens_minted, layout)
+}
+
+/// Pool tokens taken from `pool_tokens` by `WithdrawStake`
+pub fn stake_withdrawal_fee(
+    stake_pool: &StakePool,
+    pool_tokens: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    fee_amount(&stake_pool.stake_withdrawal_fee, pool_tokens, layout)
+}
+
+/// Pool tokens taken from `pool_tokens` by `WithdrawSol`
+pub fn sol_withdrawal_fee(
+    stake_pool: &StakePool,
+    pool_tokens: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    fee_amount(&stake_pool.sol_withdrawal_fee, pool_tokens, layout)
+}
+
+/// Referrer share of a `DepositStake` fee
+pub fn stake_referral_fee(stake_pool: &StakePool, stake_deposit_fee: u64) -> Option<u64> {
+    referral_fee(stake_deposit_fee, stake_pool.stake_referral_fee)
+}
+
+/// Referrer share of a `DepositSol` fee
+pub fn sol_referral_fee(stake_pool: &StakePool, sol_deposit_fee: u64) -> Option<u64> {
+    referral_fee(sol_deposit_fee, stake_pool.sol_referral_fee)
+}
+
+/// Pool tokens minted to the manager by `UpdateStakePoolBalance` for
+/// `reward_lamports` of rewards
+pub fn stake_pool_epoch_fee(
+    stake_pool: &StakePool,
+    reward_lamports: u64,
+    layout: LayoutVersion,
+) -> Option<u64> {
+    epoch_fee_amount(
+        stake_pool.total_lamports,
+        stake_pool.pool_token_supply,
+        &stake_pool.epoch_fee,
+        reward_lamports,
+        layout,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+    use super::*;
+
+    /// Layouts with a known rounding
+    const LAYOUTS: [LayoutVersion; 2] = [LayoutVersion::Legacy, LayoutVersion::Current];
+
+    /// Cases per property, generated from a fixed seed
+    const CASES: usize = 10_000;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
+    // The generators below are the `prop_compose!` strategies of
+    // `program/src/state.rs`, drawn from a seeded generator
+
+    fn fee(rng: &mut StdRng) -> (u64, u64) {
+        let denominator = rng.gen_range(1..=u16::MAX);
+        let numerator = rng.gen_range(0..=denominator);
+        (numerator as u64, denominator as u64)
+    }
+
+    fn total_stake_and_rewards(rng: &mut StdRng) -> (u64, u64) {
+        let total_lamports = rng.gen_range(1..u64::MAX);
+        let rewards = rng.gen_range(0..=total_lamports);
+        (total_lamports - rewards, rewards)
+    }
+
+    fn total_tokens_and_deposit(rng: &mut StdRng) -> (u64, u64, u64) {
+        let total_lamports = rng.gen_range(1..u64::MAX);
+        let pool_token_supply = rng.gen_range(1..=total_lamports);
+        let deposit_lamports = rng.gen_range(1..total_lamports);
+        (
+            total_lamports - deposit_lamports,
+            pool_token_supply.saturating_sub(deposit_lamports).max(1),
+            deposit_lamports,
+        )
+    }
+
+    #[test]
+    fn specific_fee_calculation() {
+        // 10% of 10 SOL in rewards should be 1 SOL in fees, less one lamport
+        // when withdrawals round down
+        for (layout, expected) in [
+            (LayoutVersion::Legacy, LAMPORTS_PER_SOL),
+            (LayoutVersion::Current, LAMPORTS_PER_SOL - 1),
+        ] {
+            let epoch_fee = Fee {
+                numerator: 1,
+                denominator: 10,
+            };
+            let mut total_lamports = 100 * LAMPORTS_PER_SOL;
+            let mut pool_token_supply = 100 * LAMPORTS_PER_SOL;
+            let reward_lamports = 10 * LAMPORTS_PER_SOL;
+            let pool_token_fee = epoch_fee_amount(
+                total_lamports,
+                pool_token_supply,
+                &epoch_fee,
+                reward_lamports,
+                layout,
+            )
+            .unwrap();
+
+            total_lamports += reward_lamports;
+            pool_token_supply += pool_token_fee;
+
+            let fee_lamports =
+                lamports_for_withdrawal(total_lamports, pool_token_supply, pool_token_fee, layout)
+                    .unwrap();
+            assert_eq!(fee_lamports, expected, "{layout}");
+        }
+    }
+
+    #[test]
+    fn zero_withdraw_calculation() {
+        for layout in LAYOUTS {
+            assert_eq!(lamports_for_withdrawal(0, 0, 0, layout), Some(0));
+            assert_eq!(lamports_for_withdrawal(100, 200, 1, layout), Some(0));
+        }
+    }
+
+    #[test]
+    fn divide_by_zero_fee() {
+        let epoch_fee = Fee {
+            numerator: 1,
+            denominator: 10,
+        };
+        for layout in LAYOUTS {
+            assert_eq!(epoch_fee_amount(0, 0, &epoch_fee, 10, layout), Some(10));
+            assert_eq!(fee_amount(&Fee::default(), 10, layout), Some(0));
+        }
+    }
+
+    #[test]
+    fn rounding_follows_layout() {
+        let fee = Fee {
+            numerator: 1,
+            denominator: 3,
+        };
+        assert_eq!(fee_amount(&fee, 10, LayoutVersion::Legacy), Some(3));
+        assert_eq!(fee_amount(&fee, 10, LayoutVersion::Current), Some(4));
+        assert_eq!(fee_amount(&fee, 10, LayoutVersion::Compatible), None);
+        assert_eq!(
+            lamports_for_withdrawal(10, 3, 1, LayoutVersion::Legacy),
+            Some(4)
+        );
+        assert_eq!(
+            lamports_for_withdrawal(10, 3, 1, LayoutVersion::Current),
+            Some(3)
+        );
+        assert_eq!(
+            lamports_for_withdrawal(10, 3, 1, LayoutVersion::Compatible),
+            None
+        );
+    }
+
+    #[test]
+    fn overflow_returns_none() {
+        let fee = Fee {
+            numerator: 2,
+            denominator: 1,
+        };
+        for layout in LAYOUTS {
+            assert_eq!(fee_amount(&fee, u64::MAX, layout), None);
+            assert_eq!(lamports_for_withdrawal(u64::MAX, 1, 2, layout), None);
+        }
+        assert_eq!(pool_tokens_for_deposit(1, u64::MAX, 2), None);
+    }
+
+    #[test]
+    fn referral_split() {
+        assert_eq!(referral_fee(1_000, 0), Some(0));
+        assert_eq!(referral_fee(1_000, 33), Some(330));
+        assert_eq!(referral_fee(999, 50), Some(499));
+        assert_eq!(referral_fee(u64::MAX, 100), Some(u64::MAX));
+    }
+
+    #[test]
+    fn fee_calculation() {
+        for layout in LAYOUTS {
+            let mut rng = rng();
+            for _ in 0..CASES {
+                let (numerator, denominator) = fee(&mut rng);
+                let (total_lamports, reward_lamports) = total_stake_and_rewards(&mut rng);
+                let epoch_fee = Fee {
+                    denominator,
+                    numerator,
+                };
+                let pool_token_fee = epoch_fee_amount(
+                    total_lamports,
+                    total_lamports,
+                    &epoch_fee,
+                    reward_lamports,
+                    layout,
+                )
+                .unwrap();
+
+                let fee_lamports = lamports_for_withdrawal(
+                    total_lamports + reward_lamports,
+                    total_lamports + pool_token_fee,
+                    pool_token_fee,
+                    layout,
+                )
+                .unwrap();
+                let max_fee_lamports = u64::try_from(
+                    (reward_lamports as u128) * (epoch_fee.numerator as u128)
+                        / (epoch_fee.denominator as u128),
+                )
+                .unwrap();
+                assert!(
+                    max_fee_lamports >= fee_lamports,
+                    "{layout}: max {max_fee_lamports} actual {fee_lamports}"
+                );
+
+                // two flooring conversions, plus a correction for huge
+                // discrepancies between rewards and total stake
+                let epsilon = 2 + reward_lamports / total_lamports;
+                assert!(
+                    max_fee_lamports - fee_lamports <= epsilon,
+                    "{layout}: max {max_fee_lamports} actual {fee_lamports} epsilon {epsilon}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn deposit_and_withdraw() {
+        for layout in LAYOUTS {
+            let mut rng = rng();
+            for _ in 0..CASES {
+                let (total_lamports, pool_token_supply, deposit_stake) =
+                    total_tokens_and_deposit(&mut rng);
+                let deposit_result =
+                    pool_tokens_for_deposit(total_lamports, pool_token_supply, deposit_stake)
+                        .unwrap();
+                if deposit_result == 0 {
+                    continue;
+                }
+                let withdraw_result = lamports_for_withdrawal(
+                    total_lamports + deposit_stake,
+                    pool_token_supply + deposit_result,
+                    deposit_result,
+                    layout,
+                )
+                .unwrap();
+                assert!(withdraw_result <= deposit_stake, "{layout}");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_stake_pool_methods() {
+        let mut rng = rng();
+        for _ in 0..CASES {
+            let fees = [(); 5].map(|_| fee(&mut rng));
+            let stake_pool = StakePool {
+                total_lamports: rng.r#gen(),
+                pool_token_supply: rng.r#gen(),
+                epoch_fee: to_fee(fees[0]),
+                stake_deposit_fee: to_fee(fees[1]),
+                sol_deposit_fee: to_fee(fees[2]),
+                stake_withdrawal_fee: to_fee(fees[3]),
+                sol_withdrawal_fee: to_fee(fees[4]),
+                stake_referral_fee: rng.gen_range(0..=100),
+                sol_referral_fee: rng.gen_range(0..=100),
+                ..StakePool::default()
+            };
+            let legacy_fee =
+                |(numerator, denominator): (u64, u64)| spl_stake_pool_legacy::state::Fee {
+                    denominator,
+                    numerator,
+                };
+            let legacy_stake_pool = spl_stake_pool_legacy::state::StakePool {
+                total_lamports: stake_pool.total_lamports,
+                pool_token_supply: stake_pool.pool_token_supply,
+                epoch_fee: legacy_fee(fees[0]),
+                stake_deposit_fee: legacy_fee(fees[1]),
+                sol_deposit_fee: legacy_fee(fees[2]),
+                stake_withdrawal_fee: legacy_fee(fees[3]),
+                sol_withdrawal_fee: legacy_fee(fees[4]),
+                stake_referral_fee: stake_pool.stake_referral_fee,
+                sol_referral_fee: stake_pool.sol_referral_fee,
+                ..spl_stake_pool_legacy::state::StakePool::default()
+            };
+            let amount = rng.r#gen();
+
+            macro_rules! assert_same {
+                ($function:ident, $method:ident) => {
+                    assert_eq!($function(&stake_pool, amount), stake_pool.$method(amount));
+                    assert_eq!(
+                        $function(&stake_pool, amount),
+                        legacy_stake_pool.$method(amount)
+                    );
+                };
+            }
+            assert_same!(stake_pool_tokens_for_deposit, calc_pool_tokens_for_deposit);
+            assert_same!(stake_referral_fee, calc_pool_tokens_stake_referral_fee);
+            assert_same!(sol_referral_fee, calc_pool_tokens_sol_referral_fee);
+
+            macro_rules! assert_rounding {
+                ($function:ident, $method:ident) => {
+                    assert_eq!(
+                        $function(&stake_pool, amount, LayoutVersion::Current),
+                        stake_pool.$method(amount)
+                    );
+                    assert_eq!(
+                        $function(&stake_pool, amount, LayoutVersion::Legacy),
+                        legacy_stake_pool.$method(amount)
+                    );
+                };
+            }
+            assert_rounding!(
+                stake_pool_lamports_for_withdrawal,
+                calc_lamports_withdraw_amount
+            );
+            assert_rounding!(stake_deposit_fee, calc_pool_tokens_stake_deposit_fee);
+            assert_rounding!(sol_deposit_fee, calc_pool_tokens_sol_deposit_fee);
+            assert_rounding!(stake_withdrawal_fee, calc_pool_tokens_stake_withdrawal_fee);
+            assert_rounding!(sol_withdrawal_fee, calc_pool_tokens_sol_withdrawal_fee);
+            assert_rounding!(stake_pool_epoch_fee, calc_epoch_fee_amount);
+        }
+    }
+
+    fn to_fee((numerator, denominator): (u64, u64)) -> Fee {
+        Fee {
+            denominator,
+            numerator,
+        }
+    }
+}
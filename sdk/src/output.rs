@@ -0,0 +1,742 @@
+//! Serializable views of stake pool accounts.
+//!
+//! The CLI prints these as text or JSON, so anything producing them from the
+//! same accounts produces the same JSON. Fields that depend on more than one
+//! account, such as the exchange rate and validator shares, are computed by
+//! the conversions. Update flags need the current epoch and are only set by
+//! [`CliStakePool::with_epoch`] and [`CliStakePool::from_snapshot`].
+
+use std::fmt::{Display, Formatter, Result, Write};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{native_token::Sol, pubkey::Pubkey, stake::state::Lockup};
+use solana_stake_interface::state::StakeStateV2;
+use spl_stake_pool::state::{
+    Fee, PodStakeStatus, StakePool, StakeStatus, ValidatorList, ValidatorStakeInfo,
+};
+
+use crate::{
+    address::{find_withdraw_authority_program_address, minimum_reserve_lamports},
+    client::PoolSnapshot,
+};
+
+fn update_required_flag(update_required: Option<bool>) -> &'static str {
+    if update_required == Some(true) {
+        " [UPDATE REQUIRED]"
+    } else {
+        ""
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePools {
+    pub pools: Vec<CliStakePool>,
+}
+
+impl Display for CliStakePools {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for pool in &self.pools {
+            writeln!(
+                f,
+                "Address: {}\tManager: {}\tLamports: {}\tPool tokens: {}\tValidators: {}",
+                pool.address,
+                pool.manager,
+                pool.total_lamports,
+                pool.pool_token_supply,
+                pool.validator_list.len()
+            )?;
+        }
+        writeln!(f, "Total number of pools: {}", self.pools.len())?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePool {
+    pub address: String,
+    pub pool_withdraw_authority: String,
+    pub manager: String,
+    pub staker: String,
+    pub stake_deposit_authority: String,
+    pub stake_withdraw_bump_seed: u8,
+    pub max_validators: u32,
+    pub validator_list: Vec<CliStakePoolValidator>,
+    pub validator_list_storage_account: String,
+    pub reserve_stake: String,
+    pub pool_mint: String,
+    pub manager_fee_account: String,
+    pub token_program_id: String,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    /// Lamports per pool token, 1 for an empty pool
+    pub exchange_rate: f64,
+    pub last_update_epoch: u64,
+    /// Whether the pool balance is out of date for the current epoch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub update_required: Option<bool>,
+    pub lockup: CliStakePoolLockup,
+    pub epoch_fee: CliStakePoolFee,
+    pub next_epoch_fee: Option<CliStakePoolFee>,
+    pub preferred_deposit_validator_vote_address: Option<String>,
+    pub preferred_withdraw_validator_vote_address: Option<String>,
+    pub stake_deposit_fee: CliStakePoolFee,
+    pub stake_withdrawal_fee: CliStakePoolFee,
+    pub next_stake_withdrawal_fee: Option<CliStakePoolFee>,
+    pub stake_referral_fee: u8,
+    pub sol_deposit_authority: Option<String>,
+    pub sol_deposit_fee: CliStakePoolFee,
+    pub sol_referral_fee: u8,
+    pub sol_withdraw_authority: Option<String>,
+    pub sol_withdrawal_fee: CliStakePoolFee,
+    pub next_sol_withdrawal_fee: Option<CliStakePoolFee>,
+    pub last_epoch_pool_token_supply: u64,
+    pub last_epoch_total_lamports: u64,
+    pub details: Option<CliStakePoolDetails>,
+}
+
+impl CliStakePool {
+    /// Sets the update flags of the pool and its validators for `epoch`
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.update_required = Some(self.last_update_epoch < epoch);
+        for validator in &mut self.validator_list {
+            validator.update_required = Some(validator.last_update_epoch < epoch);
+        }
+        self
+    }
+
+    /// Converts a snapshot, with the stake account details, for `epoch`
+    pub fn from_snapshot(program_id: &Pubkey, snapshot: &PoolSnapshot, epoch: u64) -> Self {
+        let pool_withdraw_authority =
+            find_withdraw_authority_program_address(program_id, &snapshot.stake_pool_address).0;
+        let stake_pool = Self::from((
+            snapshot.stake_pool_address,
+            snapshot.stake_pool.clone(),
+            snapshot.validator_list.clone(),
+            pool_withdraw_authority,
+        ));
+        Self {
+            details: Some(CliStakePoolDetails::from_snapshot(snapshot, epoch)),
+            ..stake_pool.with_epoch(epoch)
+        }
+    }
+
+    /// Writes every field and the stake account details
+    pub fn write_verbose(&self, w: &mut dyn Write) -> Result {
+        writeln!(w, "Stake Pool Info")?;
+        writeln!(w, "===============")?;
+        writeln!(w, "Stake Pool: {}", self.address)?;
+        writeln!(w, "Validator List: {}", self.validator_list_storage_account)?;
+        writeln!(w, "Manager: {}", self.manager)?;
+        writeln!(w, "Staker: {}", self.staker)?;
+        writeln!(w, "Depositor: {}", self.stake_deposit_authority)?;
+        writeln!(
+            w,
+            "SOL Deposit Authority: {}",
+            self.sol_deposit_authority.as_deref().unwrap_or("None")
+        )?;
+        writeln!(
+            w,
+            "SOL Withdraw Authority: {}",
+            self.sol_withdraw_authority.as_deref().unwrap_or("None")
+        )?;
+        writeln!(w, "Withdraw Authority: {}", self.pool_withdraw_authority)?;
+        writeln!(w, "Pool Token Mint: {}", self.pool_mint)?;
+        writeln!(w, "Fee Account: {}", self.manager_fee_account)?;
+        if let Some(vote_account) = &self.preferred_deposit_validator_vote_address {
+            writeln!(w, "Preferred Deposit Validator: {vote_account}")?;
+        }
+        if let Some(vote_account) = &self.preferred_withdraw_validator_vote_address {
+            writeln!(w, "Preferred Withdraw Validator: {vote_account}")?;
+        }
+        writeln!(
+            w,
+            "Exchange Rate: {} SOL per pool token",
+            self.exchange_rate
+        )?;
+        writeln!(w, "Epoch Fee: {} of epoch rewards", self.epoch_fee)?;
+        if let Some(next_epoch_fee) = &self.next_epoch_fee {
+            writeln!(w, "Next Epoch Fee: {next_epoch_fee} of epoch rewards")?;
+        }
+        writeln!(
+            w,
+            "Stake Withdrawal Fee: {} of withdrawal amount",
+            self.stake_withdrawal_fee
+        )?;
+        if let Some(next_stake_withdrawal_fee) = &self.next_stake_withdrawal_fee {
+            writeln!(
+                w,
+                "Next Stake Withdrawal Fee: {next_stake_withdrawal_fee} of withdrawal amount"
+            )?;
+        }
+        writeln!(
+            w,
+            "SOL Withdrawal Fee: {} of withdrawal amount",
+            self.sol_withdrawal_fee
+        )?;
+        if let Some(next_sol_withdrawal_fee) = &self.next_sol_withdrawal_fee {
+            writeln!(
+                w,
+                "Next SOL Withdrawal Fee: {next_sol_withdrawal_fee} of withdrawal amount"
+            )?;
+        }
+        writeln!(
+            w,
+            "Stake Deposit Fee: {} of deposit amount",
+            self.stake_deposit_fee
+        )?;
+        writeln!(
+            w,
+            "SOL Deposit Fee: {} of deposit amount",
+            self.sol_deposit_fee
+        )?;
+        writeln!(
+            w,
+            "Stake Deposit Referral Fee: {}% of Stake Deposit Fee",
+            self.stake_referral_fee
+        )?;
+        writeln!(
+            w,
+            "SOL Deposit Referral Fee: {}% of SOL Deposit Fee",
+            self.sol_referral_fee
+        )?;
+        if let Some(details) = &self.details {
+            details.write_verbose(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CliStakePool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Stake Pool: {}", self.address)?;
+        writeln!(f, "Validator List: {}", self.validator_list_storage_account)?;
+        writeln!(f, "Pool Token Mint: {}", self.pool_mint)?;
+        if let Some(vote_account) = &self.preferred_deposit_validator_vote_address {
+            writeln!(f, "Preferred Deposit Validator: {vote_account}")?;
+        }
+        if let Some(vote_account) = &self.preferred_withdraw_validator_vote_address {
+            writeln!(f, "Preferred Withdraw Validator: {vote_account}")?;
+        }
+        writeln!(
+            f,
+            "Exchange Rate: {} SOL per pool token",
+            self.exchange_rate
+        )?;
+        writeln!(f, "Epoch Fee: {} of epoch rewards", self.epoch_fee)?;
+        writeln!(
+            f,
+            "Stake Withdrawal Fee: {} of withdrawal amount",
+            self.stake_withdrawal_fee
+        )?;
+        writeln!(
+            f,
+            "SOL Withdrawal Fee: {} of withdrawal amount",
+            self.sol_withdrawal_fee
+        )?;
+        writeln!(
+            f,
+            "Stake Deposit Fee: {} of deposit amount",
+            self.stake_deposit_fee
+        )?;
+        writeln!(
+            f,
+            "SOL Deposit Fee: {} of deposit amount",
+            self.sol_deposit_fee
+        )?;
+        writeln!(
+            f,
+            "Stake Deposit Referral Fee: {}% of Stake Deposit Fee",
+            self.stake_referral_fee
+        )?;
+        writeln!(
+            f,
+            "SOL Deposit Referral Fee: {}% of SOL Deposit Fee",
+            self.sol_referral_fee
+        )?;
+        if let Some(details) = &self.details {
+            write!(f, "{details}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePoolDetails {
+    pub reserve_stake_account_address: String,
+    pub reserve_stake_lamports: u64,
+    pub minimum_reserve_stake_balance: u64,
+    pub stake_accounts: Vec<CliStakePoolStakeAccountInfo>,
+    pub total_lamports: u64,
+    pub total_pool_tokens: u64,
+    pub current_number_of_validators: u32,
+    pub max_number_of_validators: u32,
+    pub update_required: bool,
+}
+
+impl CliStakePoolDetails {
+    /// Balances of the reserve and of every validator and transient stake
+    /// account of a snapshot, for `epoch`
+    pub fn from_snapshot(snapshot: &PoolSnapshot, epoch: u64) -> Self {
+        let minimum_reserve_stake_balance = snapshot
+            .reserve_stake
+            .as_ref()
+            .and_then(|account| bincode::deserialize::<StakeStateV2>(&account.data).ok())
+            .and_then(|stake_state| stake_state.meta())
+            .map_or(0, |meta| minimum_reserve_lamports(&meta));
+        let stake_accounts = snapshot
+            .validators
+            .iter()
+            .map(|validator| {
+                let last_update_epoch = u64::from(validator.info.last_update_epoch);
+                let active_lamports = u64::from(validator.info.active_stake_lamports);
+                let transient_lamports = u64::from(validator.info.transient_stake_lamports);
+                CliStakePoolStakeAccountInfo {
+                    vote_account_address: validator.info.vote_account_address.to_string(),
+                    stake_account_address: validator.stake_address.to_string(),
+                    validator_active_stake_lamports: active_lamports,
+                    validator_last_update_epoch: last_update_epoch,
+                    validator_lamports: active_lamports.saturating_add(transient_lamports),
+                    validator_transient_stake_account_address: validator
+                        .transient_stake_address
+                        .to_string(),
+                    validator_transient_stake_lamports: transient_lamports,
+                    update_required: last_update_epoch < epoch,
+                }
+            })
+            .collect();
+        Self {
+            reserve_stake_account_address: snapshot.stake_pool.reserve_stake.to_string(),
+            reserve_stake_lamports: snapshot.reserve_lamports(),
+            minimum_reserve_stake_balance,
+            stake_accounts,
+            total_lamports: snapshot.stake_pool.total_lamports,
+            total_pool_tokens: snapshot.stake_pool.pool_token_supply,
+            current_number_of_validators: snapshot.validators.len() as u32,
+            max_number_of_validators: snapshot.validator_list.header.max_validators,
+            update_required: snapshot.stake_pool.last_update_epoch < epoch,
+        }
+    }
+
+    fn available_reserve_lamports(&self) -> u64 {
+        self.reserve_stake_lamports
+            .saturating_sub(self.minimum_reserve_stake_balance)
+    }
+
+    /// Writes the reserve and every stake account with its transient stake
+    pub fn write_verbose(&self, w: &mut dyn Write) -> Result {
+        writeln!(w)?;
+        writeln!(w, "Stake Accounts")?;
+        writeln!(w, "--------------")?;
+        writeln!(
+            w,
+            "Reserve Account: {}\tAvailable Balance: {}",
+            self.reserve_stake_account_address,
+            Sol(self.available_reserve_lamports()),
+        )?;
+        for stake_account in &self.stake_accounts {
+            writeln!(
+                w,
+                "Vote Account: {}\tStake Account: {}\tActive Balance: {}\tTransient Stake Account: {}\tTransient Balance: {}\tLast Update Epoch: {}{}",
+                stake_account.vote_account_address,
+                stake_account.stake_account_address,
+                Sol(stake_account.validator_active_stake_lamports),
+                stake_account.validator_transient_stake_account_address,
+                Sol(stake_account.validator_transient_stake_lamports),
+                stake_account.validator_last_update_epoch,
+                update_required_flag(Some(stake_account.update_required)),
+            )?;
+        }
+        self.write_totals(w)
+    }
+
+    fn write_totals(&self, w: &mut dyn Write) -> Result {
+        writeln!(
+            w,
+            "Total Pool Stake: {}{}",
+            Sol(self.total_lamports),
+            update_required_flag(Some(self.update_required)),
+        )?;
+        writeln!(w, "Total Pool Tokens: {}", self.total_pool_tokens)?;
+        writeln!(
+            w,
+            "Current Number of Validators: {}",
+            self.current_number_of_validators,
+        )?;
+        writeln!(
+            w,
+            "Max Number of Validators: {}",
+            self.max_number_of_validators,
+        )
+    }
+}
+
+impl Display for CliStakePoolDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(
+            f,
+            "Reserve Account: {}\tAvailable Balance: {}",
+            self.reserve_stake_account_address,
+            Sol(self.available_reserve_lamports()),
+        )?;
+        for stake_account in &self.stake_accounts {
+            writeln!(
+                f,
+                "Vote Account: {}\tBalance: {}\tLast Update Epoch: {}",
+                stake_account.vote_account_address,
+                Sol(stake_account.validator_lamports),
+                stake_account.validator_last_update_epoch,
+            )?;
+        }
+        self.write_totals(f)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePoolStakeAccountInfo {
+    pub vote_account_address: String,
+    pub stake_account_address: String,
+    pub validator_active_stake_lamports: u64,
+    pub validator_last_update_epoch: u64,
+    pub validator_lamports: u64,
+    pub validator_transient_stake_account_address: String,
+    pub validator_transient_stake_lamports: u64,
+    pub update_required: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePoolValidator {
+    pub active_stake_lamports: u64,
+    pub transient_stake_lamports: u64,
+    pub last_update_epoch: u64,
+    pub transient_seed_suffix: u64,
+    pub validator_seed_suffix: u32,
+    pub status: CliStakePoolValidatorStakeStatus,
+    pub vote_account_address: String,
+    /// Share of the pool's `total_lamports`, reserve included, held by this
+    /// validator, active and transient. Only set with the stake pool.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub share: Option<f64>,
+    /// Whether the validator balances are out of date for the current epoch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub update_required: Option<bool>,
+}
+
+impl From<ValidatorStakeInfo> for CliStakePoolValidator {
+    fn from(v: ValidatorStakeInfo) -> Self {
+        Self {
+            active_stake_lamports: v.active_stake_lamports.into(),
+            transient_stake_lamports: v.transient_stake_lamports.into(),
+            last_update_epoch: v.last_update_epoch.into(),
+            transient_seed_suffix: v.transient_seed_suffix.into(),
+            validator_seed_suffix: v.validator_seed_suffix.into(),
+            status: CliStakePoolValidatorStakeStatus::from(v.status),
+            vote_account_address: v.vote_account_address.to_string(),
+            share: None,
+            update_required: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CliStakePoolValidatorStakeStatus {
+    Active,
+    DeactivatingTransient,
+    ReadyForRemoval,
+    DeactivatingValidator,
+    DeactivatingAll,
+    /// A status byte unknown to this version
+    Unknown,
+}
+
+impl From<StakeStatus> for CliStakePoolValidatorStakeStatus {
+    fn from(s: StakeStatus) -> Self {
+        match s {
+            StakeStatus::Active => Self::Active,
+            StakeStatus::DeactivatingTransient => Self::DeactivatingTransient,
+            StakeStatus::ReadyForRemoval => Self::ReadyForRemoval,
+            StakeStatus::DeactivatingValidator => Self::DeactivatingValidator,
+            StakeStatus::DeactivatingAll => Self::DeactivatingAll,
+        }
+    }
+}
+
+impl From<PodStakeStatus> for CliStakePoolValidatorStakeStatus {
+    fn from(s: PodStakeStatus) -> Self {
+        StakeStatus::try_from(s).map_or(Self::Unknown, Self::from)
+    }
+}
+
+/// Validators of a validator list. Shares need the pool's `total_lamports`
+/// and are only set by the [`CliStakePool`] conversions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliValidatorList {
+    pub max_validators: u32,
+    pub validators: Vec<CliStakePoolValidator>,
+}
+
+impl From<ValidatorList> for CliValidatorList {
+    fn from(validator_list: ValidatorList) -> Self {
+        Self {
+            max_validators: validator_list.header.max_validators,
+            validators: validator_list
+                .validators
+                .into_iter()
+                .map(CliStakePoolValidator::from)
+                .collect(),
+        }
+    }
+}
+
+fn share(lamports: u64, total_lamports: u64) -> f64 {
+    if total_lamports == 0 {
+        0.0
+    } else {
+        lamports as f64 / total_lamports as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePoolLockup {
+    pub unix_timestamp: i64,
+    pub epoch: u64,
+    pub custodian: String,
+}
+
+impl From<Lockup> for CliStakePoolLockup {
+    fn from(l: Lockup) -> Self {
+        Self {
+            unix_timestamp: l.unix_timestamp,
+            epoch: l.epoch,
+            custodian: l.custodian.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePoolFee {
+    pub denominator: u64,
+    pub numerator: u64,
+}
+
+impl Display for CliStakePoolFee {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl From<Fee> for CliStakePoolFee {
+    fn from(f: Fee) -> Self {
+        Self {
+            denominator: f.denominator,
+            numerator: f.numerator,
+        }
+    }
+}
+
+impl From<(Pubkey, StakePool, ValidatorList, Pubkey)> for CliStakePool {
+    fn from(s: (Pubkey, StakePool, ValidatorList, Pubkey)) -> Self {
+        let (address, stake_pool, validator_list, pool_withdraw_authority) = s;
+        let mut validator_list = CliValidatorList::from(validator_list);
+        for validator in &mut validator_list.validators {
+            validator.share = Some(share(
+                validator
+                    .active_stake_lamports
+                    .saturating_add(validator.transient_stake_lamports),
+                stake_pool.total_lamports,
+            ));
+        }
+        let exchange_rate = if stake_pool.pool_token_supply == 0 {
+            1.0
+        } else {
+            stake_pool.total_lamports as f64 / stake_pool.pool_token_supply as f64
+        };
+        Self {
+            address: address.to_string(),
+            pool_withdraw_authority: pool_withdraw_authority.to_string(),
+            manager: stake_pool.manager.to_string(),
+            staker: stake_pool.staker.to_string(),
+            stake_deposit_authority: stake_pool.stake_deposit_authority.to_string(),
+            stake_withdraw_bump_seed: stake_pool.stake_withdraw_bump_seed,
+            max_validators: validator_list.max_validators,
+            validator_list: validator_list.validators,
+            validator_list_storage_account: stake_pool.validator_list.to_string(),
+            reserve_stake: stake_pool.reserve_stake.to_string(),
+            pool_mint: stake_pool.pool_mint.to_string(),
+            manager_fee_account: stake_pool.manager_fee_account.to_string(),
+            token_program_id: stake_pool.token_program_id.to_string(),
+            total_lamports: stake_pool.total_lamports,
+            pool_token_supply: stake_pool.pool_token_supply,
+            exchange_rate,
+            last_update_epoch: stake_pool.last_update_epoch,
+            update_required: None,
+            lockup: CliStakePoolLockup::from(stake_pool.lockup),
+            epoch_fee: CliStakePoolFee::from(stake_pool.epoch_fee),
+            next_epoch_fee: Option::<Fee>::from(stake_pool.next_epoch_fee)
+                .map(CliStakePoolFee::from),
+            preferred_deposit_validator_vote_address: stake_pool
+                .preferred_deposit_validator_vote_address
+                .map(|x| x.to_string()),
+            preferred_withdraw_validator_vote_address: stake_pool
+                .preferred_withdraw_validator_vote_address
+                .map(|x| x.to_string()),
+            stake_deposit_fee: CliStakePoolFee::from(stake_pool.stake_deposit_fee),
+            stake_withdrawal_fee: CliStakePoolFee::from(stake_pool.stake_withdrawal_fee),
+            next_stake_withdrawal_fee: Option::<Fee>::from(stake_pool.next_stake_withdrawal_fee)
+                .map(CliStakePoolFee::from),
+            stake_referral_fee: stake_pool.stake_referral_fee,
+            sol_deposit_authority: stake_pool.sol_deposit_authority.map(|x| x.to_string()),
+            sol_deposit_fee: CliStakePoolFee::from(stake_pool.sol_deposit_fee),
+            sol_referral_fee: stake_pool.sol_referral_fee,
+            sol_withdraw_authority: stake_pool.sol_withdraw_authority.map(|x| x.to_string()),
+            sol_withdrawal_fee: CliStakePoolFee::from(stake_pool.sol_withdrawal_fee),
+            next_sol_withdrawal_fee: Option::<Fee>::from(stake_pool.next_sol_withdrawal_fee)
+                .map(CliStakePoolFee::from),
+            last_epoch_pool_token_supply: stake_pool.last_epoch_pool_token_supply,
+            last_epoch_total_lamports: stake_pool.last_epoch_total_lamports,
+            details: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::account::Account;
+    use solana_stake_interface::state::Meta;
+
+    use super::*;
+    use crate::client::ValidatorSnapshot;
+
+    fn pool() -> (StakePool, ValidatorList) {
+        let stake_pool = StakePool {
+            total_lamports: 1_000,
+            pool_token_supply: 800,
+            last_update_epoch: 9,
+            reserve_stake: Pubkey::new_unique(),
+            epoch_fee: Fee {
+                numerator: 1,
+                denominator: 20,
+            },
+            ..StakePool::default()
+        };
+        let mut validator_list = ValidatorList::new(4);
+        validator_list.validators = vec![
+            ValidatorStakeInfo {
+                active_stake_lamports: 600.into(),
+                transient_stake_lamports: 100.into(),
+                last_update_epoch: 10.into(),
+                vote_account_address: Pubkey::new_unique(),
+                ..ValidatorStakeInfo::default()
+            },
+            ValidatorStakeInfo {
+                active_stake_lamports: 200.into(),
+                last_update_epoch: 9.into(),
+                status: StakeStatus::DeactivatingValidator.into(),
+                vote_account_address: Pubkey::new_unique(),
+                ..ValidatorStakeInfo::default()
+            },
+        ];
+        (stake_pool, validator_list)
+    }
+
+    #[test]
+    fn computes_exchange_rate_and_shares() {
+        let (stake_pool, validator_list) = pool();
+        let cli_pool = CliStakePool::from((
+            Pubkey::new_unique(),
+            stake_pool,
+            validator_list.clone(),
+            Pubkey::new_unique(),
+        ));
+        assert_eq!(cli_pool.exchange_rate, 1.25);
+        assert_eq!(cli_pool.max_validators, 4);
+        assert_eq!(cli_pool.update_required, None);
+        let shares: Vec<Option<f64>> = cli_pool.validator_list.iter().map(|v| v.share).collect();
+        assert_eq!(shares, vec![Some(0.7), Some(0.2)]);
+        assert_eq!(
+            cli_pool.validator_list[1].status,
+            CliStakePoolValidatorStakeStatus::DeactivatingValidator
+        );
+
+        // no shares without the pool's total lamports
+        let cli_list = CliValidatorList::from(validator_list);
+        assert!(cli_list.validators.iter().all(|v| v.share.is_none()));
+
+        let cli_pool = cli_pool.with_epoch(10);
+        assert_eq!(cli_pool.update_required, Some(true));
+        let flags: Vec<Option<bool>> = cli_pool
+            .validator_list
+            .iter()
+            .map(|v| v.update_required)
+            .collect();
+        assert_eq!(flags, vec![Some(false), Some(true)]);
+    }
+
+    #[test]
+    fn serializes_camel_case_json() {
+        let (stake_pool, validator_list) = pool();
+        let cli_pool = CliStakePool::from((
+            Pubkey::new_unique(),
+            stake_pool,
+            validator_list,
+            Pubkey::new_unique(),
+        ));
+        let json = serde_json::to_value(&cli_pool).unwrap();
+        assert_eq!(json["exchangeRate"], 1.25);
+        assert_eq!(json["epochFee"]["numerator"], 1);
+        assert_eq!(json["validatorList"][0]["activeStakeLamports"], 600);
+        assert_eq!(json["validatorList"][1]["status"], "DeactivatingValidator");
+        assert!(json.get("updateRequired").is_none());
+        assert_eq!(
+            serde_json::from_value::<CliStakePool>(json).unwrap(),
+            cli_pool
+        );
+    }
+
+    #[test]
+    fn details_from_snapshot() {
+        let (stake_pool, validator_list) = pool();
+        let reserve = StakeStateV2::Initialized(Meta {
+            rent_exempt_reserve: 50,
+            ..Meta::default()
+        });
+        let snapshot = PoolSnapshot {
+            stake_pool_address: Pubkey::new_unique(),
+            layout: crate::layout::LayoutVersion::Compatible,
+            validators: validator_list
+                .validators
+                .iter()
+                .map(|info| ValidatorSnapshot {
+                    info: *info,
+                    stake_address: Pubkey::new_unique(),
+                    stake_account: None,
+                    transient_stake_address: Pubkey::new_unique(),
+                    transient_stake_account: None,
+                })
+                .collect(),
+            stake_pool,
+            validator_list,
+            reserve_stake: Some(Account {
+                lamports: 200,
+                data: bincode::serialize(&reserve).unwrap(),
+                ..Account::default()
+            }),
+        };
+        let cli_pool = CliStakePool::from_snapshot(&spl_stake_pool::id(), &snapshot, 10);
+        assert_eq!(cli_pool.update_required, Some(true));
+        let details = cli_pool.details.unwrap();
+        assert_eq!(details.minimum_reserve_stake_balance, 51);
+        assert_eq!(details.available_reserve_lamports(), 149);
+        assert_eq!(details.stake_accounts[0].validator_lamports, 700);
+        assert!(!details.stake_accounts[0].update_required);
+        assert!(details.stake_accounts[1].update_required);
+        assert_eq!(details.current_number_of_validators, 2);
+    }
+}
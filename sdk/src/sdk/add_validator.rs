@@ -4,9 +4,9 @@ use solana_sdk::{
     pubkey::Pubkey,
     sysvar,
 };
-use spl_stake_pool::{
-    find_stake_program_address, find_withdraw_authority_program_address, state::StakePool,
-};
+use spl_stake_pool::state::StakePool;
+
+use crate::address::{find_stake_program_address, find_withdraw_authority_program_address};
 
 pub fn add_validator_to_pool_with_vote(
     program_id: &Pubkey,
@@ -66,3 +66,71 @@ pub fn add_validator_to_pool(
             .unwrap(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn add_validator_to_pool_matches_program() {
+        let [
+            program_id,
+            pool,
+            staker,
+            funder,
+            withdraw,
+            list,
+            stake,
+            vote,
+        ] = keys();
+        let instruction = add_validator_to_pool(
+            &program_id,
+            &pool,
+            &staker,
+            &funder,
+            &withdraw,
+            &list,
+            &stake,
+            &vote,
+        );
+        let expected = spl_stake_pool_legacy::instruction::add_validator_to_pool(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&staker),
+            &legacy_pubkey(&funder),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&stake),
+            &legacy_pubkey(&vote),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn add_validator_to_pool_with_vote_matches_program() {
+        let [program_id, pool, funder, vote] = keys();
+        let stake_pool = StakePool {
+            staker: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let instruction =
+            add_validator_to_pool_with_vote(&program_id, &stake_pool, &pool, &funder, &vote);
+        let legacy_program_id = legacy_pubkey(&program_id);
+        let legacy_pool = legacy_pubkey(&pool);
+        let legacy_stake_pool = spl_stake_pool_legacy::state::StakePool {
+            staker: legacy_pubkey(&stake_pool.staker),
+            validator_list: legacy_pubkey(&stake_pool.validator_list),
+            ..Default::default()
+        };
+        let expected = spl_stake_pool_legacy::instruction::add_validator_to_pool_with_vote(
+            &legacy_program_id,
+            &legacy_stake_pool,
+            &legacy_pool,
+            &legacy_pubkey(&funder),
+            &legacy_pubkey(&vote),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
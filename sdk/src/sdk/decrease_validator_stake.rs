@@ -0,0 +1,156 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_stake_pool::state::StakePool;
+
+use crate::address::{
+    find_stake_program_address, find_transient_stake_program_address,
+    find_withdraw_authority_program_address,
+};
+
+pub fn decrease_validator_stake_with_vote(
+    program_id: &Pubkey,
+    stake_pool: &StakePool,
+    stake_pool_address: &Pubkey,
+    vote_account_address: &Pubkey,
+    lamports: u64,
+    transient_stake_seed: u64,
+) -> Instruction {
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(program_id, stake_pool_address).0;
+    let (validator_stake_address, _) =
+        find_stake_program_address(program_id, vote_account_address, stake_pool_address, None);
+    let (transient_stake_address, _) = find_transient_stake_program_address(
+        program_id,
+        vote_account_address,
+        stake_pool_address,
+        transient_stake_seed,
+    );
+
+    decrease_validator_stake(
+        program_id,
+        stake_pool_address,
+        &stake_pool.staker,
+        &pool_withdraw_authority,
+        &stake_pool.validator_list,
+        &validator_stake_address,
+        &transient_stake_address,
+        lamports,
+        transient_stake_seed,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn decrease_validator_stake(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    staker: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    validator_list: &Pubkey,
+    validator_stake: &Pubkey,
+    transient_stake: &Pubkey,
+    lamports: u64,
+    transient_stake_seed: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*staker, true),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*validator_list, false),
+        AccountMeta::new(*validator_stake, false),
+        AccountMeta::new(*transient_stake, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::DecreaseValidatorStake {
+            lamports,
+            transient_stake_seed,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn decrease_validator_stake_matches_program() {
+        let [
+            program_id,
+            pool,
+            staker,
+            withdraw,
+            list,
+            validator_stake,
+            transient_stake,
+        ] = keys();
+        let instruction = decrease_validator_stake(
+            &program_id,
+            &pool,
+            &staker,
+            &withdraw,
+            &list,
+            &validator_stake,
+            &transient_stake,
+            3_000_000_000,
+            9,
+        );
+        let expected = spl_stake_pool_legacy::instruction::decrease_validator_stake(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&staker),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&validator_stake),
+            &legacy_pubkey(&transient_stake),
+            3_000_000_000,
+            9,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn decrease_validator_stake_with_vote_matches_program() {
+        let [program_id, pool, vote] = keys();
+        let stake_pool = StakePool {
+            staker: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let instruction = decrease_validator_stake_with_vote(
+            &program_id,
+            &stake_pool,
+            &pool,
+            &vote,
+            3_000_000_000,
+            9,
+        );
+        let legacy_stake_pool = spl_stake_pool_legacy::state::StakePool {
+            staker: legacy_pubkey(&stake_pool.staker),
+            validator_list: legacy_pubkey(&stake_pool.validator_list),
+            reserve_stake: legacy_pubkey(&stake_pool.reserve_stake),
+            ..Default::default()
+        };
+        let expected = spl_stake_pool_legacy::instruction::decrease_validator_stake_with_vote(
+            &legacy_pubkey(&program_id),
+            &legacy_stake_pool,
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&vote),
+            3_000_000_000,
+            9,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,171 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_sol(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_from: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*lamports_from, true),
+        AccountMeta::new(*pool_tokens_to, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*referrer_pool_tokens_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::DepositSol(amount)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Same as `deposit_sol`, for pools where the SOL deposit authority must
+/// sign
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_sol_with_authority(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    sol_deposit_authority: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_from: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction = deposit_sol(
+        program_id,
+        stake_pool,
+        stake_pool_withdraw_authority,
+        reserve_stake_account,
+        lamports_from,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        amount,
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*sol_deposit_authority, true));
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn deposit_sol_matches_program() {
+        let [
+            program_id,
+            pool,
+            withdraw,
+            reserve,
+            from,
+            to,
+            fees,
+            referrer,
+            mint,
+            token,
+        ] = keys();
+        let instruction = deposit_sol(
+            &program_id,
+            &pool,
+            &withdraw,
+            &reserve,
+            &from,
+            &to,
+            &fees,
+            &referrer,
+            &mint,
+            &token,
+            5_000_000_000,
+        );
+        let expected = spl_stake_pool_legacy::instruction::deposit_sol(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&from),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&referrer),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+            5_000_000_000,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn deposit_sol_with_authority_matches_program() {
+        let [
+            program_id,
+            pool,
+            authority,
+            withdraw,
+            reserve,
+            from,
+            to,
+            fees,
+            referrer,
+            mint,
+            token,
+        ] = keys();
+        let instruction = deposit_sol_with_authority(
+            &program_id,
+            &pool,
+            &authority,
+            &withdraw,
+            &reserve,
+            &from,
+            &to,
+            &fees,
+            &referrer,
+            &mint,
+            &token,
+            42,
+        );
+        let expected = spl_stake_pool_legacy::instruction::deposit_sol_with_authority(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&authority),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&from),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&referrer),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+            42,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
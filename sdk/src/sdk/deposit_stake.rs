@@ -0,0 +1,262 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_stake_interface::{instruction::authorize, state::StakeAuthorize};
+
+use crate::address::find_deposit_authority_program_address;
+
+/// Creates the instructions depositing `deposit_stake_address` into a pool
+/// with the default deposit authority: the staker and withdrawer of the stake
+/// account are first moved to the pool deposit authority
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_stake(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    deposit_stake_address: &Pubkey,
+    deposit_stake_withdraw_authority: &Pubkey,
+    validator_stake_account: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Vec<Instruction> {
+    let stake_pool_deposit_authority =
+        find_deposit_authority_program_address(program_id, stake_pool).0;
+    deposit_stake_internal(
+        program_id,
+        stake_pool,
+        validator_list_storage,
+        &stake_pool_deposit_authority,
+        stake_pool_withdraw_authority,
+        deposit_stake_address,
+        deposit_stake_withdraw_authority,
+        validator_stake_account,
+        reserve_stake_account,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        false,
+    )
+}
+
+/// Same as `deposit_stake`, for private pools where the stake deposit
+/// authority must sign
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_stake_with_authority(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_deposit_authority: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    deposit_stake_address: &Pubkey,
+    deposit_stake_withdraw_authority: &Pubkey,
+    validator_stake_account: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Vec<Instruction> {
+    deposit_stake_internal(
+        program_id,
+        stake_pool,
+        validator_list_storage,
+        stake_pool_deposit_authority,
+        stake_pool_withdraw_authority,
+        deposit_stake_address,
+        deposit_stake_withdraw_authority,
+        validator_stake_account,
+        reserve_stake_account,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        true,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn deposit_stake_internal(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_deposit_authority: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    deposit_stake_address: &Pubkey,
+    deposit_stake_withdraw_authority: &Pubkey,
+    validator_stake_account: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    deposit_authority_signs: bool,
+) -> Vec<Instruction> {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new(*validator_list_storage, false),
+        AccountMeta::new_readonly(*stake_pool_deposit_authority, deposit_authority_signs),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*deposit_stake_address, false),
+        AccountMeta::new(*validator_stake_account, false),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*pool_tokens_to, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*referrer_pool_tokens_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+    ];
+    vec![
+        authorize(
+            deposit_stake_address,
+            deposit_stake_withdraw_authority,
+            stake_pool_deposit_authority,
+            StakeAuthorize::Staker,
+            None,
+        ),
+        authorize(
+            deposit_stake_address,
+            deposit_stake_withdraw_authority,
+            stake_pool_deposit_authority,
+            StakeAuthorize::Withdrawer,
+            None,
+        ),
+        Instruction {
+            program_id: *program_id,
+            accounts,
+            data: spl_stake_pool_legacy::instruction::StakePoolInstruction::DepositStake
+                .try_to_vec()
+                .unwrap(),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn deposit_stake_matches_program() {
+        let [
+            program_id,
+            pool,
+            list,
+            withdraw,
+            stake,
+            stake_authority,
+            validator_stake,
+            reserve,
+            to,
+            fees,
+            referrer,
+            mint,
+            token,
+        ] = keys();
+        let instructions = deposit_stake(
+            &program_id,
+            &pool,
+            &list,
+            &withdraw,
+            &stake,
+            &stake_authority,
+            &validator_stake,
+            &reserve,
+            &to,
+            &fees,
+            &referrer,
+            &mint,
+            &token,
+        );
+        let expected = spl_stake_pool_legacy::instruction::deposit_stake(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&stake),
+            &legacy_pubkey(&stake_authority),
+            &legacy_pubkey(&validator_stake),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&referrer),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+        );
+        assert_eq!(instructions.len(), expected.len());
+        for (instruction, expected) in instructions.iter().zip(expected.iter()) {
+            assert_matches_program(instruction, expected);
+        }
+    }
+
+    #[test]
+    fn deposit_stake_with_authority_matches_program() {
+        let [
+            program_id,
+            pool,
+            list,
+            deposit,
+            withdraw,
+            stake,
+            stake_authority,
+            validator_stake,
+            reserve,
+            to,
+            fees,
+            referrer,
+            mint,
+            token,
+        ] = keys();
+        let instructions = deposit_stake_with_authority(
+            &program_id,
+            &pool,
+            &list,
+            &deposit,
+            &withdraw,
+            &stake,
+            &stake_authority,
+            &validator_stake,
+            &reserve,
+            &to,
+            &fees,
+            &referrer,
+            &mint,
+            &token,
+        );
+        let expected = spl_stake_pool_legacy::instruction::deposit_stake_with_authority(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&deposit),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&stake),
+            &legacy_pubkey(&stake_authority),
+            &legacy_pubkey(&validator_stake),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&referrer),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+        );
+        assert_eq!(instructions.len(), expected.len());
+        for (instruction, expected) in instructions.iter().zip(expected.iter()) {
+            assert_matches_program(instruction, expected);
+        }
+    }
+}
@@ -4,9 +4,10 @@ use solana_sdk::{
     pubkey::Pubkey,
     sysvar,
 };
-use spl_stake_pool::{
-    find_stake_program_address, find_transient_stake_program_address,
-    find_withdraw_authority_program_address, state::StakePool,
+use spl_stake_pool::state::StakePool;
+
+use crate::address::{
+    find_transient_stake_program_address, find_withdraw_authority_program_address,
 };
 
 pub fn increase_validator_stake_with_vote(
@@ -25,8 +26,6 @@ pub fn increase_validator_stake_with_vote(
         stake_pool_address,
         transient_stake_seed,
     );
-    let (validator_stake_address, _) =
-        find_stake_program_address(program_id, vote_account_address, stake_pool_address, None);
 
     increase_validator_stake(
         program_id,
@@ -36,7 +35,7 @@ pub fn increase_validator_stake_with_vote(
         &stake_pool.validator_list,
         &stake_pool.reserve_stake,
         &transient_stake_address,
-        &validator_stake_address,
+        vote_account_address,
         lamports,
         transient_stake_seed,
     )
@@ -82,3 +81,82 @@ pub fn increase_validator_stake(
         .unwrap(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn increase_validator_stake_matches_program() {
+        let [
+            program_id,
+            pool,
+            staker,
+            withdraw,
+            list,
+            reserve,
+            transient_stake,
+            vote,
+        ] = keys();
+        let instruction = increase_validator_stake(
+            &program_id,
+            &pool,
+            &staker,
+            &withdraw,
+            &list,
+            &reserve,
+            &transient_stake,
+            &vote,
+            3_000_000_000,
+            9,
+        );
+        let expected = spl_stake_pool_legacy::instruction::increase_validator_stake(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&staker),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&transient_stake),
+            &legacy_pubkey(&vote),
+            3_000_000_000,
+            9,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn increase_validator_stake_with_vote_matches_program() {
+        let [program_id, pool, vote] = keys();
+        let stake_pool = StakePool {
+            staker: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let instruction = increase_validator_stake_with_vote(
+            &program_id,
+            &stake_pool,
+            &pool,
+            &vote,
+            3_000_000_000,
+            9,
+        );
+        let legacy_stake_pool = spl_stake_pool_legacy::state::StakePool {
+            staker: legacy_pubkey(&stake_pool.staker),
+            validator_list: legacy_pubkey(&stake_pool.validator_list),
+            reserve_stake: legacy_pubkey(&stake_pool.reserve_stake),
+            ..Default::default()
+        };
+        let expected = spl_stake_pool_legacy::instruction::increase_validator_stake_with_vote(
+            &legacy_pubkey(&program_id),
+            &legacy_stake_pool,
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&vote),
+            3_000_000_000,
+            9,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,130 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_stake_pool::state::Fee;
+
+use super::legacy;
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    staker: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    validator_list: &Pubkey,
+    reserve_stake: &Pubkey,
+    pool_mint: &Pubkey,
+    manager_pool_account: &Pubkey,
+    token_program_id: &Pubkey,
+    deposit_authority: Option<Pubkey>,
+    fee: Fee,
+    withdrawal_fee: Fee,
+    deposit_fee: Fee,
+    referral_fee: u8,
+    max_validators: u32,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+        AccountMeta::new_readonly(*staker, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*validator_list, false),
+        AccountMeta::new_readonly(*reserve_stake, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*manager_pool_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(deposit_authority) = deposit_authority {
+        accounts.push(AccountMeta::new_readonly(deposit_authority, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::Initialize {
+            fee: legacy::fee(&fee),
+            withdrawal_fee: legacy::fee(&withdrawal_fee),
+            deposit_fee: legacy::fee(&deposit_fee),
+            referral_fee,
+            max_validators,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn initialize_matches_program() {
+        let [
+            program_id,
+            pool,
+            manager,
+            staker,
+            withdraw,
+            list,
+            reserve,
+            mint,
+            fees,
+            token,
+            deposit,
+        ] = keys();
+        let fee = Fee {
+            denominator: 100,
+            numerator: 3,
+        };
+        let withdrawal_fee = Fee {
+            denominator: 1000,
+            numerator: 1,
+        };
+        let deposit_fee = Fee {
+            denominator: 10_000,
+            numerator: 5,
+        };
+        for deposit_authority in [None, Some(deposit)] {
+            let instruction = initialize(
+                &program_id,
+                &pool,
+                &manager,
+                &staker,
+                &withdraw,
+                &list,
+                &reserve,
+                &mint,
+                &fees,
+                &token,
+                deposit_authority,
+                fee,
+                withdrawal_fee,
+                deposit_fee,
+                20,
+                1000,
+            );
+            let expected = spl_stake_pool_legacy::instruction::initialize(
+                &legacy_pubkey(&program_id),
+                &legacy_pubkey(&pool),
+                &legacy_pubkey(&manager),
+                &legacy_pubkey(&staker),
+                &legacy_pubkey(&withdraw),
+                &legacy_pubkey(&list),
+                &legacy_pubkey(&reserve),
+                &legacy_pubkey(&mint),
+                &legacy_pubkey(&fees),
+                &legacy_pubkey(&token),
+                deposit_authority.as_ref().map(legacy_pubkey),
+                legacy::fee(&fee),
+                legacy::fee(&withdrawal_fee),
+                legacy::fee(&deposit_fee),
+                20,
+                1000,
+            );
+            assert_matches_program(&instruction, &expected);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Conversions to the types of the legacy program, whose borsh layout the
+//! builders encode
+
+use solana_sdk::pubkey::Pubkey;
+use spl_stake_pool::{
+    instruction::{FundingType, PreferredValidatorType},
+    state::{Fee, FeeType},
+};
+use spl_stake_pool_legacy::{
+    instruction::{
+        FundingType as LegacyFundingType, PreferredValidatorType as LegacyPreferredValidatorType,
+    },
+    solana_program::pubkey::Pubkey as LegacyPubkey,
+    state::{Fee as LegacyFee, FeeType as LegacyFeeType},
+};
+
+pub(crate) fn pubkey(pubkey: &Pubkey) -> LegacyPubkey {
+    LegacyPubkey::new_from_array(pubkey.to_bytes())
+}
+
+pub(crate) fn fee(fee: &Fee) -> LegacyFee {
+    LegacyFee {
+        denominator: fee.denominator,
+        numerator: fee.numerator,
+    }
+}
+
+pub(crate) fn fee_type(fee_type: &FeeType) -> LegacyFeeType {
+    match fee_type {
+        FeeType::SolReferral(pct) => LegacyFeeType::SolReferral(*pct),
+        FeeType::StakeReferral(pct) => LegacyFeeType::StakeReferral(*pct),
+        FeeType::Epoch(value) => LegacyFeeType::Epoch(fee(value)),
+        FeeType::StakeWithdrawal(value) => LegacyFeeType::StakeWithdrawal(fee(value)),
+        FeeType::SolDeposit(value) => LegacyFeeType::SolDeposit(fee(value)),
+        FeeType::StakeDeposit(value) => LegacyFeeType::StakeDeposit(fee(value)),
+        FeeType::SolWithdrawal(value) => LegacyFeeType::SolWithdrawal(fee(value)),
+    }
+}
+
+pub(crate) fn preferred_validator_type(
+    validator_type: &PreferredValidatorType,
+) -> LegacyPreferredValidatorType {
+    match validator_type {
+        PreferredValidatorType::Deposit => LegacyPreferredValidatorType::Deposit,
+        PreferredValidatorType::Withdraw => LegacyPreferredValidatorType::Withdraw,
+    }
+}
+
+pub(crate) fn funding_type(funding_type: &FundingType) -> LegacyFundingType {
+    match funding_type {
+        FundingType::StakeDeposit => LegacyFundingType::StakeDeposit,
+        FundingType::SolDeposit => LegacyFundingType::SolDeposit,
+        FundingType::SolWithdraw => LegacyFundingType::SolWithdraw,
+    }
+}
@@ -1,2 +1,18 @@
 pub mod add_validator;
+pub mod decrease_validator_stake;
+pub mod deposit_sol;
+pub mod deposit_stake;
 pub mod increase_validator_stake;
+pub mod initialize;
+mod legacy;
+pub mod remove_validator;
+pub mod set_fee;
+pub mod set_funding_authority;
+pub mod set_manager;
+pub mod set_preferred_validator;
+pub mod set_staker;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod update_stake_pool;
+pub mod withdraw_sol;
+pub mod withdraw_stake;
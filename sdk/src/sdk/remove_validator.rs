@@ -0,0 +1,171 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_stake_pool::state::StakePool;
+
+use crate::address::{
+    find_stake_program_address, find_transient_stake_program_address,
+    find_withdraw_authority_program_address,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn remove_validator_from_pool_with_vote(
+    program_id: &Pubkey,
+    stake_pool: &StakePool,
+    stake_pool_address: &Pubkey,
+    vote_account_address: &Pubkey,
+    new_stake_account_authority: &Pubkey,
+    transient_stake_seed: u64,
+    destination_stake_address: &Pubkey,
+) -> Instruction {
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(program_id, stake_pool_address).0;
+    let (stake_account_address, _) =
+        find_stake_program_address(program_id, vote_account_address, stake_pool_address, None);
+    let (transient_stake_account, _) = find_transient_stake_program_address(
+        program_id,
+        vote_account_address,
+        stake_pool_address,
+        transient_stake_seed,
+    );
+    remove_validator_from_pool(
+        program_id,
+        stake_pool_address,
+        &stake_pool.staker,
+        &pool_withdraw_authority,
+        new_stake_account_authority,
+        &stake_pool.validator_list,
+        &stake_account_address,
+        &transient_stake_account,
+        destination_stake_address,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn remove_validator_from_pool(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    staker: &Pubkey,
+    stake_pool_withdraw: &Pubkey,
+    new_stake_authority: &Pubkey,
+    validator_list: &Pubkey,
+    stake_account: &Pubkey,
+    transient_stake_account: &Pubkey,
+    destination_stake_account: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*staker, true),
+        AccountMeta::new_readonly(*stake_pool_withdraw, false),
+        AccountMeta::new_readonly(*new_stake_authority, false),
+        AccountMeta::new(*validator_list, false),
+        AccountMeta::new(*stake_account, false),
+        AccountMeta::new_readonly(*transient_stake_account, false),
+        AccountMeta::new(*destination_stake_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::RemoveValidatorFromPool
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn remove_validator_from_pool_matches_program() {
+        let [
+            program_id,
+            pool,
+            staker,
+            withdraw,
+            authority,
+            list,
+            stake,
+            transient,
+            destination,
+        ] = keys();
+        let instruction = remove_validator_from_pool(
+            &program_id,
+            &pool,
+            &staker,
+            &withdraw,
+            &authority,
+            &list,
+            &stake,
+            &transient,
+            &destination,
+        );
+        let expected = spl_stake_pool_legacy::instruction::remove_validator_from_pool(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&staker),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&authority),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&stake),
+            &legacy_pubkey(&transient),
+            &legacy_pubkey(&destination),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn remove_validator_from_pool_with_vote_matches_program() {
+        let [program_id, pool, vote, authority, destination] = keys();
+        let stake_pool = StakePool {
+            staker: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let instruction = remove_validator_from_pool_with_vote(
+            &program_id,
+            &stake_pool,
+            &pool,
+            &vote,
+            &authority,
+            7,
+            &destination,
+        );
+        let legacy_program_id = legacy_pubkey(&program_id);
+        let legacy_pool = legacy_pubkey(&pool);
+        let legacy_vote = legacy_pubkey(&vote);
+        let expected = spl_stake_pool_legacy::instruction::remove_validator_from_pool(
+            &legacy_program_id,
+            &legacy_pool,
+            &legacy_pubkey(&stake_pool.staker),
+            &spl_stake_pool_legacy::find_withdraw_authority_program_address(
+                &legacy_program_id,
+                &legacy_pool,
+            )
+            .0,
+            &legacy_pubkey(&authority),
+            &legacy_pubkey(&stake_pool.validator_list),
+            &spl_stake_pool_legacy::find_stake_program_address(
+                &legacy_program_id,
+                &legacy_vote,
+                &legacy_pool,
+            )
+            .0,
+            &spl_stake_pool_legacy::find_transient_stake_program_address(
+                &legacy_program_id,
+                &legacy_vote,
+                &legacy_pool,
+                7,
+            )
+            .0,
+            &legacy_pubkey(&destination),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,64 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_stake_pool::state::FeeType;
+
+use super::legacy;
+
+pub fn set_fee(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    fee: FeeType,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::SetFee {
+            fee: legacy::fee_type(&fee),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_stake_pool::state::Fee;
+
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn set_fee_matches_program() {
+        let [program_id, pool, manager] = keys();
+        let fee = Fee {
+            denominator: 1000,
+            numerator: 3,
+        };
+        for fee_type in [
+            FeeType::SolReferral(50),
+            FeeType::StakeReferral(20),
+            FeeType::Epoch(fee),
+            FeeType::StakeWithdrawal(fee),
+            FeeType::SolDeposit(fee),
+            FeeType::StakeDeposit(fee),
+            FeeType::SolWithdrawal(fee),
+        ] {
+            let instruction = set_fee(&program_id, &pool, &manager, fee_type.clone());
+            let expected = spl_stake_pool_legacy::instruction::set_fee(
+                &legacy_pubkey(&program_id),
+                &legacy_pubkey(&pool),
+                &legacy_pubkey(&manager),
+                legacy::fee_type(&fee_type),
+            );
+            assert_matches_program(&instruction, &expected);
+        }
+    }
+}
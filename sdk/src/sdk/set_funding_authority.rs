@@ -0,0 +1,67 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_stake_pool::instruction::FundingType;
+
+use super::legacy;
+
+/// Creates a `SetFundingAuthority` instruction, `None` removes the authority
+pub fn set_funding_authority(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    new_funding_authority: Option<&Pubkey>,
+    funding_type: FundingType,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+    ];
+    if let Some(authority) = new_funding_authority {
+        accounts.push(AccountMeta::new_readonly(*authority, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::SetFundingAuthority(
+            legacy::funding_type(&funding_type),
+        )
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn set_funding_authority_matches_program() {
+        let [program_id, pool, manager, authority] = keys();
+        for (funding_type, new_authority) in [
+            (FundingType::StakeDeposit, Some(&authority)),
+            (FundingType::SolDeposit, None),
+            (FundingType::SolWithdraw, Some(&authority)),
+        ] {
+            let instruction = set_funding_authority(
+                &program_id,
+                &pool,
+                &manager,
+                new_authority,
+                funding_type.clone(),
+            );
+            let legacy_authority = new_authority.map(legacy_pubkey);
+            let expected = spl_stake_pool_legacy::instruction::set_funding_authority(
+                &legacy_pubkey(&program_id),
+                &legacy_pubkey(&pool),
+                &legacy_pubkey(&manager),
+                legacy_authority.as_ref(),
+                legacy::funding_type(&funding_type),
+            );
+            assert_matches_program(&instruction, &expected);
+        }
+    }
+}
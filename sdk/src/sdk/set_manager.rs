@@ -0,0 +1,47 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+pub fn set_manager(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    new_manager: &Pubkey,
+    new_fee_receiver: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+        AccountMeta::new_readonly(*new_manager, true),
+        AccountMeta::new_readonly(*new_fee_receiver, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::SetManager
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn set_manager_matches_program() {
+        let [program_id, pool, manager, new_manager, fee_receiver] = keys();
+        let instruction = set_manager(&program_id, &pool, &manager, &new_manager, &fee_receiver);
+        let expected = spl_stake_pool_legacy::instruction::set_manager(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&manager),
+            &legacy_pubkey(&new_manager),
+            &legacy_pubkey(&fee_receiver),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,65 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_stake_pool::instruction::PreferredValidatorType;
+
+use super::legacy;
+
+pub fn set_preferred_validator(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    staker: &Pubkey,
+    validator_list_address: &Pubkey,
+    validator_type: PreferredValidatorType,
+    validator_vote_address: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*stake_pool_address, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new_readonly(*validator_list_address, false),
+        ],
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::SetPreferredValidator {
+            validator_type: legacy::preferred_validator_type(&validator_type),
+            validator_vote_address: validator_vote_address.as_ref().map(legacy::pubkey),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn set_preferred_validator_matches_program() {
+        let [program_id, pool, staker, list, vote] = keys();
+        for (validator_type, vote_address) in [
+            (PreferredValidatorType::Deposit, Some(vote)),
+            (PreferredValidatorType::Withdraw, None),
+        ] {
+            let instruction = set_preferred_validator(
+                &program_id,
+                &pool,
+                &staker,
+                &list,
+                validator_type.clone(),
+                vote_address,
+            );
+            let expected = spl_stake_pool_legacy::instruction::set_preferred_validator(
+                &legacy_pubkey(&program_id),
+                &legacy_pubkey(&pool),
+                &legacy_pubkey(&staker),
+                &legacy_pubkey(&list),
+                legacy::preferred_validator_type(&validator_type),
+                vote_address.as_ref().map(legacy_pubkey),
+            );
+            assert_matches_program(&instruction, &expected);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+pub fn set_staker(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    set_staker_authority: &Pubkey,
+    new_staker: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*set_staker_authority, true),
+        AccountMeta::new_readonly(*new_staker, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::SetStaker
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn set_staker_matches_program() {
+        let [program_id, pool, authority, new_staker] = keys();
+        let instruction = set_staker(&program_id, &pool, &authority, &new_staker);
+        let expected = spl_stake_pool_legacy::instruction::set_staker(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&authority),
+            &legacy_pubkey(&new_staker),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
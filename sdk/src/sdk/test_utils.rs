@@ -0,0 +1,36 @@
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_stake_pool_legacy::solana_program::instruction::Instruction as LegacyInstruction;
+
+pub(crate) use super::legacy::pubkey as legacy_pubkey;
+
+/// Unique addresses to pass to the builders
+pub(crate) fn keys<const N: usize>() -> [Pubkey; N] {
+    [(); N].map(|_| Pubkey::new_unique())
+}
+
+/// Checks that `instruction` has the data and account metas of the
+/// instruction built by the program
+pub(crate) fn assert_matches_program(instruction: &Instruction, expected: &LegacyInstruction) {
+    assert_eq!(
+        instruction.program_id.to_bytes(),
+        expected.program_id.to_bytes()
+    );
+    assert_eq!(instruction.data, expected.data);
+    assert_eq!(instruction.accounts.len(), expected.accounts.len());
+    for (position, (meta, expected_meta)) in instruction
+        .accounts
+        .iter()
+        .zip(expected.accounts.iter())
+        .enumerate()
+    {
+        assert_eq!(
+            (meta.pubkey.to_bytes(), meta.is_signer, meta.is_writable),
+            (
+                expected_meta.pubkey.to_bytes(),
+                expected_meta.is_signer,
+                expected_meta.is_writable
+            ),
+            "account {position}"
+        );
+    }
+}
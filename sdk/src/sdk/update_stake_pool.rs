@@ -0,0 +1,258 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_stake_pool::state::{StakePool, ValidatorList};
+
+use crate::address::{
+    MAX_VALIDATORS_TO_UPDATE, find_stake_program_address, find_transient_stake_program_address,
+    find_withdraw_authority_program_address,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_validator_list_balance(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    validator_list_address: &Pubkey,
+    reserve_stake: &Pubkey,
+    validator_list: &ValidatorList,
+    validator_vote_accounts: &[Pubkey],
+    start_index: u32,
+    no_merge: bool,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*validator_list_address, false),
+        AccountMeta::new(*reserve_stake, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+    ];
+    for vote_account_address in validator_vote_accounts {
+        let Some(validator_stake_info) = validator_list.find(vote_account_address) else {
+            continue;
+        };
+        let (validator_stake_account, _) =
+            find_stake_program_address(program_id, vote_account_address, stake_pool, None);
+        let (transient_stake_account, _) = find_transient_stake_program_address(
+            program_id,
+            vote_account_address,
+            stake_pool,
+            validator_stake_info.transient_seed_suffix.into(),
+        );
+        accounts.push(AccountMeta::new(validator_stake_account, false));
+        accounts.push(AccountMeta::new(transient_stake_account, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data:
+            spl_stake_pool_legacy::instruction::StakePoolInstruction::UpdateValidatorListBalance {
+                start_index,
+                no_merge,
+            }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates the `UpdateValidatorListBalance` instruction for the chunk of
+/// `MAX_VALIDATORS_TO_UPDATE` validators starting at `start_index`
+pub fn update_validator_list_balance_chunk(
+    program_id: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+    stake_pool_address: &Pubkey,
+    start_index: usize,
+    no_merge: bool,
+) -> Instruction {
+    let withdraw_authority =
+        find_withdraw_authority_program_address(program_id, stake_pool_address).0;
+    let end_index = (start_index + MAX_VALIDATORS_TO_UPDATE).min(validator_list.validators.len());
+    let vote_accounts: Vec<Pubkey> = validator_list.validators[start_index..end_index]
+        .iter()
+        .map(|validator| validator.vote_account_address)
+        .collect();
+    update_validator_list_balance(
+        program_id,
+        stake_pool_address,
+        &withdraw_authority,
+        &stake_pool.validator_list,
+        &stake_pool.reserve_stake,
+        validator_list,
+        &vote_accounts,
+        start_index as u32,
+        no_merge,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_stake_pool_balance(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    withdraw_authority: &Pubkey,
+    validator_list_storage: &Pubkey,
+    reserve_stake: &Pubkey,
+    manager_fee_account: &Pubkey,
+    stake_pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*withdraw_authority, false),
+        AccountMeta::new(*validator_list_storage, false),
+        AccountMeta::new_readonly(*reserve_stake, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*stake_pool_mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::UpdateStakePoolBalance
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn update_stake_pool_balance_with_pool(
+    program_id: &Pubkey,
+    stake_pool: &StakePool,
+    stake_pool_address: &Pubkey,
+) -> Instruction {
+    let withdraw_authority =
+        find_withdraw_authority_program_address(program_id, stake_pool_address).0;
+    update_stake_pool_balance(
+        program_id,
+        stake_pool_address,
+        &withdraw_authority,
+        &stake_pool.validator_list,
+        &stake_pool.reserve_stake,
+        &stake_pool.manager_fee_account,
+        &stake_pool.pool_mint,
+        &stake_pool.token_program_id,
+    )
+}
+
+pub fn cleanup_removed_validator_entries(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new(*validator_list_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data:
+            spl_stake_pool_legacy::instruction::StakePoolInstruction::CleanupRemovedValidatorEntries
+                .try_to_vec()
+                .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_stake_pool::state::{StakeStatus, ValidatorStakeInfo};
+
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn update_validator_list_balance_matches_program() {
+        let [program_id, pool, withdraw, list_address, reserve] = keys();
+        let mut validator_list = ValidatorList::new(3);
+        let mut legacy_validator_list = spl_stake_pool_legacy::state::ValidatorList::new(3);
+        for (index, (validator, legacy_validator)) in validator_list
+            .validators
+            .iter_mut()
+            .zip(legacy_validator_list.validators.iter_mut())
+            .enumerate()
+        {
+            *validator = ValidatorStakeInfo {
+                transient_seed_suffix: (index as u64 * 3).into(),
+                status: StakeStatus::Active.into(),
+                vote_account_address: Pubkey::new_unique(),
+                ..ValidatorStakeInfo::default()
+            };
+            legacy_validator.transient_seed_suffix_start = index as u64 * 3;
+            legacy_validator.vote_account_address = legacy_pubkey(&validator.vote_account_address);
+        }
+        let vote_accounts: Vec<Pubkey> = validator_list
+            .validators
+            .iter()
+            .map(|validator| validator.vote_account_address)
+            .collect();
+        let legacy_vote_accounts: Vec<_> = vote_accounts.iter().map(legacy_pubkey).collect();
+
+        for no_merge in [false, true] {
+            let instruction = update_validator_list_balance(
+                &program_id,
+                &pool,
+                &withdraw,
+                &list_address,
+                &reserve,
+                &validator_list,
+                &vote_accounts,
+                5,
+                no_merge,
+            );
+            let expected = spl_stake_pool_legacy::instruction::update_validator_list_balance(
+                &legacy_pubkey(&program_id),
+                &legacy_pubkey(&pool),
+                &legacy_pubkey(&withdraw),
+                &legacy_pubkey(&list_address),
+                &legacy_pubkey(&reserve),
+                &legacy_validator_list,
+                &legacy_vote_accounts,
+                5,
+                no_merge,
+            );
+            assert_matches_program(&instruction, &expected);
+        }
+    }
+
+    #[test]
+    fn update_stake_pool_balance_matches_program() {
+        let [program_id, pool, withdraw, list, reserve, fees, mint, token] = keys();
+        let instruction = update_stake_pool_balance(
+            &program_id,
+            &pool,
+            &withdraw,
+            &list,
+            &reserve,
+            &fees,
+            &mint,
+            &token,
+        );
+        let expected = spl_stake_pool_legacy::instruction::update_stake_pool_balance(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn cleanup_removed_validator_entries_matches_program() {
+        let [program_id, pool, list] = keys();
+        let instruction = cleanup_removed_validator_entries(&program_id, &pool, &list);
+        let expected = spl_stake_pool_legacy::instruction::cleanup_removed_validator_entries(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&list),
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
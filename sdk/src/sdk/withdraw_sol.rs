@@ -0,0 +1,174 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_sol(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool_tokens_from: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    pool_tokens: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new_readonly(*user_transfer_authority, true),
+        AccountMeta::new(*pool_tokens_from, false),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*lamports_to, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::WithdrawSol(pool_tokens)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Same as `withdraw_sol`, for pools where the SOL withdraw authority must
+/// sign
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_sol_with_authority(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    sol_withdraw_authority: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool_tokens_from: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    pool_tokens: u64,
+) -> Instruction {
+    let mut instruction = withdraw_sol(
+        program_id,
+        stake_pool,
+        stake_pool_withdraw_authority,
+        user_transfer_authority,
+        pool_tokens_from,
+        reserve_stake_account,
+        lamports_to,
+        manager_fee_account,
+        pool_mint,
+        token_program_id,
+        pool_tokens,
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*sol_withdraw_authority, true));
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn withdraw_sol_matches_program() {
+        let [
+            program_id,
+            pool,
+            withdraw,
+            transfer_authority,
+            from,
+            reserve,
+            to,
+            fees,
+            mint,
+            token,
+        ] = keys();
+        let instruction = withdraw_sol(
+            &program_id,
+            &pool,
+            &withdraw,
+            &transfer_authority,
+            &from,
+            &reserve,
+            &to,
+            &fees,
+            &mint,
+            &token,
+            1_000,
+        );
+        let expected = spl_stake_pool_legacy::instruction::withdraw_sol(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&transfer_authority),
+            &legacy_pubkey(&from),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+            1_000,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+
+    #[test]
+    fn withdraw_sol_with_authority_matches_program() {
+        let [
+            program_id,
+            pool,
+            authority,
+            withdraw,
+            transfer_authority,
+            from,
+            reserve,
+            to,
+            fees,
+            mint,
+            token,
+        ] = keys();
+        let instruction = withdraw_sol_with_authority(
+            &program_id,
+            &pool,
+            &authority,
+            &withdraw,
+            &transfer_authority,
+            &from,
+            &reserve,
+            &to,
+            &fees,
+            &mint,
+            &token,
+            1_000,
+        );
+        let expected = spl_stake_pool_legacy::instruction::withdraw_sol_with_authority(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&authority),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&transfer_authority),
+            &legacy_pubkey(&from),
+            &legacy_pubkey(&reserve),
+            &legacy_pubkey(&to),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+            1_000,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,101 @@
+use borsh_legacy::BorshSerialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_stake(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_withdraw: &Pubkey,
+    stake_to_split: &Pubkey,
+    stake_to_receive: &Pubkey,
+    user_stake_authority: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    user_pool_token_account: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new(*validator_list_storage, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw, false),
+        AccountMeta::new(*stake_to_split, false),
+        AccountMeta::new(*stake_to_receive, false),
+        AccountMeta::new_readonly(*user_stake_authority, false),
+        AccountMeta::new_readonly(*user_transfer_authority, true),
+        AccountMeta::new(*user_pool_token_account, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(solana_stake_interface::program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: spl_stake_pool_legacy::instruction::StakePoolInstruction::WithdrawStake(amount)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::test_utils::{assert_matches_program, keys, legacy_pubkey};
+
+    #[test]
+    fn withdraw_stake_matches_program() {
+        let [
+            program_id,
+            pool,
+            list,
+            withdraw,
+            split,
+            receive,
+            stake_authority,
+            transfer_authority,
+            from,
+            fees,
+            mint,
+            token,
+        ] = keys();
+        let instruction = withdraw_stake(
+            &program_id,
+            &pool,
+            &list,
+            &withdraw,
+            &split,
+            &receive,
+            &stake_authority,
+            &transfer_authority,
+            &from,
+            &fees,
+            &mint,
+            &token,
+            1_234,
+        );
+        let expected = spl_stake_pool_legacy::instruction::withdraw_stake(
+            &legacy_pubkey(&program_id),
+            &legacy_pubkey(&pool),
+            &legacy_pubkey(&list),
+            &legacy_pubkey(&withdraw),
+            &legacy_pubkey(&split),
+            &legacy_pubkey(&receive),
+            &legacy_pubkey(&stake_authority),
+            &legacy_pubkey(&transfer_authority),
+            &legacy_pubkey(&from),
+            &legacy_pubkey(&fees),
+            &legacy_pubkey(&mint),
+            &legacy_pubkey(&token),
+            1_234,
+        );
+        assert_matches_program(&instruction, &expected);
+    }
+}
@@ -0,0 +1,596 @@
+//! Discovery and classification of the stake accounts of an authority.
+//!
+//! Stake accounts are found with `getProgramAccounts` on the stake program,
+//! filtering on the staker or withdrawer of their `Meta`. Classification uses
+//! the clock and stake history sysvars, so warmup and cooldown over several
+//! epochs are reported as the runtime computes them.
+
+use std::collections::BTreeMap;
+
+use futures::try_join;
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    clock::{Clock, Epoch},
+    epoch_schedule::EpochSchedule,
+    pubkey,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_stake_interface::{
+    stake_history::StakeHistory,
+    state::{StakeAuthorize, StakeStateV2},
+};
+use spl_stake_pool::state::{StakePool, StakeStatus, ValidatorList};
+
+use crate::client::ClientError;
+
+/// Byte offset of `Meta::authorized::staker` in a stake account
+pub const STAKER_OFFSET: usize = 12;
+
+/// Byte offset of `Meta::authorized::withdrawer` in a stake account
+pub const WITHDRAWER_OFFSET: usize = 44;
+
+/// Feature lowering the stake warmup and cooldown rate from 25% to 9%
+const REDUCE_STAKE_WARMUP_COOLDOWN: Pubkey =
+    pubkey!("GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj");
+
+/// State of a stake account at the current epoch
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StakeAccountStatus {
+    /// Initialized but never delegated
+    Initialized,
+    /// Delegated, part of the stake is still warming up
+    Activating,
+    /// Delegated, the whole stake is effective
+    Active,
+    /// Deactivated, part of the stake is still cooling down
+    Deactivating,
+    /// Deactivated and fully cooled down, or never effective
+    Inactive,
+    /// The lockup is in force, whatever the delegation
+    Locked,
+}
+
+/// Why a stake account cannot be deposited into a stake pool
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DepositError {
+    /// The stake account is not delegated
+    NotDelegated,
+    /// The stake is not fully active, so it cannot be merged
+    NotActive(StakeAccountStatus),
+    /// The lockup is in force, so it cannot be merged
+    Locked,
+    /// The delegated vote account is not in the validator list
+    ValidatorNotInPool(Pubkey),
+    /// The delegated validator is being removed from the pool
+    ValidatorRemoving(Pubkey),
+    /// The pool only accepts stake deposits to its preferred validator
+    NotPreferredValidator(Pubkey),
+}
+
+/// Clock, stake history and warmup rate of the cluster
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StakeContext {
+    pub clock: Clock,
+    pub stake_history: StakeHistory,
+    pub new_rate_activation_epoch: Option<Epoch>,
+}
+
+impl StakeContext {
+    /// Classifies a stake account, or returns `None` if it is uninitialized
+    pub fn stake_status(&self, stake_state: &StakeStateV2) -> Option<StakeAccountStatus> {
+        let meta = stake_state.meta()?;
+        if meta.lockup.is_in_force(&self.clock, None) {
+            return Some(StakeAccountStatus::Locked);
+        }
+        let Some(delegation) = stake_state.delegation() else {
+            return Some(StakeAccountStatus::Initialized);
+        };
+        let activation = delegation.stake_activating_and_deactivating(
+            self.clock.epoch,
+            &self.stake_history,
+            self.new_rate_activation_epoch,
+        );
+        Some(if activation.deactivating > 0 {
+            StakeAccountStatus::Deactivating
+        } else if activation.activating > 0 {
+            StakeAccountStatus::Activating
+        } else if activation.effective > 0 {
+            StakeAccountStatus::Active
+        } else {
+            StakeAccountStatus::Inactive
+        })
+    }
+}
+
+/// Stake account found for an authority
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeAccountInfo {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub stake_state: StakeStateV2,
+    pub status: StakeAccountStatus,
+}
+
+impl StakeAccountInfo {
+    /// Vote account the stake is delegated to
+    pub fn vote_account(&self) -> Option<Pubkey> {
+        self.stake_state
+            .delegation()
+            .map(|delegation| delegation.voter_pubkey)
+    }
+
+    pub fn is_staker(&self, authority: &Pubkey) -> bool {
+        self.stake_state
+            .meta()
+            .is_some_and(|meta| meta.authorized.staker == *authority)
+    }
+
+    pub fn is_withdrawer(&self, authority: &Pubkey) -> bool {
+        self.stake_state
+            .meta()
+            .is_some_and(|meta| meta.authorized.withdrawer == *authority)
+    }
+
+    /// Checks that `DepositStake` can merge this account into the pool, and
+    /// returns the vote account it would be deposited to
+    pub fn check_deposit(
+        &self,
+        stake_pool: &StakePool,
+        validator_list: &ValidatorList,
+    ) -> Result<Pubkey, DepositError> {
+        let vote_account = self.vote_account().ok_or(DepositError::NotDelegated)?;
+        match self.status {
+            StakeAccountStatus::Active => {}
+            StakeAccountStatus::Locked => return Err(DepositError::Locked),
+            status => return Err(DepositError::NotActive(status)),
+        }
+        if let Some(preferred) = stake_pool.preferred_deposit_validator_vote_address
+            && preferred != vote_account
+        {
+            return Err(DepositError::NotPreferredValidator(preferred));
+        }
+        let validator = validator_list
+            .find(&vote_account)
+            .ok_or(DepositError::ValidatorNotInPool(vote_account))?;
+        if StakeStatus::try_from(validator.status) != Ok(StakeStatus::Active) {
+            return Err(DepositError::ValidatorRemoving(vote_account));
+        }
+        Ok(vote_account)
+    }
+}
+
+/// Addresses of the accounts [`StakeContext::from_accounts`] reads
+pub const STAKE_CONTEXT_ADDRESSES: [Pubkey; 4] = [
+    sysvar::clock::ID,
+    sysvar::stake_history::ID,
+    sysvar::epoch_schedule::ID,
+    REDUCE_STAKE_WARMUP_COOLDOWN,
+];
+
+impl StakeContext {
+    /// Decodes the accounts fetched at [`STAKE_CONTEXT_ADDRESSES`]
+    pub fn from_accounts(accounts: &[Option<Account>]) -> Result<Self, ClientError> {
+        let account = |index: usize| accounts.get(index).and_then(Option::as_ref);
+        let clock = deserialize_sysvar(&STAKE_CONTEXT_ADDRESSES[0], account(0))?;
+        let stake_history = deserialize_sysvar(&STAKE_CONTEXT_ADDRESSES[1], account(1))?;
+        let epoch_schedule: EpochSchedule =
+            deserialize_sysvar(&STAKE_CONTEXT_ADDRESSES[2], account(2))?;
+        // `Feature` is a bincode `Option<Slot>`
+        let new_rate_activation_epoch = account(3)
+            .and_then(|account| bincode::deserialize::<Option<u64>>(&account.data).ok())
+            .flatten()
+            .map(|slot| epoch_schedule.get_epoch(slot));
+        Ok(Self {
+            clock,
+            stake_history,
+            new_rate_activation_epoch,
+        })
+    }
+}
+
+/// Fetches the clock and stake history sysvars and the activation epoch of
+/// the new warmup rate
+pub async fn get_stake_context(rpc_client: &RpcClient) -> Result<StakeContext, ClientError> {
+    let accounts = rpc_client
+        .get_multiple_accounts(&STAKE_CONTEXT_ADDRESSES)
+        .await?;
+    StakeContext::from_accounts(&accounts)
+}
+
+fn deserialize_sysvar<T: serde::de::DeserializeOwned>(
+    address: &Pubkey,
+    account: Option<&Account>,
+) -> Result<T, ClientError> {
+    let account = account.ok_or_else(|| ClientError::InvalidAccount {
+        address: *address,
+        error: "account not found".to_string(),
+    })?;
+    bincode::deserialize(&account.data).map_err(|err| ClientError::InvalidAccount {
+        address: *address,
+        error: err.to_string(),
+    })
+}
+
+/// `getProgramAccounts` filter on the staker or the withdrawer of stake
+/// accounts
+pub fn stake_authority_filter(
+    authority: &Pubkey,
+    stake_authorize: StakeAuthorize,
+) -> RpcFilterType {
+    let offset = match stake_authorize {
+        StakeAuthorize::Staker => STAKER_OFFSET,
+        StakeAuthorize::Withdrawer => WITHDRAWER_OFFSET,
+    };
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, authority.as_ref()))
+}
+
+/// Fetches every stake account where `authority` is the staker or the
+/// withdrawer, as selected by `stake_authorize`
+pub async fn get_stake_accounts_by_authority(
+    rpc_client: &RpcClient,
+    authority: &Pubkey,
+    stake_authorize: StakeAuthorize,
+) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+    let accounts = rpc_client
+        .get_program_accounts_with_config(
+            &solana_stake_interface::program::id(),
+            RpcProgramAccountsConfig {
+                filters: Some(vec![stake_authority_filter(authority, stake_authorize)]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(rpc_client.commitment()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .await?;
+    Ok(accounts)
+}
+
+/// Finds and classifies every stake account where `authority` is the staker
+/// or the withdrawer, sorted by address. Accounts that do not parse as an
+/// initialized stake account are skipped.
+pub async fn find_stake_accounts(
+    rpc_client: &RpcClient,
+    authority: &Pubkey,
+) -> Result<Vec<StakeAccountInfo>, ClientError> {
+    let (staker_accounts, withdrawer_accounts, context) = try_join!(
+        get_stake_accounts_by_authority(rpc_client, authority, StakeAuthorize::Staker),
+        get_stake_accounts_by_authority(rpc_client, authority, StakeAuthorize::Withdrawer),
+        get_stake_context(rpc_client),
+    )?;
+    Ok(classify_stake_accounts(
+        &context,
+        staker_accounts.into_iter().chain(withdrawer_accounts),
+    ))
+}
+
+/// Parses and classifies stake accounts, dropping duplicates and sorting them
+/// by address
+pub fn classify_stake_accounts(
+    context: &StakeContext,
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+) -> Vec<StakeAccountInfo> {
+    let accounts: BTreeMap<Pubkey, Account> = accounts.into_iter().collect();
+    accounts
+        .into_iter()
+        .filter_map(|(address, account)| parse_stake_account(context, address, &account))
+        .collect()
+}
+
+/// Parses and classifies a stake account
+pub fn parse_stake_account(
+    context: &StakeContext,
+    address: Pubkey,
+    account: &Account,
+) -> Option<StakeAccountInfo> {
+    let stake_state: StakeStateV2 = bincode::deserialize(&account.data).ok()?;
+    let status = context.stake_status(&stake_state)?;
+    Some(StakeAccountInfo {
+        address,
+        lamports: account.lamports,
+        stake_state,
+        status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::{Value, json};
+    use solana_account_decoder::encode_ui_account;
+    use solana_rpc_client::mock_sender::MocksMap;
+    use solana_rpc_client_api::request::RpcRequest;
+    use solana_stake_interface::{
+        stake_flags::StakeFlags,
+        stake_history::StakeHistoryEntry,
+        state::{Authorized, Delegation, Lockup, Meta, Stake},
+    };
+    use spl_stake_pool::state::ValidatorStakeInfo;
+
+    use super::*;
+
+    const STAKE: u64 = 1_000_000_000;
+
+    fn context(epoch: Epoch) -> StakeContext {
+        let mut stake_history = StakeHistory::default();
+        // the cluster has far more stake than the test accounts, so every
+        // activation or deactivation completes within one epoch
+        for past_epoch in 0..epoch {
+            stake_history.add(
+                past_epoch,
+                StakeHistoryEntry {
+                    effective: 1_000 * STAKE,
+                    activating: STAKE,
+                    deactivating: STAKE,
+                },
+            );
+        }
+        StakeContext {
+            clock: Clock {
+                epoch,
+                unix_timestamp: 1_000,
+                ..Clock::default()
+            },
+            stake_history,
+            new_rate_activation_epoch: Some(0),
+        }
+    }
+
+    fn meta(authority: Pubkey, lockup: Lockup) -> Meta {
+        Meta {
+            authorized: Authorized {
+                staker: authority,
+                withdrawer: Pubkey::new_unique(),
+            },
+            lockup,
+            ..Meta::default()
+        }
+    }
+
+    fn delegated(
+        vote_account: Pubkey,
+        activation_epoch: Epoch,
+        deactivation_epoch: Epoch,
+    ) -> StakeStateV2 {
+        StakeStateV2::Stake(
+            meta(Pubkey::new_unique(), Lockup::default()),
+            Stake {
+                delegation: Delegation {
+                    voter_pubkey: vote_account,
+                    stake: STAKE,
+                    activation_epoch,
+                    deactivation_epoch,
+                    ..Delegation::default()
+                },
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        )
+    }
+
+    fn info(stake_state: StakeStateV2, context: &StakeContext) -> StakeAccountInfo {
+        StakeAccountInfo {
+            address: Pubkey::new_unique(),
+            lamports: STAKE,
+            status: context.stake_status(&stake_state).unwrap(),
+            stake_state,
+        }
+    }
+
+    #[test]
+    fn classifies_stake_accounts() {
+        let context = context(10);
+        let vote = Pubkey::new_unique();
+        let status = |stake_state: &StakeStateV2| context.stake_status(stake_state);
+        assert_eq!(status(&StakeStateV2::Uninitialized), None);
+        assert_eq!(
+            status(&StakeStateV2::Initialized(meta(
+                Pubkey::new_unique(),
+                Lockup::default()
+            ))),
+            Some(StakeAccountStatus::Initialized)
+        );
+        assert_eq!(
+            status(&delegated(vote, 10, u64::MAX)),
+            Some(StakeAccountStatus::Activating)
+        );
+        assert_eq!(
+            status(&delegated(vote, 5, u64::MAX)),
+            Some(StakeAccountStatus::Active)
+        );
+        assert_eq!(
+            status(&delegated(vote, 5, 10)),
+            Some(StakeAccountStatus::Deactivating)
+        );
+        assert_eq!(
+            status(&delegated(vote, 5, 8)),
+            Some(StakeAccountStatus::Inactive)
+        );
+
+        let lockup = Lockup {
+            epoch: 11,
+            ..Lockup::default()
+        };
+        assert_eq!(
+            status(&StakeStateV2::Initialized(meta(
+                Pubkey::new_unique(),
+                lockup
+            ))),
+            Some(StakeAccountStatus::Locked)
+        );
+        let expired = Lockup {
+            unix_timestamp: 999,
+            epoch: 10,
+            ..Lockup::default()
+        };
+        assert_eq!(
+            status(&StakeStateV2::Initialized(meta(
+                Pubkey::new_unique(),
+                expired
+            ))),
+            Some(StakeAccountStatus::Initialized)
+        );
+    }
+
+    #[test]
+    fn checks_deposits() {
+        let context = context(10);
+        let vote = Pubkey::new_unique();
+        let removing = Pubkey::new_unique();
+        let mut stake_pool = StakePool::default();
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = vec![
+            ValidatorStakeInfo {
+                vote_account_address: vote,
+                ..ValidatorStakeInfo::default()
+            },
+            ValidatorStakeInfo {
+                vote_account_address: removing,
+                status: StakeStatus::DeactivatingValidator.into(),
+                ..ValidatorStakeInfo::default()
+            },
+        ];
+        let check = |stake_state, stake_pool: &StakePool| {
+            info(stake_state, &context).check_deposit(stake_pool, &validator_list)
+        };
+
+        assert_eq!(check(delegated(vote, 5, u64::MAX), &stake_pool), Ok(vote));
+        assert_eq!(
+            check(
+                StakeStateV2::Initialized(meta(Pubkey::new_unique(), Lockup::default())),
+                &stake_pool
+            ),
+            Err(DepositError::NotDelegated)
+        );
+        assert_eq!(
+            check(delegated(vote, 10, u64::MAX), &stake_pool),
+            Err(DepositError::NotActive(StakeAccountStatus::Activating))
+        );
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            check(delegated(other, 5, u64::MAX), &stake_pool),
+            Err(DepositError::ValidatorNotInPool(other))
+        );
+        assert_eq!(
+            check(delegated(removing, 5, u64::MAX), &stake_pool),
+            Err(DepositError::ValidatorRemoving(removing))
+        );
+
+        let StakeStateV2::Stake(mut locked_meta, stake, flags) = delegated(vote, 5, u64::MAX)
+        else {
+            unreachable!();
+        };
+        locked_meta.lockup.epoch = 20;
+        assert_eq!(
+            check(StakeStateV2::Stake(locked_meta, stake, flags), &stake_pool),
+            Err(DepositError::Locked)
+        );
+
+        stake_pool.preferred_deposit_validator_vote_address = Some(removing);
+        assert_eq!(
+            check(delegated(vote, 5, u64::MAX), &stake_pool),
+            Err(DepositError::NotPreferredValidator(removing))
+        );
+    }
+
+    fn encode(address: &Pubkey, account: &Account) -> Value {
+        json!(encode_ui_account(
+            address,
+            account,
+            UiAccountEncoding::Base64,
+            None,
+            None
+        ))
+    }
+
+    fn stake_account(stake_state: &StakeStateV2) -> Account {
+        Account {
+            lamports: STAKE,
+            data: bincode::serialize(stake_state).unwrap(),
+            owner: solana_stake_interface::program::id(),
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn finds_stake_accounts_by_staker_and_withdrawer() {
+        let context = context(10);
+        let vote = Pubkey::new_unique();
+        let both = Pubkey::new_unique();
+        let staker_only = Pubkey::new_unique();
+        let uninitialized = Pubkey::new_unique();
+        let active = stake_account(&delegated(vote, 5, u64::MAX));
+        let initialized = stake_account(&StakeStateV2::Initialized(meta(
+            Pubkey::new_unique(),
+            Lockup::default(),
+        )));
+        let program_accounts = |accounts: &[(Pubkey, &Account)]| {
+            Value::Array(
+                accounts
+                    .iter()
+                    .map(|(address, account)| {
+                        json!({ "pubkey": address.to_string(), "account": encode(address, account) })
+                    })
+                    .collect(),
+            )
+        };
+        let sysvar = |data: Vec<u8>| Account {
+            lamports: 1,
+            data,
+            owner: sysvar::id(),
+            ..Account::default()
+        };
+        let clock = sysvar(bincode::serialize(&context.clock).unwrap());
+        let stake_history = sysvar(bincode::serialize(&context.stake_history).unwrap());
+        let feature = sysvar(bincode::serialize(&Some(0u64)).unwrap());
+
+        let mut mocks = MocksMap::default();
+        mocks.insert(
+            RpcRequest::GetProgramAccounts,
+            program_accounts(&[
+                (both, &active),
+                (staker_only, &initialized),
+                (uninitialized, &stake_account(&StakeStateV2::Uninitialized)),
+            ]),
+        );
+        mocks.insert(
+            RpcRequest::GetProgramAccounts,
+            program_accounts(&[(both, &active)]),
+        );
+        let epoch_schedule = sysvar(bincode::serialize(&EpochSchedule::default()).unwrap());
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            json!({ "context": { "slot": 1 }, "value": [
+                encode(&sysvar::clock::id(), &clock),
+                encode(&sysvar::stake_history::id(), &stake_history),
+                encode(&sysvar::epoch_schedule::id(), &epoch_schedule),
+                encode(&REDUCE_STAKE_WARMUP_COOLDOWN, &feature),
+            ] }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks_map("succeeds", mocks);
+
+        let mut expected = vec![
+            (both, StakeAccountStatus::Active),
+            (staker_only, StakeAccountStatus::Initialized),
+        ];
+        expected.sort_by_key(|(address, _)| *address);
+        let found = block_on(find_stake_accounts(&rpc_client, &Pubkey::new_unique())).unwrap();
+        assert_eq!(
+            found
+                .iter()
+                .map(|info| (info.address, info.status))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(found[0].lamports, STAKE);
+    }
+}
@@ -0,0 +1,296 @@
+//! Planning of the epoch update of a stake pool.
+//!
+//! `UpdateValidatorListBalance` walks the validator list from `start_index`
+//! and pairs every entry with the next two stake accounts, so a batch always
+//! covers consecutive entries. Skipping up-to-date validators therefore splits
+//! the list into runs of stale entries, each sent in chunks of
+//! `MAX_VALIDATORS_TO_UPDATE`.
+
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_stake_pool::state::{StakePool, ValidatorList};
+
+use crate::{
+    address::{MAX_VALIDATORS_TO_UPDATE, find_withdraw_authority_program_address},
+    client::PoolSnapshot,
+    sdk::update_stake_pool::{
+        cleanup_removed_validator_entries, update_stake_pool_balance_with_pool,
+        update_validator_list_balance,
+    },
+};
+
+/// How to update the pool
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct UpdateOptions {
+    /// Update every validator and the pool, even if already updated this epoch
+    pub force: bool,
+    /// Do not merge transient stakes into the validator stakes or the reserve
+    pub no_merge: bool,
+}
+
+/// What one batch of the update does
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateStep {
+    /// `UpdateValidatorListBalance` for the entries `start_index..end_index`
+    ValidatorList {
+        start_index: usize,
+        end_index: usize,
+    },
+    /// `UpdateStakePoolBalance`, once every validator is updated
+    StakePoolBalance,
+    /// `CleanupRemovedValidatorEntries`, after the pool balance
+    Cleanup,
+}
+
+/// One transaction of the update
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpdateBatch {
+    pub step: UpdateStep,
+    pub instructions: Vec<Instruction>,
+}
+
+/// Plans the update of a pool for `epoch`, in the order the batches must
+/// land. The `ValidatorList` batches are independent of each other, the
+/// `StakePoolBalance` batch requires all of them.
+///
+/// Returns no batch if the pool is already updated for `epoch`, unless forced.
+pub fn plan_update(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+    epoch: u64,
+    options: UpdateOptions,
+) -> Vec<UpdateBatch> {
+    let stale: Vec<bool> = validator_list
+        .validators
+        .iter()
+        .map(|validator| options.force || u64::from(validator.last_update_epoch) < epoch)
+        .collect();
+    if !options.force && stake_pool.last_update_epoch >= epoch && !stale.contains(&true) {
+        return vec![];
+    }
+
+    let withdraw_authority =
+        find_withdraw_authority_program_address(program_id, stake_pool_address).0;
+    let mut batches: Vec<UpdateBatch> = stale_runs(&stale)
+        .into_iter()
+        .map(|(start_index, end_index)| {
+            let vote_accounts: Vec<Pubkey> = validator_list.validators[start_index..end_index]
+                .iter()
+                .map(|validator| validator.vote_account_address)
+                .collect();
+            UpdateBatch {
+                step: UpdateStep::ValidatorList {
+                    start_index,
+                    end_index,
+                },
+                instructions: vec![update_validator_list_balance(
+                    program_id,
+                    stake_pool_address,
+                    &withdraw_authority,
+                    &stake_pool.validator_list,
+                    &stake_pool.reserve_stake,
+                    validator_list,
+                    &vote_accounts,
+                    start_index as u32,
+                    options.no_merge,
+                )],
+            }
+        })
+        .collect();
+    batches.push(UpdateBatch {
+        step: UpdateStep::StakePoolBalance,
+        instructions: vec![update_stake_pool_balance_with_pool(
+            program_id,
+            stake_pool,
+            stake_pool_address,
+        )],
+    });
+    batches.push(UpdateBatch {
+        step: UpdateStep::Cleanup,
+        instructions: vec![cleanup_removed_validator_entries(
+            program_id,
+            stake_pool_address,
+            &stake_pool.validator_list,
+        )],
+    });
+    batches
+}
+
+impl PoolSnapshot {
+    /// Plans the update of the snapshotted pool, see [`plan_update`]
+    pub fn plan_update(
+        &self,
+        program_id: &Pubkey,
+        epoch: u64,
+        options: UpdateOptions,
+    ) -> Vec<UpdateBatch> {
+        plan_update(
+            program_id,
+            &self.stake_pool_address,
+            &self.stake_pool,
+            &self.validator_list,
+            epoch,
+            options,
+        )
+    }
+}
+
+/// Ranges of consecutive stale entries, at most `MAX_VALIDATORS_TO_UPDATE` long
+fn stale_runs(stale: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut index = 0;
+    while index < stale.len() {
+        if !stale[index] {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < stale.len() && stale[index] && index - start < MAX_VALIDATORS_TO_UPDATE {
+            index += 1;
+        }
+        runs.push((start, index));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_stake_pool::state::ValidatorStakeInfo;
+
+    use super::*;
+    use crate::decode::{DecodedInstruction, decode_instruction};
+
+    fn pool(last_update_epochs: &[u64], pool_epoch: u64) -> (StakePool, ValidatorList) {
+        let stake_pool = StakePool {
+            last_update_epoch: pool_epoch,
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let mut validator_list = ValidatorList::new(last_update_epochs.len() as u32);
+        validator_list.validators = last_update_epochs
+            .iter()
+            .map(|epoch| ValidatorStakeInfo {
+                last_update_epoch: (*epoch).into(),
+                vote_account_address: Pubkey::new_unique(),
+                ..ValidatorStakeInfo::default()
+            })
+            .collect();
+        (stake_pool, validator_list)
+    }
+
+    fn steps(batches: &[UpdateBatch]) -> Vec<UpdateStep> {
+        batches.iter().map(|batch| batch.step).collect()
+    }
+
+    #[test]
+    fn stale_runs_are_chunked() {
+        assert_eq!(stale_runs(&[]), vec![]);
+        assert_eq!(
+            stale_runs(&[true, true, false, true, false, false, true]),
+            vec![(0, 2), (3, 4), (6, 7)]
+        );
+        assert_eq!(stale_runs(&[true; 12]), vec![(0, 5), (5, 10), (10, 12)]);
+        assert_eq!(
+            stale_runs(&[false, true, true, true, true, true, true]),
+            vec![(1, 6), (6, 7)]
+        );
+    }
+
+    #[test]
+    fn skips_up_to_date_validators() {
+        let program_id = spl_stake_pool::id();
+        let stake_pool_address = Pubkey::new_unique();
+        let (stake_pool, validator_list) = pool(&[9, 10, 9, 9, 9, 9, 9, 9], 9);
+        let batches = plan_update(
+            &program_id,
+            &stake_pool_address,
+            &stake_pool,
+            &validator_list,
+            10,
+            UpdateOptions {
+                no_merge: true,
+                ..UpdateOptions::default()
+            },
+        );
+        assert_eq!(
+            steps(&batches),
+            vec![
+                UpdateStep::ValidatorList {
+                    start_index: 0,
+                    end_index: 1
+                },
+                UpdateStep::ValidatorList {
+                    start_index: 2,
+                    end_index: 7
+                },
+                UpdateStep::ValidatorList {
+                    start_index: 7,
+                    end_index: 8
+                },
+                UpdateStep::StakePoolBalance,
+                UpdateStep::Cleanup,
+            ]
+        );
+
+        let decoded = decode_instruction(&program_id, &batches[1].instructions[0]).unwrap();
+        let DecodedInstruction::UpdateValidatorListBalance {
+            start_index,
+            no_merge,
+            stake_accounts,
+            ..
+        } = decoded
+        else {
+            panic!("unexpected {decoded:?}");
+        };
+        assert_eq!((start_index, no_merge), (2, true));
+        assert_eq!(stake_accounts.len(), 5);
+    }
+
+    #[test]
+    fn up_to_date_pool() {
+        let program_id = spl_stake_pool::id();
+        let stake_pool_address = Pubkey::new_unique();
+        let (stake_pool, validator_list) = pool(&[10, 10], 10);
+        let plan = |force| {
+            plan_update(
+                &program_id,
+                &stake_pool_address,
+                &stake_pool,
+                &validator_list,
+                10,
+                UpdateOptions {
+                    force,
+                    ..UpdateOptions::default()
+                },
+            )
+        };
+        assert!(plan(false).is_empty());
+        assert_eq!(
+            steps(&plan(true)),
+            vec![
+                UpdateStep::ValidatorList {
+                    start_index: 0,
+                    end_index: 2
+                },
+                UpdateStep::StakePoolBalance,
+                UpdateStep::Cleanup,
+            ]
+        );
+
+        // Validators updated, but the pool balance is not
+        let (stake_pool, validator_list) = pool(&[10, 10], 9);
+        assert_eq!(
+            steps(&plan_update(
+                &program_id,
+                &stake_pool_address,
+                &stake_pool,
+                &validator_list,
+                10,
+                UpdateOptions::default(),
+            )),
+            vec![UpdateStep::StakePoolBalance, UpdateStep::Cleanup]
+        );
+    }
+}